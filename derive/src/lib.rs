@@ -0,0 +1,377 @@
+//! Derive macros for the [`typed-index-collections`] crate.
+//!
+//! This crate is not intended to be used directly,
+//! enable the `derive` feature of the [`typed-index-collections`] crate instead.
+//!
+//! [`typed-index-collections`]: https://crates.io/crates/typed-index-collections
+
+#![warn(
+    clippy::all,
+    rust_2018_idioms,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    single_use_lifetimes,
+    missing_docs,
+    trivial_casts,
+    unused_import_braces,
+    unused_qualifications,
+    unused_results
+)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields, Index, Lit, Member,
+    Meta, NestedMeta, Type,
+};
+
+/// Derives the `TiKey` trait for a single-field struct
+/// so it can be used as a `TiSlice` and `TiVec` key.
+///
+/// The field type is used as the backing integer.
+/// It can be `u8`, `u16`, `u32`, `usize` or any other type implementing `TiKey`.
+/// `usize` indices greater than the backing integer maximum
+/// cause a panic when converted to the key.
+///
+/// The derive can be configured with the `#[ti_index(...)]` attribute:
+/// - `prefix = "bb"` - Implements `Debug` and `Display` traits
+///   that format the key as the prefix followed by the index, such as `bb12`.
+/// - `serde` - Implements transparent `Serialize` and `Deserialize` traits
+///   that use the backing integer representation.
+///   Requires the `serde` crate to be a dependency of the current crate.
+/// - `unchecked` - Checks `usize` index overflows only in debug builds.
+///   Only supported for `u8`, `u16` and `u32` fields.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::{TiIndex, TiVec};
+///
+/// #[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd, TiIndex)]
+/// #[ti_index(prefix = "bb")]
+/// struct BlockId(u32);
+///
+/// let mut blocks: TiVec<BlockId, &str> = TiVec::new();
+/// let entry = blocks.push_and_get_key("entry");
+/// let exit = blocks.push_and_get_key("exit");
+/// assert_eq!(blocks[exit], "exit");
+/// assert_eq!(format!("{:?}", entry), "bb0");
+/// assert_eq!(format!("{:?}", blocks), r#"{bb0: "entry", bb1: "exit"}"#);
+/// ```
+///
+/// Using `unchecked` with other field types fails to compile:
+///
+/// ```compile_fail
+/// use typed_index_collections::TiIndex;
+///
+/// #[derive(Clone, Copy, TiIndex)]
+/// #[ti_index(unchecked)]
+/// struct NodeId(usize);
+/// ```
+#[proc_macro_derive(TiIndex, attributes(ti_index))]
+pub fn derive_ti_index(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_ti_index(&input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Derives the `TiEnum` and `TiKey` traits for a fieldless enum
+/// so it can be used as a `TiEnumMap`, `TiSlice` and `TiVec` key.
+///
+/// Variant indices are assigned in declaration order starting from zero,
+/// explicit discriminants are ignored.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::{TiEnum, TiEnumMap};
+///
+/// #[derive(Clone, Copy, Debug, Eq, PartialEq, TiEnum)]
+/// enum Color {
+///     Red,
+///     Green,
+///     Blue,
+/// }
+///
+/// let mut counts: TiEnumMap<Color, u32> = TiEnumMap::default();
+/// counts[Color::Green] += 1;
+/// assert_eq!(Color::COUNT, 3);
+/// assert_eq!(counts.iter_enumerated().nth(1), Some((Color::Green, &1)));
+/// ```
+#[proc_macro_derive(TiEnum)]
+pub fn derive_ti_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_ti_enum(&input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+#[derive(Default)]
+struct Options {
+    prefix: Option<String>,
+    serde: bool,
+    unchecked: Option<Span>,
+}
+
+impl Options {
+    fn from_input(input: &DeriveInput) -> syn::Result<Self> {
+        let mut options = Self::default();
+        for attr in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("ti_index"))
+        {
+            let list = match attr.parse_meta()? {
+                Meta::List(list) => list,
+                meta => return Err(Error::new(meta.span(), "expected `#[ti_index(...)]`")),
+            };
+            for nested in list.nested {
+                match nested {
+                    NestedMeta::Meta(Meta::NameValue(ref value))
+                        if value.path.is_ident("prefix") =>
+                    {
+                        match value.lit {
+                            Lit::Str(ref prefix) => options.prefix = Some(prefix.value()),
+                            ref lit => {
+                                return Err(Error::new(lit.span(), "expected string literal"))
+                            }
+                        }
+                    }
+                    NestedMeta::Meta(Meta::Path(ref path)) if path.is_ident("serde") => {
+                        options.serde = true;
+                    }
+                    NestedMeta::Meta(Meta::Path(ref path)) if path.is_ident("unchecked") => {
+                        options.unchecked = Some(path.span());
+                    }
+                    nested => {
+                        return Err(Error::new(
+                            nested.span(),
+                            "unknown `ti_index` option, expected `prefix`, `serde` or `unchecked`",
+                        ))
+                    }
+                }
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn expand_ti_index(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let options = Options::from_input(input)?;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "`TiIndex` can not be derived for generic structs",
+        ));
+    }
+    let fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "`TiIndex` can only be derived for structs with a single field",
+            ))
+        }
+    };
+    let field = match *fields {
+        Fields::Named(ref fields) if fields.named.len() == 1 => &fields.named[0],
+        Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => &fields.unnamed[0],
+        _ => {
+            return Err(Error::new(
+                fields.span(),
+                "`TiIndex` can only be derived for structs with a single field",
+            ))
+        }
+    };
+    let member = match field.ident {
+        Some(ref ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(Index::from(0)),
+    };
+    let name = &input.ident;
+    let ty = &field.ty;
+    let construct = match field.ident {
+        Some(ref ident) => quote!(Self { #ident: value }),
+        None => quote!(Self(value)),
+    };
+
+    if let (Some(span), false) = (options.unchecked, is_primitive(ty)) {
+        return Err(Error::new(
+            span,
+            "`unchecked` is only supported for `u8`, `u16` and `u32` fields",
+        ));
+    }
+
+    let key_impl = if is_primitive(ty) {
+        let check = if options.unchecked.is_some() {
+            quote!(debug_assert!)
+        } else {
+            quote!(assert!)
+        };
+        quote! {
+            impl ::typed_index_collections::TiKey for #name {
+                const MAX: usize = <#ty>::MAX as usize;
+
+                #[inline]
+                fn from_usize(index: usize) -> Self {
+                    #check(
+                        index <= <Self as ::typed_index_collections::TiKey>::MAX,
+                        "index {} is out of range for `{}`",
+                        index,
+                        stringify!(#name),
+                    );
+                    let value = index as #ty;
+                    #construct
+                }
+
+                #[inline]
+                fn to_usize(self) -> usize {
+                    self.#member as usize
+                }
+            }
+        }
+    } else {
+        quote! {
+            impl ::typed_index_collections::TiKey for #name {
+                const MAX: usize = <#ty as ::typed_index_collections::TiKey>::MAX;
+
+                #[inline]
+                fn from_usize(index: usize) -> Self {
+                    let value = <#ty as ::typed_index_collections::TiKey>::from_usize(index);
+                    #construct
+                }
+
+                #[inline]
+                fn to_usize(self) -> usize {
+                    <#ty as ::typed_index_collections::TiKey>::to_usize(self.#member)
+                }
+            }
+        }
+    };
+
+    let fmt_impl = match options.prefix {
+        Some(ref prefix) => quote! {
+            impl ::core::fmt::Debug for #name {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    ::core::fmt::Display::fmt(self, f)
+                }
+            }
+
+            impl ::core::fmt::Display for #name {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    f.write_str(#prefix)?;
+                    ::core::fmt::Display::fmt(&self.#member, f)
+                }
+            }
+        },
+        None => quote!(),
+    };
+
+    let serde_impl = if options.serde {
+        quote! {
+            impl ::serde::Serialize for #name {
+                fn serialize<S>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+                where
+                    S: ::serde::Serializer,
+                {
+                    ::serde::Serialize::serialize(&self.#member, serializer)
+                }
+            }
+
+            impl<'de> ::serde::Deserialize<'de> for #name {
+                fn deserialize<D>(deserializer: D) -> ::core::result::Result<Self, D::Error>
+                where
+                    D: ::serde::Deserializer<'de>,
+                {
+                    let value = <#ty as ::serde::Deserialize<'de>>::deserialize(deserializer)?;
+                    ::core::result::Result::Ok(#construct)
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    Ok(quote! {
+        #key_impl
+        #fmt_impl
+        #serde_impl
+    })
+}
+
+fn expand_ti_enum(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "`TiEnum` can not be derived for generic enums",
+        ));
+    }
+    let variants = match input.data {
+        Data::Enum(ref data) => &data.variants,
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "`TiEnum` can only be derived for fieldless enums",
+            ))
+        }
+    };
+    if variants.is_empty() {
+        return Err(Error::new(
+            input.span(),
+            "`TiEnum` can not be derived for enums without variants",
+        ));
+    }
+    if let Some(variant) = variants
+        .iter()
+        .find(|variant| !matches!(variant.fields, Fields::Unit))
+    {
+        return Err(Error::new(
+            variant.span(),
+            "`TiEnum` can only be derived for fieldless enums",
+        ));
+    }
+
+    let name = &input.ident;
+    let count = variants.len();
+    let idents: Vec<_> = variants.iter().map(|variant| &variant.ident).collect();
+    let indices: Vec<_> = (0..count).collect();
+
+    Ok(quote! {
+        impl ::typed_index_collections::TiKey for #name {
+            const MAX: usize = #count - 1;
+
+            #[inline]
+            fn from_usize(index: usize) -> Self {
+                match index {
+                    #(#indices => Self::#idents,)*
+                    _ => panic!(
+                        "index {} is out of range for the enum with {} variants",
+                        index,
+                        #count,
+                    ),
+                }
+            }
+
+            #[inline]
+            fn to_usize(self) -> usize {
+                match self {
+                    #(Self::#idents => #indices,)*
+                }
+            }
+        }
+
+        impl ::typed_index_collections::TiEnum for #name {
+            const COUNT: usize = #count;
+        }
+    })
+}
+
+fn is_primitive(ty: &Type) -> bool {
+    match *ty {
+        Type::Path(ref path) if path.qself.is_none() => ["u8", "u16", "u32"]
+            .iter()
+            .any(|name| path.path.is_ident(name)),
+        _ => false,
+    }
+}
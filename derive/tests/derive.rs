@@ -0,0 +1,107 @@
+use serde_test::{assert_tokens, Token};
+use typed_index_collections::{TiEnum, TiEnumMap, TiIndex, TiKey, TiSlice, TiVec};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TiIndex)]
+struct SmallId(u8);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TiIndex)]
+struct NamedId {
+    index: u16,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TiIndex)]
+struct UsizeId(usize);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TiIndex)]
+struct NestedId(SmallId);
+
+#[derive(Clone, Copy, Eq, PartialEq, TiIndex)]
+#[ti_index(prefix = "bb", serde)]
+struct BlockId(u32);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TiIndex)]
+#[ti_index(unchecked)]
+struct UncheckedId(u8);
+
+#[test]
+fn test_key_conversions() {
+    assert_eq!(SmallId::MAX, 255);
+    assert_eq!(SmallId::from_usize(3), SmallId(3));
+    assert_eq!(SmallId(3).to_usize(), 3);
+    assert_eq!(SmallId::checked_from_usize(256), None);
+
+    assert_eq!(NamedId::MAX, 65535);
+    assert_eq!(NamedId::from_usize(7), NamedId { index: 7 });
+    assert_eq!(NamedId { index: 7 }.to_usize(), 7);
+
+    assert_eq!(UsizeId::MAX, usize::MAX);
+    assert_eq!(UsizeId::from_usize(usize::MAX), UsizeId(usize::MAX));
+
+    assert_eq!(NestedId::MAX, 255);
+    assert_eq!(NestedId::from_usize(4), NestedId(SmallId(4)));
+    assert_eq!(NestedId(SmallId(4)).to_usize(), 4);
+
+    assert_eq!(BlockId::MAX, u32::MAX as usize);
+    assert_eq!(UncheckedId::from_usize(5), UncheckedId(5));
+}
+
+#[test]
+#[should_panic(expected = "index 256 is out of range for `SmallId`")]
+fn test_key_overflow() {
+    let _ = SmallId::from_usize(256);
+}
+
+#[test]
+#[should_panic(expected = "out of range")]
+fn test_push_overflow() {
+    let mut vec: TiVec<SmallId, ()> = TiVec::new();
+    vec.resize(256, ());
+    let _ = vec.push_and_get_key(());
+}
+
+#[test]
+fn test_containers() {
+    let slice: &TiSlice<NamedId, u32> = TiSlice::from_ref(&[1, 2, 4]);
+    assert_eq!(slice[NamedId { index: 2 }], 4);
+    assert_eq!(slice.last_key(), Some(NamedId { index: 2 }));
+
+    let mut vec: TiVec<BlockId, &str> = TiVec::new();
+    assert_eq!(vec.push_and_get_key("entry"), BlockId(0));
+    assert_eq!(vec.push_and_get_key("exit"), BlockId(1));
+    assert_eq!(format!("{:?}", vec), r#"{bb0: "entry", bb1: "exit"}"#);
+}
+
+#[test]
+fn test_prefix() {
+    assert_eq!(format!("{:?}", BlockId(12)), "bb12");
+    assert_eq!(format!("{}", BlockId(12)), "bb12");
+}
+
+#[test]
+fn test_serde() {
+    assert_tokens(&BlockId(12), &[Token::U32(12)]);
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, TiEnum)]
+enum Axis {
+    X,
+    Y = 5,
+    Z,
+}
+
+#[test]
+fn test_enum() {
+    assert_eq!(Axis::COUNT, 3);
+    assert_eq!(<Axis as TiKey>::MAX, 2);
+    assert_eq!(Axis::from_index(1), Axis::Y);
+    assert_eq!(Axis::Z.to_index(), 2);
+    assert_eq!(Axis::checked_from_usize(3), None);
+
+    let map: TiEnumMap<Axis, char> = TiEnumMap::from_fn(|axis| match axis {
+        Axis::X => 'x',
+        Axis::Y => 'y',
+        Axis::Z => 'z',
+    });
+    assert_eq!(map[Axis::Y], 'y');
+    assert_eq!(format!("{:?}", map), "{X: 'x', Y: 'y', Z: 'z'}");
+}
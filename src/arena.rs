@@ -0,0 +1,900 @@
+use core::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::{self, FusedIterator},
+    marker::PhantomData,
+    mem, ops, slice,
+};
+
+use alloc::vec::{self, Vec};
+
+#[cfg(feature = "serde")]
+use serde::ser::{Serialize, SerializeSeq, SerializeTuple, Serializer};
+
+#[cfg(feature = "serde")]
+use serde::de::{Deserialize, Deserializer, Error};
+
+use crate::key::key_from_len;
+use crate::TiKey;
+
+/// A handle to a value stored in a [`TiArena`].
+///
+/// The handle pairs the index of type `K` with the generation of the arena slot.
+/// The generation changes every time the slot is vacated,
+/// so a handle to a removed value never refers to a value inserted later.
+///
+/// [`TiArena`]: struct.TiArena.html
+pub struct TiHandle<K> {
+    index: K,
+    generation: u32,
+}
+
+impl<K> TiHandle<K> {
+    /// Creates a handle from the index and the generation.
+    #[inline]
+    pub fn new(index: K, generation: u32) -> Self {
+        Self { index, generation }
+    }
+
+    /// Returns the index of the handle.
+    #[inline]
+    pub fn index(&self) -> K
+    where
+        K: Copy,
+    {
+        self.index
+    }
+
+    /// Returns the generation of the handle.
+    #[inline]
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Converts the handle into its index and generation.
+    #[inline]
+    pub fn into_parts(self) -> (K, u32) {
+        (self.index, self.generation)
+    }
+}
+
+impl<K: fmt::Debug> fmt::Debug for TiHandle<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}@{}", self.index, self.generation)
+    }
+}
+
+impl<K: Clone> Clone for TiHandle<K> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            index: self.index.clone(),
+            generation: self.generation,
+        }
+    }
+}
+
+impl<K: Copy> Copy for TiHandle<K> {}
+
+impl<K: PartialEq> PartialEq for TiHandle<K> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<K: Eq> Eq for TiHandle<K> {}
+
+impl<K: PartialOrd> PartialOrd for TiHandle<K> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.index.partial_cmp(&other.index) {
+            Some(Ordering::Equal) => self.generation.partial_cmp(&other.generation),
+            ordering => ordering,
+        }
+    }
+}
+
+impl<K: Ord> Ord for TiHandle<K> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.index
+            .cmp(&other.index)
+            .then(self.generation.cmp(&other.generation))
+    }
+}
+
+impl<K: Hash> Hash for TiHandle<K> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K: Serialize> Serialize for TiHandle<K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(2)?;
+        tuple.serialize_element(&self.index)?;
+        tuple.serialize_element(&self.generation)?;
+        tuple.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K: Deserialize<'de>> Deserialize<'de> for TiHandle<K> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <(K, u32)>::deserialize(deserializer)
+            .map(|(index, generation)| Self::new(index, generation))
+    }
+}
+
+/// A generational arena that returns [`TiHandle<K>`][`TiHandle`] handles on insertion.
+///
+/// Removing a value leaves a vacant slot that is reused by later insertions
+/// with a new generation.
+/// A slot is retired instead of reused once its generation reaches `u32::MAX`,
+/// so the arena never issues the same handle twice.
+/// Stale handles to removed values are rejected by [`get`], [`get_mut`] and [`remove`].
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::TiArena;
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct EntityId(usize);
+///
+/// let mut entities: TiArena<EntityId, &str> = TiArena::new();
+/// let player = entities.insert("player");
+/// assert_eq!(entities.remove(player), Some("player"));
+/// let enemy = entities.insert("enemy");
+/// assert_eq!(enemy.index(), player.index());
+/// assert_eq!(entities.get(player), None);
+/// assert_eq!(entities[enemy], "enemy");
+/// ```
+///
+/// [`TiHandle`]: struct.TiHandle.html
+/// [`get`]: #method.get
+/// [`get_mut`]: #method.get_mut
+/// [`remove`]: #method.remove
+pub struct TiArena<K, V> {
+    entries: Vec<Entry<V>>,
+    len: usize,
+    next_vacant: usize,
+
+    /// Ties the container to the key type `K`.
+    _marker: PhantomData<fn(K) -> K>,
+}
+
+#[derive(Clone)]
+struct Entry<V> {
+    generation: u32,
+    slot: Slot<V>,
+}
+
+#[derive(Clone)]
+enum Slot<V> {
+    Occupied(V),
+    /// Vacant slot with the index of the next vacant slot.
+    /// The last vacant slot points to the end of the entries.
+    /// Retired slots with the `RETIRED_GENERATION` are not linked.
+    Vacant(usize),
+}
+
+/// The generation of vacant slots that are never reused.
+const RETIRED_GENERATION: u32 = u32::MAX;
+
+impl<K, V> TiArena<K, V> {
+    /// Constructs a new, empty `TiArena<K, V>`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Constructs a new, empty `TiArena<K, V>` with the specified capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            len: 0,
+            next_vacant: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of values the arena can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    /// Reserves capacity for at least `additional` more values.
+    ///
+    /// Vacant slots are not taken into account.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.entries.reserve(additional)
+    }
+
+    /// Returns the number of stored values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the arena contains no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts a value into the arena and returns its handle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index can not be represented by the key type.
+    pub fn insert(&mut self, value: V) -> TiHandle<K>
+    where
+        K: TiKey,
+    {
+        let index = self.next_vacant;
+        let key = key_from_len(index);
+        let generation = if index == self.entries.len() {
+            self.entries.push(Entry {
+                generation: 0,
+                slot: Slot::Occupied(value),
+            });
+            self.next_vacant = index + 1;
+            0
+        } else {
+            let entry = &mut self.entries[index];
+            match mem::replace(&mut entry.slot, Slot::Occupied(value)) {
+                Slot::Vacant(next) => self.next_vacant = next,
+                Slot::Occupied(_) => unreachable!(),
+            }
+            entry.generation
+        };
+        self.len += 1;
+        TiHandle::new(key, generation)
+    }
+
+    #[inline]
+    fn get_raw(&self, index: usize, generation: u32) -> Option<&V> {
+        match self.entries.get(index) {
+            Some(Entry {
+                generation: entry_generation,
+                slot: Slot::Occupied(value),
+            }) if *entry_generation == generation => Some(value),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn get_raw_mut(&mut self, index: usize, generation: u32) -> Option<&mut V> {
+        match self.entries.get_mut(index) {
+            Some(Entry {
+                generation: entry_generation,
+                slot: Slot::Occupied(value),
+            }) if *entry_generation == generation => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the handle refers to a stored value.
+    #[inline]
+    pub fn contains(&self, handle: TiHandle<K>) -> bool
+    where
+        K: TiKey,
+    {
+        self.get(handle).is_some()
+    }
+
+    /// Returns a reference to the value with the given handle,
+    /// or [`None`] if the handle is stale.
+    #[inline]
+    pub fn get(&self, handle: TiHandle<K>) -> Option<&V>
+    where
+        K: TiKey,
+    {
+        let (index, generation) = handle.into_parts();
+        self.get_raw(index.to_usize(), generation)
+    }
+
+    /// Returns a mutable reference to the value with the given handle,
+    /// or [`None`] if the handle is stale.
+    #[inline]
+    pub fn get_mut(&mut self, handle: TiHandle<K>) -> Option<&mut V>
+    where
+        K: TiKey,
+    {
+        let (index, generation) = handle.into_parts();
+        self.get_raw_mut(index.to_usize(), generation)
+    }
+
+    /// Returns the handle of the value currently stored at `index`,
+    /// or [`None`] if the slot is vacant.
+    #[inline]
+    pub fn handle_of(&self, index: K) -> Option<TiHandle<K>>
+    where
+        K: TiKey,
+    {
+        let index = index.to_usize();
+        match self.entries.get(index) {
+            Some(Entry {
+                generation,
+                slot: Slot::Occupied(_),
+            }) => Some(TiHandle::new(K::from_usize(index), *generation)),
+            _ => None,
+        }
+    }
+
+    fn vacate(&mut self, index: usize) -> V {
+        let end = self.entries.len();
+        let entry = &mut self.entries[index];
+        let retired = entry.generation >= RETIRED_GENERATION - 1;
+        let next = if retired {
+            entry.generation = RETIRED_GENERATION;
+            end
+        } else {
+            entry.generation += 1;
+            self.next_vacant
+        };
+        let value = match mem::replace(&mut entry.slot, Slot::Vacant(next)) {
+            Slot::Occupied(value) => value,
+            Slot::Vacant(_) => unreachable!(),
+        };
+        if !retired {
+            self.next_vacant = index;
+        }
+        self.len -= 1;
+        value
+    }
+
+    /// Removes the value with the given handle and returns it,
+    /// or [`None`] if the handle is stale.
+    pub fn remove(&mut self, handle: TiHandle<K>) -> Option<V>
+    where
+        K: TiKey,
+    {
+        let (index, generation) = handle.into_parts();
+        let index = index.to_usize();
+        if self.get_raw(index, generation).is_some() {
+            Some(self.vacate(index))
+        } else {
+            None
+        }
+    }
+
+    /// Retains only the values specified by the predicate.
+    ///
+    /// Handles of removed values become stale.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        K: TiKey,
+        F: FnMut(TiHandle<K>, &mut V) -> bool,
+    {
+        for index in 0..self.entries.len() {
+            let entry = &mut self.entries[index];
+            let keep = match entry.slot {
+                Slot::Occupied(ref mut value) => {
+                    f(TiHandle::new(K::from_usize(index), entry.generation), value)
+                }
+                Slot::Vacant(_) => true,
+            };
+            if !keep {
+                drop(self.vacate(index));
+            }
+        }
+    }
+
+    /// Removes all values.
+    ///
+    /// Slots are kept, so handles of removed values become stale.
+    pub fn clear(&mut self) {
+        for index in (0..self.entries.len()).rev() {
+            if let Slot::Occupied(_) = self.entries[index].slot {
+                drop(self.vacate(index));
+            }
+        }
+    }
+
+    /// Returns an iterator over stored values with their handles.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::{TiArena, TiHandle};
+    /// #[derive(Clone, Copy, Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut arena: TiArena<Id, char> = TiArena::new();
+    /// let a = arena.insert('a');
+    /// let b = arena.insert('b');
+    /// arena.remove(a);
+    /// let c = arena.insert('c');
+    /// let mut iterator = arena.iter_enumerated();
+    /// assert_eq!(iterator.next(), Some((c, &'c')));
+    /// assert_eq!(iterator.next(), Some((b, &'b')));
+    /// assert_eq!(iterator.next(), None);
+    /// assert_eq!(c, TiHandle::new(Id(0), 1));
+    /// ```
+    #[inline]
+    pub fn iter_enumerated(&self) -> TiArenaIter<'_, K, V> {
+        TiArenaIter {
+            entries: self.entries.iter().enumerate(),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over stored values with their handles,
+    /// with mutable references to the values.
+    #[inline]
+    pub fn iter_mut_enumerated(&mut self) -> TiArenaIterMut<'_, K, V> {
+        TiArenaIterMut {
+            entries: self.entries.iter_mut().enumerate(),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Converts the arena into iterator over stored values with their handles.
+    #[inline]
+    pub fn into_iter_enumerated(self) -> TiArenaIntoIter<K, V> {
+        TiArenaIntoIter {
+            entries: self.entries.into_iter().enumerate(),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn stale_handle(index: usize, generation: u32) -> ! {
+    panic!(
+        "handle with index {} and generation {} is stale in the arena",
+        index, generation
+    )
+}
+
+impl<K, V> fmt::Debug for TiArena<K, V>
+where
+    K: fmt::Debug + TiKey,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter_enumerated()).finish()
+    }
+}
+
+impl<K, V> ops::Index<TiHandle<K>> for TiArena<K, V>
+where
+    K: TiKey,
+{
+    type Output = V;
+
+    #[inline]
+    fn index(&self, handle: TiHandle<K>) -> &V {
+        let (index, generation) = handle.into_parts();
+        let index = index.to_usize();
+        self.get_raw(index, generation)
+            .unwrap_or_else(|| stale_handle(index, generation))
+    }
+}
+
+impl<K, V> ops::IndexMut<TiHandle<K>> for TiArena<K, V>
+where
+    K: TiKey,
+{
+    #[inline]
+    fn index_mut(&mut self, handle: TiHandle<K>) -> &mut V {
+        let (index, generation) = handle.into_parts();
+        let index = index.to_usize();
+        self.get_raw_mut(index, generation)
+            .unwrap_or_else(|| stale_handle(index, generation))
+    }
+}
+
+impl<K, V> Clone for TiArena<K, V>
+where
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            len: self.len,
+            next_vacant: self.next_vacant,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> Default for TiArena<K, V> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> IntoIterator for TiArena<K, V>
+where
+    K: TiKey,
+{
+    type Item = (TiHandle<K>, V);
+    type IntoIter = TiArenaIntoIter<K, V>;
+
+    #[inline]
+    fn into_iter(self) -> TiArenaIntoIter<K, V> {
+        self.into_iter_enumerated()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a TiArena<K, V>
+where
+    K: TiKey,
+{
+    type Item = (TiHandle<K>, &'a V);
+    type IntoIter = TiArenaIter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> TiArenaIter<'a, K, V> {
+        self.iter_enumerated()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut TiArena<K, V>
+where
+    K: TiKey,
+{
+    type Item = (TiHandle<K>, &'a mut V);
+    type IntoIter = TiArenaIterMut<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> TiArenaIterMut<'a, K, V> {
+        self.iter_mut_enumerated()
+    }
+}
+
+/// Serializes the arena as a sequence of `(generation, Option<value>)` slots,
+/// so deserialized arenas keep accepting previously issued handles.
+/// Deserialized arenas reuse vacant slots in ascending index order.
+#[cfg(feature = "serde")]
+impl<K, V: Serialize> Serialize for TiArena<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.entries.len()))?;
+        for entry in &self.entries {
+            let value = match entry.slot {
+                Slot::Occupied(ref value) => Some(value),
+                Slot::Vacant(_) => None,
+            };
+            seq.serialize_element(&(entry.generation, value))?;
+        }
+        seq.end()
+    }
+}
+
+#[cfg(any(feature = "serde-alloc", feature = "serde-std"))]
+impl<'de, K: TiKey, V: Deserialize<'de>> Deserialize<'de> for TiArena<K, V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let slots: Vec<(u32, Option<V>)> = Vec::deserialize(deserializer)?;
+        if !slots.is_empty() && slots.len() - 1 > K::MAX {
+            return Err(D::Error::custom(format_args!(
+                "arena with {} slots is out of range for the key type with maximum index {}",
+                slots.len(),
+                K::MAX
+            )));
+        }
+        let mut next_vacant = slots.len();
+        let mut len = 0;
+        let mut entries = Vec::with_capacity(slots.len());
+        for (index, (generation, value)) in slots.into_iter().enumerate() {
+            let slot = match value {
+                Some(_) if generation == RETIRED_GENERATION => {
+                    return Err(D::Error::custom(format_args!(
+                        "occupied arena slot {} has the retired generation",
+                        index
+                    )))
+                }
+                Some(value) => {
+                    len += 1;
+                    Slot::Occupied(value)
+                }
+                None => Slot::Vacant(0),
+            };
+            entries.push(Entry { generation, slot });
+        }
+        let end = entries.len();
+        for (index, entry) in entries.iter_mut().enumerate().rev() {
+            if let Slot::Vacant(ref mut next) = entry.slot {
+                if entry.generation == RETIRED_GENERATION {
+                    *next = end;
+                } else {
+                    *next = next_vacant;
+                    next_vacant = index;
+                }
+            }
+        }
+        Ok(Self {
+            entries,
+            len,
+            next_vacant,
+            _marker: PhantomData,
+        })
+    }
+}
+
+macro_rules! arena_iter {
+    (
+        $(#[$attr:meta])*
+        $name:ident<$($lt:lifetime,)? K, V>,
+        $entries:ty,
+        $item:ty,
+        |$entry:ident| $value:expr
+    ) => {
+        $(#[$attr])*
+        pub struct $name<$($lt,)? K, V> {
+            entries: iter::Enumerate<$entries>,
+            len: usize,
+            _marker: PhantomData<fn(K) -> K>,
+        }
+
+        #[allow(single_use_lifetimes)]
+        impl<$($lt,)? K, V> fmt::Debug for $name<$($lt,)? K, V> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($name)).field("len", &self.len).finish()
+            }
+        }
+
+        #[allow(single_use_lifetimes)]
+        impl<$($lt,)? K: TiKey, V> Iterator for $name<$($lt,)? K, V> {
+            type Item = (TiHandle<K>, $item);
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                for (index, $entry) in &mut self.entries {
+                    let generation = $entry.generation;
+                    if let Some(value) = $value {
+                        self.len -= 1;
+                        return Some((TiHandle::new(K::from_usize(index), generation), value));
+                    }
+                }
+                None
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (self.len, Some(self.len))
+            }
+        }
+
+        #[allow(single_use_lifetimes)]
+        impl<$($lt,)? K: TiKey, V> DoubleEndedIterator for $name<$($lt,)? K, V> {
+            #[inline]
+            fn next_back(&mut self) -> Option<Self::Item> {
+                while let Some((index, $entry)) = self.entries.next_back() {
+                    let generation = $entry.generation;
+                    if let Some(value) = $value {
+                        self.len -= 1;
+                        return Some((TiHandle::new(K::from_usize(index), generation), value));
+                    }
+                }
+                None
+            }
+        }
+
+        #[allow(single_use_lifetimes)]
+        impl<$($lt,)? K: TiKey, V> ExactSizeIterator for $name<$($lt,)? K, V> {}
+
+        #[allow(single_use_lifetimes)]
+        impl<$($lt,)? K: TiKey, V> FusedIterator for $name<$($lt,)? K, V> {}
+    };
+}
+
+arena_iter!(
+    /// An iterator over values of a [`TiArena`] with their handles.
+    ///
+    /// This struct is created by the [`TiArena::iter_enumerated`] method.
+    ///
+    /// [`TiArena`]: struct.TiArena.html
+    /// [`TiArena::iter_enumerated`]: struct.TiArena.html#method.iter_enumerated
+    TiArenaIter<'a, K, V>,
+    slice::Iter<'a, Entry<V>>,
+    &'a V,
+    |entry| match entry.slot {
+        Slot::Occupied(ref value) => Some(value),
+        Slot::Vacant(_) => None,
+    }
+);
+
+arena_iter!(
+    /// A mutable iterator over values of a [`TiArena`] with their handles.
+    ///
+    /// This struct is created by the [`TiArena::iter_mut_enumerated`] method.
+    ///
+    /// [`TiArena`]: struct.TiArena.html
+    /// [`TiArena::iter_mut_enumerated`]: struct.TiArena.html#method.iter_mut_enumerated
+    TiArenaIterMut<'a, K, V>,
+    slice::IterMut<'a, Entry<V>>,
+    &'a mut V,
+    |entry| match entry.slot {
+        Slot::Occupied(ref mut value) => Some(value),
+        Slot::Vacant(_) => None,
+    }
+);
+
+arena_iter!(
+    /// An iterator that moves values out of a [`TiArena`] with their handles.
+    ///
+    /// This struct is created by the [`TiArena::into_iter_enumerated`] method.
+    ///
+    /// [`TiArena`]: struct.TiArena.html
+    /// [`TiArena::into_iter_enumerated`]: struct.TiArena.html#method.into_iter_enumerated
+    TiArenaIntoIter<K, V>,
+    vec::IntoIter<Entry<V>>,
+    V,
+    |entry| match entry.slot {
+        Slot::Occupied(value) => Some(value),
+        Slot::Vacant(_) => None,
+    }
+);
+
+#[cfg(test)]
+mod test {
+    use crate::test::Id;
+    use crate::{TiArena, TiHandle};
+    use alloc::{format, vec::Vec};
+
+    #[test]
+    fn stale_handles() {
+        let mut arena: TiArena<Id, u32> = TiArena::with_capacity(2);
+        let first = arena.insert(1);
+        let second = arena.insert(2);
+        assert_eq!(arena.remove(first), Some(1));
+        assert_eq!(arena.remove(first), None);
+        let third = arena.insert(3);
+        assert_eq!(third, TiHandle::new(Id::from(0), 1));
+        assert_eq!(arena.get(first), None);
+        assert!(!arena.contains(first));
+        assert_eq!(arena.handle_of(Id::from(0)), Some(third));
+        arena[second] += 10;
+        assert_eq!(arena.get(second), Some(&12));
+        assert_eq!(format!("{:?}", arena), "{Id(0)@1: 3, Id(1)@0: 12}");
+        arena.clear();
+        assert!(arena.is_empty());
+        assert_eq!(arena.get(third), None);
+        assert_eq!(arena.insert(4), TiHandle::new(Id::from(0), 2));
+    }
+
+    #[test]
+    fn retain_and_iterate() {
+        let mut arena: TiArena<Id, u32> = TiArena::new();
+        let handles: Vec<_> = (0..4).map(|value| arena.insert(value)).collect();
+        arena.retain(|_, value| *value % 2 == 1);
+        assert_eq!(arena.len(), 2);
+        for (_, value) in &mut arena {
+            *value *= 10;
+        }
+        let mut iter = arena.iter_enumerated();
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.next_back(), Some((handles[3], &30)));
+        assert_eq!(
+            arena.into_iter().collect::<Vec<_>>(),
+            [(handles[1], 10), (handles[3], 30)]
+        );
+    }
+
+    #[test]
+    fn retire_exhausted_slots() {
+        let mut arena: TiArena<Id, u32> = TiArena::new();
+        let _ = arena.insert(0);
+        arena.entries[0].generation = u32::MAX - 2;
+        let first = TiHandle::new(Id::from(0), u32::MAX - 2);
+        assert_eq!(arena.remove(first), Some(0));
+        let second = arena.insert(1);
+        assert_eq!(second, TiHandle::new(Id::from(0), u32::MAX - 1));
+        assert_eq!(arena.remove(second), Some(1));
+        let third = arena.insert(2);
+        assert_eq!(third, TiHandle::new(Id::from(1), 0));
+        assert_eq!(arena.get(TiHandle::new(Id::from(0), u32::MAX)), None);
+        assert_eq!(arena.handle_of(Id::from(0)), None);
+        arena.clear();
+        assert_eq!(arena.insert(3), TiHandle::new(Id::from(1), 1));
+        assert_eq!(arena.insert(4), TiHandle::new(Id::from(2), 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "handle with index 0 and generation 0 is stale in the arena")]
+    fn index_stale() {
+        let mut arena: TiArena<Id, u32> = TiArena::new();
+        let handle = arena.insert(0);
+        let _ = arena.remove(handle);
+        let _ = arena[handle];
+    }
+
+    #[cfg(any(feature = "serde-alloc", feature = "serde-std"))]
+    fn slot_tokens() -> Vec<serde_test::Token> {
+        use serde_test::Token;
+
+        let mut tokens = alloc::vec![Token::Seq { len: Some(4) }];
+        for &(generation, value) in &[(0, Some(10)), (1, None), (0, Some(30)), (1, None)] {
+            tokens.extend_from_slice(&[Token::Tuple { len: 2 }, Token::U32(generation)]);
+            match value {
+                Some(value) => tokens.extend_from_slice(&[Token::Some, Token::U32(value)]),
+                None => tokens.push(Token::None),
+            }
+            tokens.push(Token::TupleEnd);
+        }
+        tokens.push(Token::SeqEnd);
+        tokens
+    }
+
+    #[cfg(any(feature = "serde-alloc", feature = "serde-std"))]
+    #[test]
+    fn serde_round_trip() {
+        use serde::de::{Deserialize, Deserializer};
+        use serde_test::{assert_de_tokens, assert_ser_tokens};
+
+        /// Compares arenas by their handles and the handles of the next insertions.
+        #[derive(Debug)]
+        struct Reused(TiArena<Id, u32>);
+
+        impl PartialEq for Reused {
+            fn eq(&self, other: &Self) -> bool {
+                let (mut a, mut b) = (self.0.clone(), other.0.clone());
+                a.iter_enumerated().eq(b.iter_enumerated())
+                    && (0..3).all(|value| a.insert(value) == b.insert(value))
+            }
+        }
+
+        impl<'de> Deserialize<'de> for Reused {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                TiArena::deserialize(deserializer).map(Self)
+            }
+        }
+
+        let mut arena: TiArena<Id, u32> = TiArena::new();
+        let handles: Vec<_> = [10, 20, 30, 40]
+            .iter()
+            .map(|&value| arena.insert(value))
+            .collect();
+        assert_eq!(arena.remove(handles[1]), Some(20));
+        assert_eq!(arena.remove(handles[3]), Some(40));
+        assert_ser_tokens(&arena, &slot_tokens());
+
+        let mut expected: TiArena<Id, u32> = TiArena::new();
+        let _ = [10, 20, 30, 40]
+            .iter()
+            .map(|&value| expected.insert(value))
+            .count();
+        assert_eq!(expected.remove(handles[3]), Some(40));
+        assert_eq!(expected.remove(handles[1]), Some(20));
+        assert_eq!(expected[handles[2]], 30);
+        let mut reused = expected.clone();
+        assert_eq!(reused.insert(0), TiHandle::new(Id::from(1), 1));
+        assert_eq!(reused.insert(0), TiHandle::new(Id::from(3), 1));
+        assert_de_tokens(&Reused(expected), &slot_tokens());
+    }
+
+    #[cfg(any(feature = "serde-alloc", feature = "serde-std"))]
+    #[test]
+    fn deserialize_retired_occupied_slot() {
+        use serde_test::{assert_de_tokens_error, Token};
+
+        assert_de_tokens_error::<TiArena<Id, u32>>(
+            &[
+                Token::Seq { len: Some(1) },
+                Token::Tuple { len: 2 },
+                Token::U32(u32::MAX),
+                Token::Some,
+                Token::U32(10),
+                Token::TupleEnd,
+                Token::SeqEnd,
+            ],
+            "occupied arena slot 0 has the retired generation",
+        );
+    }
+}
@@ -0,0 +1,578 @@
+use core::{
+    array,
+    borrow::{Borrow, BorrowMut},
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops, slice,
+};
+
+#[cfg(any(feature = "alloc", feature = "std"))]
+use core::convert::TryFrom;
+
+#[cfg(any(feature = "alloc", feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{
+    de::{Deserialize, Deserializer, Error, SeqAccess, Visitor},
+    ser::{Serialize, SerializeTuple, Serializer},
+};
+
+use crate::key::assert_keys_in_range;
+use crate::{TiEnumerated, TiKey, TiSlice};
+
+#[cfg(any(feature = "alloc", feature = "std"))]
+use crate::TiVec;
+
+/// A fixed-size array type
+/// that only accepts keys of the type `K`.
+///
+/// `TiArray<K, V, N>` is a wrapper around Rust primitive type [`[V; N]`][`array`].
+/// It dereferences to [`TiSlice<K, V>`][`TiSlice`],
+/// so all slice methods are available with `K` used for element indices.
+/// Unlike [`TiVec`], it does not require memory allocation.
+///
+/// `TiArray<K, V, N>` can be converted to [`[V; N]`][`array`] and back
+/// using [`From`] and [`Into`]
+/// and to [`TiVec<K, V>`][`TiVec`] and back using [`From`] and [`TryFrom`].
+///
+/// Added methods:
+/// - [`from_ref`] - Converts a [`&[V; N]`][`array`] into a `&TiArray<K, V, N>`.
+/// - [`from_mut`] - Converts a [`&mut [V; N]`][`array`] into a `&mut TiArray<K, V, N>`.
+/// - [`from_fn`] - Creates an array where each element is produced by calling a function
+///   with its index of type `K`.
+/// - [`map`] - Returns an array of the same size with a function applied to each element.
+/// - [`map_enumerated`] - Returns an array of the same size with a function
+///   applied to each element and its index of type `K`.
+/// - [`into_iter_enumerated`] - Converts the array into iterator over all key-value pairs
+///   with `K` used for iteration indices.
+///   It acts like `self.into_iter().enumerate()`,
+///   but use `K` instead of `usize` for iteration indices.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::{TiArray, TiKey};
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, From, Into, Eq, PartialEq)]
+/// struct RegId(usize);
+///
+/// let names: TiArray<RegId, &str, 3> = TiArray::from([
+///     "rax", "rbx", "rcx",
+/// ]);
+/// let ids = names.map_enumerated(|id, _| id);
+/// assert_eq!(names[RegId(1)], "rbx");
+/// assert_eq!(ids[RegId(2)], RegId(2));
+/// assert_eq!(names.position(|&name| name == "rcx"), Some(RegId(2)));
+/// ```
+///
+/// [`from_ref`]: #method.from_ref
+/// [`from_mut`]: #method.from_mut
+/// [`from_fn`]: #method.from_fn
+/// [`map`]: #method.map
+/// [`map_enumerated`]: #method.map_enumerated
+/// [`into_iter_enumerated`]: #method.into_iter_enumerated
+/// [`TiSlice`]: struct.TiSlice.html
+/// [`TiVec`]: struct.TiVec.html
+/// [`array`]: https://doc.rust-lang.org/std/primitive.array.html
+/// [`From`]: https://doc.rust-lang.org/std/convert/trait.From.html
+/// [`Into`]: https://doc.rust-lang.org/std/convert/trait.Into.html
+/// [`TryFrom`]: https://doc.rust-lang.org/std/convert/trait.TryFrom.html
+#[repr(transparent)]
+pub struct TiArray<K, V, const N: usize> {
+    /// Raw array property
+    pub raw: [V; N],
+
+    /// Ties the container to the key type `K`.
+    _marker: PhantomData<fn(K) -> K>,
+}
+
+impl<K, V, const N: usize> TiArray<K, V, N> {
+    /// Converts a [`&[V; N]`][`array`] into a `&TiArray<K, V, N>`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use typed_index_collections::TiArray;
+    /// pub struct Id(usize);
+    /// let array: &TiArray<Id, usize, 3> = TiArray::from_ref(&[1, 2, 4]);
+    /// ```
+    ///
+    /// [`array`]: https://doc.rust-lang.org/std/primitive.array.html
+    #[allow(trivial_casts)]
+    #[inline]
+    pub fn from_ref(raw: &[V; N]) -> &Self {
+        unsafe { &*(raw as *const [V; N] as *const Self) }
+    }
+
+    /// Converts a [`&mut [V; N]`][`array`] into a `&mut TiArray<K, V, N>`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use typed_index_collections::TiArray;
+    /// pub struct Id(usize);
+    /// let array: &mut TiArray<Id, usize, 3> = TiArray::from_mut(&mut [1, 2, 4]);
+    /// ```
+    ///
+    /// [`array`]: https://doc.rust-lang.org/std/primitive.array.html
+    #[allow(trivial_casts)]
+    #[inline]
+    pub fn from_mut(raw: &mut [V; N]) -> &mut Self {
+        unsafe { &mut *(raw as *mut [V; N] as *mut Self) }
+    }
+
+    /// Creates an array where each element is produced by calling `f`
+    /// with that element's index of type `K`.
+    ///
+    /// See [`array::from_fn`] for more details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key type can not represent every index of the array.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiArray;
+    /// #[derive(Debug, Eq, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let array: TiArray<Id, usize, 3> = TiArray::from_fn(|Id(index)| index * 2);
+    /// assert_eq!(array.raw, [0, 2, 4]);
+    /// ```
+    ///
+    /// [`array::from_fn`]: https://doc.rust-lang.org/std/array/fn.from_fn.html
+    #[inline]
+    pub fn from_fn<F>(mut f: F) -> Self
+    where
+        K: TiKey,
+        F: FnMut(K) -> V,
+    {
+        assert_keys_in_range::<K>(N);
+        array::from_fn(|index| f(K::from_usize(index))).into()
+    }
+
+    /// Returns an array of the same size as `self`,
+    /// with function `f` applied to each element in order.
+    ///
+    /// See [`array::map`] for more details.
+    ///
+    /// [`array::map`]: https://doc.rust-lang.org/std/primitive.array.html#method.map
+    #[inline]
+    pub fn map<U, F>(self, f: F) -> TiArray<K, U, N>
+    where
+        F: FnMut(V) -> U,
+    {
+        self.raw.map(f).into()
+    }
+
+    /// Returns an array of the same size as `self`,
+    /// with function `f` applied to each element and its index of type `K` in order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiArray;
+    /// #[derive(Debug, Eq, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let array: TiArray<Id, usize, 3> = [1, 2, 4].into();
+    /// let array = array.map_enumerated(|Id(index), value| index + value);
+    /// assert_eq!(array.raw, [1, 3, 6]);
+    /// ```
+    #[inline]
+    pub fn map_enumerated<U, F>(self, mut f: F) -> TiArray<K, U, N>
+    where
+        K: TiKey,
+        F: FnMut(K, V) -> U,
+    {
+        assert_keys_in_range::<K>(N);
+        let mut index = 0;
+        self.raw
+            .map(|value| {
+                let key = K::from_usize(index);
+                index += 1;
+                f(key, value)
+            })
+            .into()
+    }
+
+    /// Returns a `TiSlice` containing the entire array.
+    ///
+    /// See [`array::as_slice`] for more details.
+    ///
+    /// [`array::as_slice`]: https://doc.rust-lang.org/std/primitive.array.html#method.as_slice
+    #[inline]
+    pub fn as_slice(&self) -> &TiSlice<K, V> {
+        TiSlice::from_ref(&self.raw)
+    }
+
+    /// Returns a mutable `TiSlice` containing the entire array.
+    ///
+    /// See [`array::as_mut_slice`] for more details.
+    ///
+    /// [`array::as_mut_slice`]: https://doc.rust-lang.org/std/primitive.array.html#method.as_mut_slice
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut TiSlice<K, V> {
+        TiSlice::from_mut(&mut self.raw)
+    }
+
+    /// Converts the array into iterator over all key-value pairs
+    /// with `K` used for iteration indices.
+    ///
+    /// It acts like `self.into_iter().enumerate()`,
+    /// but use `K` instead of `usize` for iteration indices.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiArray;
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let array: TiArray<Id, usize, 3> = [1, 2, 4].into();
+    /// let mut iterator = array.into_iter_enumerated();
+    /// assert_eq!(iterator.next(), Some((Id(0), 1)));
+    /// assert_eq!(iterator.next(), Some((Id(1), 2)));
+    /// assert_eq!(iterator.next(), Some((Id(2), 4)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key type can not represent every index of the array.
+    #[inline(always)]
+    pub fn into_iter_enumerated(self) -> TiEnumerated<array::IntoIter<V, N>, K, V>
+    where
+        K: TiKey,
+    {
+        assert_keys_in_range::<K>(N);
+        IntoIterator::into_iter(self.raw)
+            .enumerate()
+            .map(|(key, value)| (K::from_usize(key), value))
+    }
+}
+
+impl<K, V, const N: usize> fmt::Debug for TiArray<K, V, N>
+where
+    K: fmt::Debug + TiKey,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter_enumerated()).finish()
+    }
+}
+
+impl<K, V, const N: usize> AsRef<TiSlice<K, V>> for TiArray<K, V, N> {
+    fn as_ref(&self) -> &TiSlice<K, V> {
+        self
+    }
+}
+
+impl<K, V, const N: usize> AsMut<TiSlice<K, V>> for TiArray<K, V, N> {
+    fn as_mut(&mut self) -> &mut TiSlice<K, V> {
+        self
+    }
+}
+
+impl<K, V, const N: usize> AsRef<[V]> for TiArray<K, V, N> {
+    fn as_ref(&self) -> &[V] {
+        &self.raw
+    }
+}
+
+impl<K, V, const N: usize> AsMut<[V]> for TiArray<K, V, N> {
+    fn as_mut(&mut self) -> &mut [V] {
+        &mut self.raw
+    }
+}
+
+impl<K, V, const N: usize> Borrow<TiSlice<K, V>> for TiArray<K, V, N> {
+    fn borrow(&self) -> &TiSlice<K, V> {
+        self
+    }
+}
+
+impl<K, V, const N: usize> BorrowMut<TiSlice<K, V>> for TiArray<K, V, N> {
+    fn borrow_mut(&mut self) -> &mut TiSlice<K, V> {
+        self
+    }
+}
+
+impl<K, V, const N: usize> ops::Deref for TiArray<K, V, N> {
+    type Target = TiSlice<K, V>;
+
+    fn deref(&self) -> &TiSlice<K, V> {
+        Self::Target::from_ref(&self.raw)
+    }
+}
+
+impl<K, V, const N: usize> ops::DerefMut for TiArray<K, V, N> {
+    fn deref_mut(&mut self) -> &mut TiSlice<K, V> {
+        Self::Target::from_mut(&mut self.raw)
+    }
+}
+
+impl<K, A, B, const N: usize> PartialEq<TiArray<K, B, N>> for TiArray<K, A, N>
+where
+    A: PartialEq<B>,
+{
+    #[inline]
+    fn eq(&self, other: &TiArray<K, B, N>) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl<'a, K, A, B, const N: usize> PartialEq<&'a TiSlice<K, B>> for TiArray<K, A, N>
+where
+    A: PartialEq<B>,
+{
+    #[inline]
+    fn eq(&self, other: &&'a TiSlice<K, B>) -> bool {
+        self.raw[..] == other.raw
+    }
+}
+
+impl<K, V, const N: usize> PartialOrd<TiArray<K, V, N>> for TiArray<K, V, N>
+where
+    V: PartialOrd<V>,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &TiArray<K, V, N>) -> Option<Ordering> {
+        self.raw.partial_cmp(&other.raw)
+    }
+}
+
+impl<K, V, const N: usize> Clone for TiArray<K, V, N>
+where
+    V: Clone,
+{
+    #[inline]
+    fn clone(&self) -> TiArray<K, V, N> {
+        self.raw.clone().into()
+    }
+}
+
+impl<K, V, const N: usize> Copy for TiArray<K, V, N> where V: Copy {}
+
+impl<K, V, const N: usize> Default for TiArray<K, V, N>
+where
+    V: Default,
+{
+    #[inline]
+    fn default() -> Self {
+        array::from_fn(|_| V::default()).into()
+    }
+}
+
+impl<K, V, const N: usize> Eq for TiArray<K, V, N> where V: Eq {}
+
+impl<K, V, const N: usize> Hash for TiArray<K, V, N>
+where
+    V: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.raw.hash(state)
+    }
+}
+
+impl<K, V, const N: usize> Ord for TiArray<K, V, N>
+where
+    V: Ord,
+{
+    #[inline]
+    fn cmp(&self, other: &TiArray<K, V, N>) -> Ordering {
+        self.raw.cmp(&other.raw)
+    }
+}
+
+impl<K, V, const N: usize> From<[V; N]> for TiArray<K, V, N> {
+    fn from(array: [V; N]) -> Self {
+        Self {
+            raw: array,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V, const N: usize> From<TiArray<K, V, N>> for [V; N] {
+    fn from(array: TiArray<K, V, N>) -> Self {
+        array.raw
+    }
+}
+
+#[cfg(any(feature = "alloc", feature = "std"))]
+impl<K, V, const N: usize> From<TiArray<K, V, N>> for TiVec<K, V> {
+    fn from(array: TiArray<K, V, N>) -> Self {
+        Vec::from(array.raw).into()
+    }
+}
+
+#[cfg(any(feature = "alloc", feature = "std"))]
+impl<K, V, const N: usize> TryFrom<TiVec<K, V>> for TiArray<K, V, N> {
+    type Error = TiVec<K, V>;
+
+    /// Converts a vector into an array if the vector length is equal to `N`,
+    /// otherwise returns the original vector.
+    fn try_from(vec: TiVec<K, V>) -> Result<Self, TiVec<K, V>> {
+        <[V; N]>::try_from(vec.raw)
+            .map(Into::into)
+            .map_err(Into::into)
+    }
+}
+
+impl<K, V, const N: usize> IntoIterator for TiArray<K, V, N> {
+    type Item = V;
+    type IntoIter = array::IntoIter<V, N>;
+
+    #[inline]
+    fn into_iter(self) -> array::IntoIter<V, N> {
+        IntoIterator::into_iter(self.raw)
+    }
+}
+
+impl<'a, K, V, const N: usize> IntoIterator for &'a TiArray<K, V, N> {
+    type Item = &'a V;
+    type IntoIter = slice::Iter<'a, V>;
+
+    #[inline]
+    fn into_iter(self) -> slice::Iter<'a, V> {
+        self.raw.iter()
+    }
+}
+
+impl<'a, K, V, const N: usize> IntoIterator for &'a mut TiArray<K, V, N> {
+    type Item = &'a mut V;
+    type IntoIter = slice::IterMut<'a, V>;
+
+    #[inline]
+    fn into_iter(self) -> slice::IterMut<'a, V> {
+        self.raw.iter_mut()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K, V: Serialize, const N: usize> Serialize for TiArray<K, V, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(N)?;
+        for value in &self.raw {
+            tuple.serialize_element(value)?;
+        }
+        tuple.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V: Deserialize<'de>, const N: usize> Deserialize<'de> for TiArray<K, V, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ArrayVisitor<V, const N: usize>(PhantomData<fn() -> V>);
+
+        impl<'de, V: Deserialize<'de>, const N: usize> Visitor<'de> for ArrayVisitor<V, N> {
+            type Value = [V; N];
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(formatter, "an array of length {}", N)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<[V; N], A::Error> {
+                let mut values: [Option<V>; N] = array::from_fn(|_| None);
+                for (index, value) in values.iter_mut().enumerate() {
+                    *value = Some(
+                        seq.next_element()?
+                            .ok_or_else(|| A::Error::invalid_length(index, &self))?,
+                    );
+                }
+                Ok(values.map(|value| value.unwrap_or_else(|| unreachable!())))
+            }
+        }
+
+        deserializer
+            .deserialize_tuple(N, ArrayVisitor(PhantomData))
+            .map(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::{Id, SmallId};
+    use crate::{TiArray, TiSlice};
+
+    #[test]
+    fn from_fn_and_map() {
+        let array: TiArray<Id, usize, 4> = TiArray::from_fn(|id: Id| usize::from(id) * 10);
+        assert_eq!(array.raw, [0, 10, 20, 30]);
+        assert_eq!(array[Id::from(2)], 20);
+        assert_eq!(array.map(|value| value + 1).raw, [1, 11, 21, 31]);
+        assert_eq!(
+            array
+                .map_enumerated(|id, value| usize::from(id) + value)
+                .raw,
+            [0, 11, 22, 33]
+        );
+        assert_eq!(array.last_key(), Some(Id::from(3)));
+        assert_eq!(array, TiSlice::from_ref(&[0, 10, 20, 30]));
+    }
+
+    #[test]
+    fn iterators() {
+        let mut array: TiArray<Id, usize, 3> = [1, 2, 4].into();
+        for value in &mut array {
+            *value *= 2;
+        }
+        assert_eq!((&array).into_iter().sum::<usize>(), 14);
+        let mut iter = array.into_iter_enumerated();
+        assert_eq!(iter.next(), Some((Id::from(0), 2)));
+        assert_eq!(iter.next_back(), Some((Id::from(2), 8)));
+        assert_eq!(iter.next(), Some((Id::from(1), 4)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn zero_sized() {
+        let array: TiArray<Id, usize, 0> = TiArray::default();
+        assert!(array.is_empty());
+        assert_eq!(array.into_iter_enumerated().next(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn from_fn_key_overflow() {
+        let _: TiArray<SmallId, u8, 3> = TiArray::from_fn(|id: SmallId| id.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn into_iter_enumerated_key_overflow() {
+        let array: TiArray<SmallId, u8, 3> = [0, 1, 2].into();
+        let _ = array.into_iter_enumerated();
+    }
+
+    #[cfg(any(feature = "alloc", feature = "std"))]
+    #[test]
+    fn vec_conversions() {
+        use crate::TiVec;
+        use alloc::vec;
+        use core::convert::TryFrom;
+
+        let vec: TiVec<Id, u32> = vec![1, 2, 3].into();
+        let array = TiArray::<Id, u32, 3>::try_from(vec).unwrap_or_else(|_| unreachable!());
+        assert_eq!(array.raw, [1, 2, 3]);
+        let vec = TiVec::from(array);
+        assert_eq!(vec.raw, vec![1, 2, 3]);
+        let vec = TiArray::<Id, u32, 2>::try_from(vec).err();
+        assert_eq!(vec.map(|vec| vec.raw), Some(vec![1, 2, 3]));
+    }
+
+    #[cfg(any(feature = "alloc", feature = "std"))]
+    #[test]
+    fn debug() {
+        use alloc::format;
+
+        let array: TiArray<Id, u32, 2> = [5, 7].into();
+        assert_eq!(format!("{:?}", array), "{Id(0): 5, Id(1): 7}");
+    }
+}
@@ -0,0 +1,1582 @@
+use core::{
+    borrow::{Borrow, BorrowMut},
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::{FromIterator, FusedIterator},
+    marker::PhantomData,
+    mem::{ManuallyDrop, MaybeUninit},
+    ops, ptr, slice,
+};
+
+#[cfg(any(feature = "alloc", feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{
+    de::{Deserialize, Deserializer, Error, SeqAccess, Visitor},
+    ser::{Serialize, Serializer},
+};
+
+use crate::range::range_to_bounds;
+use crate::{TiEnumerated, TiKey, TiRangeBounds, TiSlice};
+
+#[cfg(any(feature = "alloc", feature = "std"))]
+use crate::TiVec;
+
+/// A fixed-capacity vector type stored inline
+/// that only accepts keys of the type `K`.
+///
+/// `TiArrayVec<K, V, CAP>` mirrors the [`TiVec`] API,
+/// but stores up to `CAP` elements without memory allocation,
+/// so it is available in `#![no_std]` context without `alloc` feature.
+/// It dereferences to [`TiSlice<K, V>`][`TiSlice`],
+/// so all slice methods are available with `K` used for element indices.
+///
+/// Methods that add elements panic if the vector is full,
+/// fallible alternatives return [`CapacityError`] with the rejected element instead.
+///
+/// Added methods:
+/// - [`try_push`] - Appends an element to the back of a collection
+///   or returns it back if the vector is full.
+/// - [`push_and_get_key`] - Appends an element to the back of a collection
+///   and returns its index of type `K`.
+/// - [`try_push_and_get_key`] - Appends an element to the back of a collection
+///   and returns its index of type `K` or returns the element back if the vector is full.
+/// - [`try_insert`] - Inserts an element at the specified position
+///   or returns it back if the vector is full.
+/// - [`pop_key_value`] - Removes the last element from a vector and returns it
+///   with its index of type `K`, or [`None`] if the vector is empty.
+/// - [`try_append`] - Moves all the elements of `other` into `self`
+///   or leaves both vectors unchanged if the elements do not fit.
+/// - [`try_resize`], [`try_resize_with`] - Resizes the vector in-place
+///   or leaves it unchanged if `new_len` exceeds the capacity.
+/// - [`try_splice`] - Replaces the specified range in the vector
+///   or leaves it unchanged if the result does not fit.
+/// - [`drain_enumerated`] - Creates a draining iterator that removes the specified
+///   range in the vector and yields the current count and the removed items.
+///   It acts like `self.drain(range).enumerate()`,
+///   but instead of `usize` it returns index of type `K`.
+/// - [`into_iter_enumerated`] - Converts the vector into iterator over all key-value pairs
+///   with `K` used for iteration indices.
+///   It acts like `self.into_iter().enumerate()`,
+///   but use `K` instead of `usize` for iteration indices.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::{ti_index, TiArrayVec};
+///
+/// ti_index! {
+///     #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+///     struct TaskId(u8);
+/// }
+///
+/// let mut tasks: TiArrayVec<TaskId, &str, 2> = TiArrayVec::new();
+/// let idle = tasks.push_and_get_key("idle");
+/// let blink = tasks.push_and_get_key("blink");
+/// assert_eq!(tasks[blink], "blink");
+/// assert_eq!(tasks.try_push("uart").map_err(|err| err.element()), Err("uart"));
+/// assert_eq!(tasks.position(|&task| task == "idle"), Some(idle));
+/// ```
+///
+/// [`try_push`]: #method.try_push
+/// [`push_and_get_key`]: #method.push_and_get_key
+/// [`try_push_and_get_key`]: #method.try_push_and_get_key
+/// [`try_insert`]: #method.try_insert
+/// [`pop_key_value`]: #method.pop_key_value
+/// [`try_append`]: #method.try_append
+/// [`try_resize`]: #method.try_resize
+/// [`try_resize_with`]: #method.try_resize_with
+/// [`try_splice`]: #method.try_splice
+/// [`drain_enumerated`]: #method.drain_enumerated
+/// [`into_iter_enumerated`]: #method.into_iter_enumerated
+/// [`CapacityError`]: struct.CapacityError.html
+/// [`TiSlice`]: struct.TiSlice.html
+/// [`TiVec`]: struct.TiVec.html
+pub struct TiArrayVec<K, V, const CAP: usize> {
+    /// Inline storage, only the first `len` elements are initialized.
+    raw: [MaybeUninit<V>; CAP],
+
+    /// The number of initialized elements.
+    len: usize,
+
+    /// Ties the container to the key type `K`.
+    _marker: PhantomData<fn(K) -> K>,
+}
+
+/// An error returned when an element does not fit into a fixed-capacity container.
+///
+/// The error holds the rejected element that can be retrieved with [`element`].
+///
+/// [`element`]: #method.element
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct CapacityError<V> {
+    element: V,
+}
+
+impl<V> CapacityError<V> {
+    /// Creates an error holding the rejected element.
+    #[inline]
+    pub fn new(element: V) -> Self {
+        Self { element }
+    }
+
+    /// Returns the rejected element.
+    #[inline]
+    pub fn element(self) -> V {
+        self.element
+    }
+}
+
+impl<V> fmt::Debug for CapacityError<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CapacityError: insufficient capacity")
+    }
+}
+
+impl<V> fmt::Display for CapacityError<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("insufficient capacity")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V> alloc::error::Error for CapacityError<V> {}
+
+impl<K, V, const CAP: usize> TiArrayVec<K, V, CAP> {
+    /// Constructs a new, empty `TiArrayVec<K, V, CAP>`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use typed_index_collections::TiArrayVec;
+    /// pub struct Id(usize);
+    /// let vec: TiArrayVec<Id, u32, 4> = TiArrayVec::new();
+    /// assert!(vec.is_empty());
+    /// assert_eq!(vec.capacity(), 4);
+    /// ```
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            // SAFETY: An array of `MaybeUninit` does not require initialization.
+            raw: unsafe { MaybeUninit::uninit().assume_init() },
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of elements the vector can hold.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        CAP
+    }
+
+    /// Returns the number of elements that can be added before the vector is full.
+    #[inline]
+    pub fn remaining_capacity(&self) -> usize {
+        CAP - self.len
+    }
+
+    /// Returns `true` if the vector contains `CAP` elements.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len == CAP
+    }
+
+    /// Returns the number of elements in the vector.
+    ///
+    /// See [`Vec::len`] for more details.
+    ///
+    /// [`Vec::len`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.len
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the vector contains no elements.
+    ///
+    /// See [`Vec::is_empty`] for more details.
+    ///
+    /// [`Vec::is_empty`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.is_empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Extracts a slice containing the entire vector.
+    ///
+    /// See [`Vec::as_slice`] for more details.
+    ///
+    /// [`Vec::as_slice`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.as_slice
+    #[inline]
+    pub fn as_slice(&self) -> &TiSlice<K, V> {
+        // SAFETY: The first `len` elements are initialized.
+        TiSlice::from_ref(unsafe { slice::from_raw_parts(self.as_ptr(), self.len) })
+    }
+
+    /// Extracts a mutable slice of the entire vector.
+    ///
+    /// See [`Vec::as_mut_slice`] for more details.
+    ///
+    /// [`Vec::as_mut_slice`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.as_mut_slice
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut TiSlice<K, V> {
+        // SAFETY: The first `len` elements are initialized.
+        TiSlice::from_mut(unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) })
+    }
+
+    /// Returns a raw pointer to the vector's buffer.
+    ///
+    /// See [`Vec::as_ptr`] for more details.
+    ///
+    /// [`Vec::as_ptr`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.as_ptr
+    #[inline]
+    pub fn as_ptr(&self) -> *const V {
+        self.raw.as_ptr().cast()
+    }
+
+    /// Returns an unsafe mutable pointer to the vector's buffer.
+    ///
+    /// See [`Vec::as_mut_ptr`] for more details.
+    ///
+    /// [`Vec::as_mut_ptr`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.as_mut_ptr
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut V {
+        self.raw.as_mut_ptr().cast()
+    }
+
+    /// Forces the length of the vector to `new_len`.
+    ///
+    /// See [`Vec::set_len`] for more details.
+    ///
+    /// # Safety
+    ///
+    /// `new_len` must be less than or equal to `CAP`
+    /// and the elements at `old_len..new_len` must be initialized.
+    ///
+    /// [`Vec::set_len`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.set_len
+    #[inline]
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        debug_assert!(new_len <= CAP);
+        self.len = new_len;
+    }
+
+    /// Shortens the vector, keeping the first `len` elements and dropping the rest.
+    ///
+    /// See [`Vec::truncate`] for more details.
+    ///
+    /// [`Vec::truncate`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.truncate
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            let old_len = self.len;
+            self.len = len;
+            // SAFETY: The elements at `len..old_len` are initialized
+            // and are no longer reachable.
+            unsafe {
+                ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                    self.as_mut_ptr().add(len),
+                    old_len - len,
+                ));
+            }
+        }
+    }
+
+    /// Clears the vector, removing all values.
+    ///
+    /// See [`Vec::clear`] for more details.
+    ///
+    /// [`Vec::clear`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.clear
+    #[inline]
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+
+    /// Appends an element to the back of a collection.
+    ///
+    /// See [`Vec::push`] for more details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is full.
+    ///
+    /// [`Vec::push`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.push
+    #[inline]
+    pub fn push(&mut self, value: V) {
+        if self.try_push(value).is_err() {
+            capacity_overflow(CAP)
+        }
+    }
+
+    /// Appends an element to the back of a collection
+    /// or returns it in the error if the vector is full.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use typed_index_collections::TiArrayVec;
+    /// pub struct Id(usize);
+    /// let mut vec: TiArrayVec<Id, u32, 1> = TiArrayVec::new();
+    /// assert!(vec.try_push(1).is_ok());
+    /// assert_eq!(vec.try_push(2).map_err(|err| err.element()), Err(2));
+    /// ```
+    #[inline]
+    pub fn try_push(&mut self, value: V) -> Result<(), CapacityError<V>> {
+        if self.len < CAP {
+            let _ = self.raw[self.len].write(value);
+            self.len += 1;
+            Ok(())
+        } else {
+            Err(CapacityError::new(value))
+        }
+    }
+
+    /// Appends an element to the back of a collection and returns its index of type `K`.
+    ///
+    /// It acts like `{ vec.push(...); vec.last_key().unwrap() }`,
+    /// but is optimized better.
+    ///
+    /// See [`Vec::push`] for more details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is full.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiArrayVec;
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut vec: TiArrayVec<Id, usize, 4> = TiArrayVec::new();
+    /// assert_eq!(vec.push_and_get_key(8), Id(0));
+    /// assert_eq!(vec.push_and_get_key(16), Id(1));
+    /// ```
+    ///
+    /// [`Vec::push`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.push
+    #[inline]
+    pub fn push_and_get_key(&mut self, value: V) -> K
+    where
+        K: TiKey,
+    {
+        match self.try_push_and_get_key(value) {
+            Ok(key) => key,
+            Err(_) => capacity_overflow(CAP),
+        }
+    }
+
+    /// Appends an element to the back of a collection and returns its index of type `K`,
+    /// or returns the element in the error if the vector is full.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiArrayVec;
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut vec: TiArrayVec<Id, usize, 1> = TiArrayVec::new();
+    /// assert_eq!(vec.try_push_and_get_key(8).ok(), Some(Id(0)));
+    /// assert_eq!(vec.try_push_and_get_key(16).map_err(|err| err.element()), Err(16));
+    /// ```
+    #[inline]
+    pub fn try_push_and_get_key(&mut self, value: V) -> Result<K, CapacityError<V>>
+    where
+        K: TiKey,
+    {
+        if self.is_full() {
+            return Err(CapacityError::new(value));
+        }
+        let key = self.next_key();
+        let _ = self.raw[self.len].write(value);
+        self.len += 1;
+        Ok(key)
+    }
+
+    /// Removes the last element from a vector and returns it, or [`None`] if it
+    /// is empty.
+    ///
+    /// See [`Vec::pop`] for more details.
+    ///
+    /// [`Vec::pop`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.pop
+    #[inline]
+    pub fn pop(&mut self) -> Option<V> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            // SAFETY: The element at `len` was initialized and is no longer reachable.
+            Some(unsafe { self.raw[self.len].as_ptr().read() })
+        }
+    }
+
+    /// Removes the last element from a vector and returns it with
+    /// its index of type `K`, or [`None`] if the vector is empty.
+    ///
+    /// See [`Vec::pop`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiArrayVec;
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut vec: TiArrayVec<Id, usize, 4> = [1, 2].iter().copied().collect();
+    /// assert_eq!(vec.pop_key_value(), Some((Id(1), 2)));
+    /// assert_eq!(vec.pop_key_value(), Some((Id(0), 1)));
+    /// assert_eq!(vec.pop_key_value(), None);
+    /// ```
+    ///
+    /// [`Vec::pop`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.pop
+    #[inline]
+    pub fn pop_key_value(&mut self) -> Option<(K, V)>
+    where
+        K: TiKey,
+    {
+        self.pop().map(|value| (K::from_usize(self.len), value))
+    }
+
+    /// Moves all the elements of `other` into `self`, leaving `other` empty.
+    ///
+    /// See [`Vec::append`] for more details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the elements do not fit into the vector.
+    ///
+    /// [`Vec::append`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.append
+    #[inline]
+    pub fn append(&mut self, other: &mut Self) {
+        if self.try_append(other).is_err() {
+            capacity_overflow(CAP)
+        }
+    }
+
+    /// Moves all the elements of `other` into `self`, leaving `other` empty,
+    /// or returns an error without changing the vectors if the elements do not fit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::{TiArrayVec, TiSlice};
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut vec: TiArrayVec<Id, u32, 3> = [1, 2].iter().copied().collect();
+    /// let mut other: TiArrayVec<Id, u32, 3> = [3].iter().copied().collect();
+    /// assert!(vec.try_append(&mut other).is_ok());
+    /// assert!(other.is_empty());
+    /// other.push(4);
+    /// assert!(vec.try_append(&mut other).is_err());
+    /// assert_eq!(vec.as_slice(), TiSlice::from_ref(&[1, 2, 3]));
+    /// assert_eq!(other.as_slice(), TiSlice::from_ref(&[4]));
+    /// ```
+    pub fn try_append(&mut self, other: &mut Self) -> Result<(), CapacityError<()>> {
+        let count = other.len;
+        if count > self.remaining_capacity() {
+            return Err(CapacityError::new(()));
+        }
+        other.len = 0;
+        // SAFETY: The elements of `other` are initialized and no longer reachable from it,
+        // `self` has room for them after its own elements.
+        unsafe {
+            ptr::copy_nonoverlapping(other.as_ptr(), self.as_mut_ptr().add(self.len), count);
+        }
+        self.len += count;
+        Ok(())
+    }
+
+    /// Inserts an element at position `index` within the vector, shifting all
+    /// elements after it to the right.
+    ///
+    /// See [`Vec::insert`] for more details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is full or if `index` is greater than the vector length.
+    ///
+    /// [`Vec::insert`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.insert
+    pub fn insert(&mut self, index: K, element: V)
+    where
+        K: TiKey,
+    {
+        if self.try_insert(index, element).is_err() {
+            capacity_overflow(CAP)
+        }
+    }
+
+    /// Inserts an element at position `index` within the vector, shifting all
+    /// elements after it to the right,
+    /// or returns the element in the error if the vector is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the vector length.
+    pub fn try_insert(&mut self, index: K, element: V) -> Result<(), CapacityError<V>>
+    where
+        K: TiKey,
+    {
+        let index = index.to_usize();
+        let len = self.len;
+        assert!(
+            index <= len,
+            "insertion index (is {}) should be <= len (is {})",
+            index,
+            len
+        );
+        if len == CAP {
+            return Err(CapacityError::new(element));
+        }
+        // SAFETY: `index <= len < CAP`, so the shifted elements stay within the storage.
+        unsafe {
+            let ptr = self.as_mut_ptr().add(index);
+            ptr::copy(ptr, ptr.add(1), len - index);
+            ptr.write(element);
+        }
+        self.len = len + 1;
+        Ok(())
+    }
+
+    /// Removes and returns the element at position `index` within the vector,
+    /// shifting all elements after it to the left.
+    ///
+    /// See [`Vec::remove`] for more details.
+    ///
+    /// [`Vec::remove`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.remove
+    pub fn remove(&mut self, index: K) -> V
+    where
+        K: TiKey,
+    {
+        let index = index.to_usize();
+        let len = self.len;
+        assert!(
+            index < len,
+            "removal index (is {}) should be < len (is {})",
+            index,
+            len
+        );
+        // SAFETY: `index < len`, so the element is initialized
+        // and the following elements are shifted into its place.
+        unsafe {
+            let ptr = self.as_mut_ptr().add(index);
+            let value = ptr.read();
+            ptr::copy(ptr.add(1), ptr, len - index - 1);
+            self.len = len - 1;
+            value
+        }
+    }
+
+    /// Removes an element from the vector and returns it.
+    ///
+    /// The removed element is replaced by the last element of the vector.
+    ///
+    /// See [`Vec::swap_remove`] for more details.
+    ///
+    /// [`Vec::swap_remove`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.swap_remove
+    pub fn swap_remove(&mut self, index: K) -> V
+    where
+        K: TiKey,
+    {
+        let index = index.to_usize();
+        let len = self.len;
+        assert!(
+            index < len,
+            "swap_remove index (is {}) should be < len (is {})",
+            index,
+            len
+        );
+        // SAFETY: `index < len`, so the element is initialized
+        // and the last element is moved into its place.
+        unsafe {
+            let base = self.as_mut_ptr();
+            let value = base.add(index).read();
+            ptr::copy(base.add(len - 1), base.add(index), 1);
+            self.len = len - 1;
+            value
+        }
+    }
+
+    /// Retains only the elements specified by the predicate.
+    ///
+    /// See [`Vec::retain`] for more details.
+    ///
+    /// [`Vec::retain`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.retain
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&V) -> bool,
+    {
+        struct Guard<'a, V> {
+            base: *mut V,
+            len: &'a mut usize,
+            original_len: usize,
+            processed: usize,
+            deleted: usize,
+        }
+
+        impl<V> Drop for Guard<'_, V> {
+            fn drop(&mut self) {
+                // SAFETY: Moves the unprocessed elements
+                // to close the gap left by the deleted ones.
+                unsafe {
+                    ptr::copy(
+                        self.base.add(self.processed),
+                        self.base.add(self.processed - self.deleted),
+                        self.original_len - self.processed,
+                    );
+                }
+                *self.len = self.original_len - self.deleted;
+            }
+        }
+
+        let original_len = self.len;
+        self.len = 0;
+        let mut guard = Guard {
+            base: self.raw.as_mut_ptr().cast::<V>(),
+            len: &mut self.len,
+            original_len,
+            processed: 0,
+            deleted: 0,
+        };
+        while guard.processed < original_len {
+            // SAFETY: Elements at `processed..original_len` are initialized
+            // and elements at `processed - deleted..processed` are moved out or dropped.
+            unsafe {
+                let current = guard.base.add(guard.processed);
+                if f(&*current) {
+                    if guard.deleted > 0 {
+                        ptr::copy_nonoverlapping(current, current.sub(guard.deleted), 1);
+                    }
+                    guard.processed += 1;
+                } else {
+                    guard.processed += 1;
+                    guard.deleted += 1;
+                    ptr::drop_in_place(current);
+                }
+            }
+        }
+    }
+
+    /// Removes all but the first of consecutive elements in the vector that resolve to the same
+    /// key.
+    ///
+    /// See [`Vec::dedup_by_key`] for more details.
+    ///
+    /// [`Vec::dedup_by_key`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.dedup_by_key
+    #[inline]
+    pub fn dedup_by_key<F, K2>(&mut self, mut key: F)
+    where
+        F: FnMut(&mut V) -> K2,
+        K2: PartialEq,
+    {
+        self.dedup_by(|a, b| key(a) == key(b))
+    }
+
+    /// Removes all but the first of consecutive elements in the vector satisfying a given equality
+    /// relation.
+    ///
+    /// See [`Vec::dedup_by`] for more details.
+    ///
+    /// [`Vec::dedup_by`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.dedup_by
+    pub fn dedup_by<F>(&mut self, mut same_bucket: F)
+    where
+        F: FnMut(&mut V, &mut V) -> bool,
+    {
+        if self.len <= 1 {
+            return;
+        }
+        let values = &mut self.as_mut_slice().raw;
+        let mut write = 1;
+        for read in 1..values.len() {
+            let (kept, rest) = values.split_at_mut(read);
+            if !same_bucket(&mut rest[0], &mut kept[write - 1]) {
+                values.swap(read, write);
+                write += 1;
+            }
+        }
+        self.truncate(write);
+    }
+
+    /// Removes consecutive repeated elements in the vector according to the
+    /// [`PartialEq`] trait implementation.
+    ///
+    /// See [`Vec::dedup`] for more details.
+    ///
+    /// [`PartialEq`]: https://doc.rust-lang.org/std/cmp/trait.PartialEq.html
+    /// [`Vec::dedup`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.dedup
+    #[inline]
+    pub fn dedup(&mut self)
+    where
+        V: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b)
+    }
+
+    /// Creates a draining iterator that removes the specified range in the vector
+    /// and yields the removed items.
+    ///
+    /// See [`Vec::drain`] for more details.
+    ///
+    /// [`Vec::drain`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.drain
+    pub fn drain<R>(&mut self, range: R) -> TiArrayVecDrain<'_, V>
+    where
+        R: TiRangeBounds<K>,
+    {
+        let range = range_to_bounds(range.into_range(), self.len);
+        self.drain_bounds(range)
+    }
+
+    pub(crate) fn drain_bounds(&mut self, range: ops::Range<usize>) -> TiArrayVecDrain<'_, V> {
+        let len = self.len;
+        self.len = range.start;
+        TiArrayVecDrain {
+            base: self.raw.as_mut_ptr().cast(),
+            len: &mut self.len,
+            front: range.start,
+            back: range.end,
+            tail_start: range.end,
+            tail_len: len - range.end,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates a draining iterator that removes the specified
+    /// range in the vector and yields the current count and the removed items.
+    ///
+    /// It acts like `self.drain(range).enumerate()`,
+    /// but instead of `usize` it returns index of type `K`.
+    ///
+    /// Note that the indices started from `K::from_usize(0)`,
+    /// regardless of the range starting point.
+    ///
+    /// See [`Vec::drain`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::{TiArrayVec, TiSlice};
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut vec: TiArrayVec<Id, usize, 4> = [1, 2, 4].iter().copied().collect();
+    /// {
+    ///     let mut iterator = vec.drain_enumerated(Id(1)..);
+    ///     assert_eq!(iterator.next(), Some((Id(0), 2)));
+    ///     assert_eq!(iterator.next(), Some((Id(1), 4)));
+    ///     assert_eq!(iterator.next(), None);
+    /// }
+    /// assert_eq!(vec.as_slice(), TiSlice::from_ref(&[1]));
+    /// ```
+    ///
+    /// [`Vec::drain`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.drain
+    #[inline]
+    pub fn drain_enumerated<R>(&mut self, range: R) -> TiEnumerated<TiArrayVecDrain<'_, V>, K, V>
+    where
+        K: TiKey,
+        R: TiRangeBounds<K>,
+    {
+        self.drain(range)
+            .enumerate()
+            .map(|(key, value)| (K::from_usize(key), value))
+    }
+
+    /// Splits the collection into two at the given index.
+    ///
+    /// See [`Vec::split_off`] for more details.
+    ///
+    /// [`Vec::split_off`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.split_off
+    #[inline]
+    #[must_use = "use `.truncate()` if you don't need the other half"]
+    pub fn split_off(&mut self, at: K) -> Self
+    where
+        K: TiKey,
+    {
+        self.drain(at..).collect()
+    }
+
+    /// Resizes the vector in-place so that `len` is equal to `new_len`.
+    ///
+    /// See [`Vec::resize_with`] for more details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new_len` is greater than `CAP`.
+    ///
+    /// [`Vec::resize_with`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.resize_with
+    #[inline]
+    pub fn resize_with<F>(&mut self, new_len: usize, f: F)
+    where
+        F: FnMut() -> V,
+    {
+        if self.try_resize_with(new_len, f).is_err() {
+            capacity_overflow(CAP)
+        }
+    }
+
+    /// Resizes the vector in-place so that `len` is equal to `new_len`,
+    /// or returns an error without changing the vector if `new_len` is greater than `CAP`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::{TiArrayVec, TiSlice};
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut vec: TiArrayVec<Id, u32, 3> = TiArrayVec::new();
+    /// let mut next = 0;
+    /// assert!(vec.try_resize_with(3, || { next += 1; next }).is_ok());
+    /// assert!(vec.try_resize_with(4, || 0).is_err());
+    /// assert_eq!(vec.as_slice(), TiSlice::from_ref(&[1, 2, 3]));
+    /// ```
+    pub fn try_resize_with<F>(&mut self, new_len: usize, mut f: F) -> Result<(), CapacityError<()>>
+    where
+        F: FnMut() -> V,
+    {
+        if new_len > CAP {
+            return Err(CapacityError::new(()));
+        }
+        if new_len > self.len {
+            while self.len < new_len {
+                let _ = self.raw[self.len].write(f());
+                self.len += 1;
+            }
+        } else {
+            self.truncate(new_len);
+        }
+        Ok(())
+    }
+
+    /// Resizes the vector in-place so that `len` is equal to `new_len`.
+    ///
+    /// See [`Vec::resize`] for more details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `new_len` is greater than `CAP`.
+    ///
+    /// [`Vec::resize`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.resize
+    #[inline]
+    pub fn resize(&mut self, new_len: usize, value: V)
+    where
+        V: Clone,
+    {
+        self.resize_with(new_len, || value.clone())
+    }
+
+    /// Resizes the vector in-place so that `len` is equal to `new_len`,
+    /// or returns an error without changing the vector if `new_len` is greater than `CAP`.
+    #[inline]
+    pub fn try_resize(&mut self, new_len: usize, value: V) -> Result<(), CapacityError<()>>
+    where
+        V: Clone,
+    {
+        self.try_resize_with(new_len, || value.clone())
+    }
+
+    /// Clones and appends all elements in a slice to the vector.
+    ///
+    /// See [`Vec::extend_from_slice`] for more details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the elements do not fit into the vector.
+    ///
+    /// [`Vec::extend_from_slice`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.extend_from_slice
+    pub fn extend_from_slice(&mut self, other: &TiSlice<K, V>)
+    where
+        V: Clone,
+    {
+        if self.try_extend_from_slice(other).is_err() {
+            capacity_overflow(CAP)
+        }
+    }
+
+    /// Clones and appends all elements in a slice to the vector,
+    /// or returns an error without changing the vector if the elements do not fit into it.
+    pub fn try_extend_from_slice(&mut self, other: &TiSlice<K, V>) -> Result<(), CapacityError<()>>
+    where
+        V: Clone,
+    {
+        if other.len() > self.remaining_capacity() {
+            return Err(CapacityError::new(()));
+        }
+        for value in other {
+            let _ = self.raw[self.len].write(value.clone());
+            self.len += 1;
+        }
+        Ok(())
+    }
+
+    /// Replaces the specified range in the vector with the given `replace_with` iterator
+    /// and returns an iterator over the removed items.
+    /// `replace_with` does not need to be the same length as `range`.
+    ///
+    /// Unlike [`Vec::splice`], the range is replaced immediately
+    /// and the returned iterator owns the removed items.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resulting elements do not fit into the vector.
+    ///
+    /// [`Vec::splice`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.splice
+    #[inline]
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> TiArrayVecIntoIter<V, CAP>
+    where
+        R: TiRangeBounds<K>,
+        I: IntoIterator<Item = V>,
+    {
+        match self.try_splice(range, replace_with) {
+            Ok(removed) => removed,
+            Err(_) => capacity_overflow(CAP),
+        }
+    }
+
+    /// Replaces the specified range in the vector with the given `replace_with` iterator
+    /// and returns an iterator over the removed items,
+    /// or returns an error without changing the vector if the resulting elements do not fit.
+    ///
+    /// The items already taken from `replace_with` are dropped on error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::{TiArrayVec, TiSlice};
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut vec: TiArrayVec<Id, u32, 4> = [1, 2, 3].iter().copied().collect();
+    /// let removed = vec.try_splice(Id(1)..Id(2), [7, 8].iter().copied()).ok();
+    /// assert_eq!(removed.map(|iter| iter.collect::<Vec<_>>()), Some(vec![2]));
+    /// assert!(vec.try_splice(Id(0)..Id(1), [5, 6].iter().copied()).is_err());
+    /// assert_eq!(vec.as_slice(), TiSlice::from_ref(&[1, 7, 8, 3]));
+    /// ```
+    pub fn try_splice<R, I>(
+        &mut self,
+        range: R,
+        replace_with: I,
+    ) -> Result<TiArrayVecIntoIter<V, CAP>, CapacityError<()>>
+    where
+        R: TiRangeBounds<K>,
+        I: IntoIterator<Item = V>,
+    {
+        let range = range_to_bounds(range.into_range(), self.len);
+        let mut replacement: TiArrayVec<K, V, CAP> = TiArrayVec::new();
+        for value in replace_with {
+            if replacement.try_push(value).is_err() {
+                return Err(CapacityError::new(()));
+            }
+        }
+        if self.len - (range.end - range.start) > CAP - replacement.len {
+            return Err(CapacityError::new(()));
+        }
+        let start = range.start;
+        let removed: TiArrayVec<K, V, CAP> = self.drain_bounds(range).collect();
+        let mut tail: TiArrayVec<K, V, CAP> = self.drain_bounds(start..self.len).collect();
+        self.append(&mut replacement);
+        self.append(&mut tail);
+        Ok(removed.into_iter())
+    }
+
+    /// Converts the vector into iterator over all key-value pairs
+    /// with `K` used for iteration indices.
+    ///
+    /// It acts like `self.into_iter().enumerate()`,
+    /// but use `K` instead of `usize` for iteration indices.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiArrayVec;
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let vec: TiArrayVec<Id, usize, 4> = [1, 2].iter().copied().collect();
+    /// let mut iterator = vec.into_iter_enumerated();
+    /// assert_eq!(iterator.next(), Some((Id(0), 1)));
+    /// assert_eq!(iterator.next(), Some((Id(1), 2)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    #[inline(always)]
+    pub fn into_iter_enumerated(self) -> TiEnumerated<TiArrayVecIntoIter<V, CAP>, K, V>
+    where
+        K: TiKey,
+    {
+        self.into_iter()
+            .enumerate()
+            .map(|(key, value)| (K::from_usize(key), value))
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn capacity_overflow(capacity: usize) -> ! {
+    panic!("TiArrayVec capacity {} exceeded", capacity)
+}
+
+impl<K, V, const CAP: usize> Drop for TiArrayVec<K, V, CAP> {
+    fn drop(&mut self) {
+        self.clear()
+    }
+}
+
+impl<K, V, const CAP: usize> fmt::Debug for TiArrayVec<K, V, CAP>
+where
+    K: fmt::Debug + TiKey,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter_enumerated()).finish()
+    }
+}
+
+impl<K, V, const CAP: usize> AsRef<TiSlice<K, V>> for TiArrayVec<K, V, CAP> {
+    fn as_ref(&self) -> &TiSlice<K, V> {
+        self
+    }
+}
+
+impl<K, V, const CAP: usize> AsMut<TiSlice<K, V>> for TiArrayVec<K, V, CAP> {
+    fn as_mut(&mut self) -> &mut TiSlice<K, V> {
+        self
+    }
+}
+
+impl<K, V, const CAP: usize> AsRef<[V]> for TiArrayVec<K, V, CAP> {
+    fn as_ref(&self) -> &[V] {
+        &self.as_slice().raw
+    }
+}
+
+impl<K, V, const CAP: usize> AsMut<[V]> for TiArrayVec<K, V, CAP> {
+    fn as_mut(&mut self) -> &mut [V] {
+        &mut self.as_mut_slice().raw
+    }
+}
+
+impl<K, V, const CAP: usize> Borrow<TiSlice<K, V>> for TiArrayVec<K, V, CAP> {
+    fn borrow(&self) -> &TiSlice<K, V> {
+        self
+    }
+}
+
+impl<K, V, const CAP: usize> BorrowMut<TiSlice<K, V>> for TiArrayVec<K, V, CAP> {
+    fn borrow_mut(&mut self) -> &mut TiSlice<K, V> {
+        self
+    }
+}
+
+impl<K, V, const CAP: usize> ops::Deref for TiArrayVec<K, V, CAP> {
+    type Target = TiSlice<K, V>;
+
+    fn deref(&self) -> &TiSlice<K, V> {
+        self.as_slice()
+    }
+}
+
+impl<K, V, const CAP: usize> ops::DerefMut for TiArrayVec<K, V, CAP> {
+    fn deref_mut(&mut self) -> &mut TiSlice<K, V> {
+        self.as_mut_slice()
+    }
+}
+
+impl<K, V, const CAP: usize> Extend<V> for TiArrayVec<K, V, CAP> {
+    /// Extends the vector with the contents of an iterator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the elements do not fit into the vector.
+    #[inline]
+    fn extend<I: IntoIterator<Item = V>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<'a, K, V: 'a + Copy, const CAP: usize> Extend<&'a V> for TiArrayVec<K, V, CAP> {
+    /// Extends the vector with the contents of an iterator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the elements do not fit into the vector.
+    #[inline]
+    fn extend<I: IntoIterator<Item = &'a V>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+
+impl<K, V, const CAP: usize> FromIterator<V> for TiArrayVec<K, V, CAP> {
+    /// Creates a vector from the contents of an iterator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the elements do not fit into the vector.
+    fn from_iter<I: IntoIterator<Item = V>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<K, A, B, const CAP_A: usize, const CAP_B: usize> PartialEq<TiArrayVec<K, B, CAP_B>>
+    for TiArrayVec<K, A, CAP_A>
+where
+    A: PartialEq<B>,
+{
+    #[inline]
+    fn eq(&self, other: &TiArrayVec<K, B, CAP_B>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<'a, K, A, B, const CAP: usize> PartialEq<&'a TiSlice<K, B>> for TiArrayVec<K, A, CAP>
+where
+    A: PartialEq<B>,
+{
+    #[inline]
+    fn eq(&self, other: &&'a TiSlice<K, B>) -> bool {
+        self.as_slice() == *other
+    }
+}
+
+impl<K, V, const CAP: usize> PartialOrd<TiArrayVec<K, V, CAP>> for TiArrayVec<K, V, CAP>
+where
+    V: PartialOrd<V>,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &TiArrayVec<K, V, CAP>) -> Option<Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<K, V, const CAP: usize> Clone for TiArrayVec<K, V, CAP>
+where
+    V: Clone,
+{
+    fn clone(&self) -> TiArrayVec<K, V, CAP> {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<K, V, const CAP: usize> Default for TiArrayVec<K, V, CAP> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, const CAP: usize> Eq for TiArrayVec<K, V, CAP> where V: Eq {}
+
+impl<K, V, const CAP: usize> Hash for TiArrayVec<K, V, CAP>
+where
+    V: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().raw.hash(state)
+    }
+}
+
+impl<K, V, const CAP: usize> Ord for TiArrayVec<K, V, CAP>
+where
+    V: Ord,
+{
+    #[inline]
+    fn cmp(&self, other: &TiArrayVec<K, V, CAP>) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+#[cfg(any(feature = "alloc", feature = "std"))]
+impl<K, V, const CAP: usize> From<TiArrayVec<K, V, CAP>> for TiVec<K, V> {
+    fn from(vec: TiArrayVec<K, V, CAP>) -> Self {
+        let mut result = Vec::with_capacity(vec.len());
+        result.extend(vec);
+        result.into()
+    }
+}
+
+impl<K, V, const CAP: usize> IntoIterator for TiArrayVec<K, V, CAP> {
+    type Item = V;
+    type IntoIter = TiArrayVecIntoIter<V, CAP>;
+
+    #[inline]
+    fn into_iter(self) -> TiArrayVecIntoIter<V, CAP> {
+        let vec = ManuallyDrop::new(self);
+        TiArrayVecIntoIter {
+            // SAFETY: `vec` is not dropped, so the elements are moved into the iterator.
+            raw: unsafe { ptr::read(&vec.raw) },
+            front: 0,
+            back: vec.len,
+        }
+    }
+}
+
+impl<'a, K, V, const CAP: usize> IntoIterator for &'a TiArrayVec<K, V, CAP> {
+    type Item = &'a V;
+    type IntoIter = slice::Iter<'a, V>;
+
+    #[inline]
+    fn into_iter(self) -> slice::Iter<'a, V> {
+        self.as_slice().raw.iter()
+    }
+}
+
+impl<'a, K, V, const CAP: usize> IntoIterator for &'a mut TiArrayVec<K, V, CAP> {
+    type Item = &'a mut V;
+    type IntoIter = slice::IterMut<'a, V>;
+
+    #[inline]
+    fn into_iter(self) -> slice::IterMut<'a, V> {
+        self.as_mut_slice().raw.iter_mut()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K, V: Serialize, const CAP: usize> Serialize for TiArrayVec<K, V, CAP> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_slice().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V: Deserialize<'de>, const CAP: usize> Deserialize<'de> for TiArrayVec<K, V, CAP> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ArrayVecVisitor<K, V, const CAP: usize>(PhantomData<fn() -> (K, V)>);
+
+        impl<'de, K, V: Deserialize<'de>, const CAP: usize> Visitor<'de> for ArrayVecVisitor<K, V, CAP> {
+            type Value = TiArrayVec<K, V, CAP>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(formatter, "a sequence of at most {} elements", CAP)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut vec = TiArrayVec::new();
+                while let Some(value) = seq.next_element()? {
+                    if vec.try_push(value).is_err() {
+                        return Err(A::Error::invalid_length(CAP + 1, &self));
+                    }
+                }
+                Ok(vec)
+            }
+        }
+
+        deserializer.deserialize_seq(ArrayVecVisitor(PhantomData))
+    }
+}
+
+/// A draining iterator for [`TiArrayVec`].
+///
+/// This struct is created by the [`TiArrayVec::drain`] method.
+///
+/// [`TiArrayVec`]: struct.TiArrayVec.html
+/// [`TiArrayVec::drain`]: struct.TiArrayVec.html#method.drain
+pub struct TiArrayVecDrain<'a, V> {
+    base: *mut V,
+    len: &'a mut usize,
+    front: usize,
+    back: usize,
+    tail_start: usize,
+    tail_len: usize,
+    _marker: PhantomData<&'a mut V>,
+}
+
+// SAFETY: The drain only provides access to the elements of the borrowed vector.
+unsafe impl<V: Send> Send for TiArrayVecDrain<'_, V> {}
+
+// SAFETY: The drain only provides access to the elements of the borrowed vector.
+unsafe impl<V: Sync> Sync for TiArrayVecDrain<'_, V> {}
+
+impl<V> TiArrayVecDrain<'_, V> {
+    fn remaining(&self) -> &[V] {
+        // SAFETY: Elements at `front..back` are initialized and not yet yielded.
+        unsafe { slice::from_raw_parts(self.base.add(self.front), self.back - self.front) }
+    }
+}
+
+impl<V> Iterator for TiArrayVecDrain<'_, V> {
+    type Item = V;
+
+    #[inline]
+    fn next(&mut self) -> Option<V> {
+        if self.front < self.back {
+            // SAFETY: The element is initialized and is not yielded again.
+            let value = unsafe { self.base.add(self.front).read() };
+            self.front += 1;
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<V> DoubleEndedIterator for TiArrayVecDrain<'_, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<V> {
+        if self.front < self.back {
+            self.back -= 1;
+            // SAFETY: The element is initialized and is not yielded again.
+            Some(unsafe { self.base.add(self.back).read() })
+        } else {
+            None
+        }
+    }
+}
+
+impl<V> ExactSizeIterator for TiArrayVecDrain<'_, V> {}
+
+impl<V> FusedIterator for TiArrayVecDrain<'_, V> {}
+
+impl<V> Drop for TiArrayVecDrain<'_, V> {
+    fn drop(&mut self) {
+        // SAFETY: Drops the elements that were not yielded
+        // and moves the tail to close the gap.
+        unsafe {
+            let remaining =
+                ptr::slice_from_raw_parts_mut(self.base.add(self.front), self.back - self.front);
+            self.front = self.back;
+            ptr::drop_in_place(remaining);
+            let start = *self.len;
+            ptr::copy(
+                self.base.add(self.tail_start),
+                self.base.add(start),
+                self.tail_len,
+            );
+            *self.len = start + self.tail_len;
+        }
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for TiArrayVecDrain<'_, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TiArrayVecDrain")
+            .field(&self.remaining())
+            .finish()
+    }
+}
+
+/// An iterator that moves out of a [`TiArrayVec`].
+///
+/// This struct is created by the `into_iter` method on [`TiArrayVec`].
+///
+/// [`TiArrayVec`]: struct.TiArrayVec.html
+pub struct TiArrayVecIntoIter<V, const CAP: usize> {
+    raw: [MaybeUninit<V>; CAP],
+    front: usize,
+    back: usize,
+}
+
+impl<V, const CAP: usize> TiArrayVecIntoIter<V, CAP> {
+    fn remaining(&self) -> &[V] {
+        // SAFETY: Elements at `front..back` are initialized and not yet yielded.
+        unsafe {
+            slice::from_raw_parts(
+                self.raw.as_ptr().cast::<V>().add(self.front),
+                self.back - self.front,
+            )
+        }
+    }
+}
+
+impl<V, const CAP: usize> Iterator for TiArrayVecIntoIter<V, CAP> {
+    type Item = V;
+
+    #[inline]
+    fn next(&mut self) -> Option<V> {
+        if self.front < self.back {
+            // SAFETY: The element is initialized and is not yielded again.
+            let value = unsafe { self.raw[self.front].as_ptr().read() };
+            self.front += 1;
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<V, const CAP: usize> DoubleEndedIterator for TiArrayVecIntoIter<V, CAP> {
+    #[inline]
+    fn next_back(&mut self) -> Option<V> {
+        if self.front < self.back {
+            self.back -= 1;
+            // SAFETY: The element is initialized and is not yielded again.
+            Some(unsafe { self.raw[self.back].as_ptr().read() })
+        } else {
+            None
+        }
+    }
+}
+
+impl<V, const CAP: usize> ExactSizeIterator for TiArrayVecIntoIter<V, CAP> {}
+
+impl<V, const CAP: usize> FusedIterator for TiArrayVecIntoIter<V, CAP> {}
+
+impl<V, const CAP: usize> Drop for TiArrayVecIntoIter<V, CAP> {
+    fn drop(&mut self) {
+        // SAFETY: Drops the elements that were not yielded.
+        unsafe {
+            let remaining = ptr::slice_from_raw_parts_mut(
+                self.raw.as_mut_ptr().cast::<V>().add(self.front),
+                self.back - self.front,
+            );
+            self.front = self.back;
+            ptr::drop_in_place(remaining);
+        }
+    }
+}
+
+impl<V: fmt::Debug, const CAP: usize> fmt::Debug for TiArrayVecIntoIter<V, CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TiArrayVecIntoIter")
+            .field(&self.remaining())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::Id;
+    use crate::{TiArrayVec, TiSlice};
+    use core::cell::Cell;
+
+    struct DropCounter<'a>(&'a Cell<usize>);
+
+    impl Drop for DropCounter<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn push_pop() {
+        let mut vec: TiArrayVec<Id, u32, 3> = TiArrayVec::new();
+        assert_eq!(vec.push_and_get_key(1), Id::from(0));
+        assert_eq!(vec.try_push_and_get_key(2).ok(), Some(Id::from(1)));
+        vec.push(3);
+        assert!(vec.is_full());
+        assert_eq!(vec.try_push(4).map_err(|err| err.element()), Err(4));
+        assert_eq!(vec.pop_key_value(), Some((Id::from(2), 3)));
+        assert_eq!(vec.remaining_capacity(), 1);
+        assert_eq!(vec, TiSlice::from_ref(&[1, 2]));
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut vec: TiArrayVec<Id, u32, 4> = [1, 2, 4].iter().copied().collect();
+        vec.insert(Id::from(2), 3);
+        assert_eq!(vec, TiSlice::from_ref(&[1, 2, 3, 4]));
+        assert_eq!(
+            vec.try_insert(Id::from(0), 0).map_err(|err| err.element()),
+            Err(0)
+        );
+        assert_eq!(vec.remove(Id::from(1)), 2);
+        assert_eq!(vec.swap_remove(Id::from(0)), 1);
+        assert_eq!(vec, TiSlice::from_ref(&[4, 3]));
+        vec.retain(|&value| value > 3);
+        assert_eq!(vec, TiSlice::from_ref(&[4]));
+    }
+
+    #[test]
+    fn drain() {
+        let mut vec: TiArrayVec<Id, u32, 8> = (0..6).collect();
+        let drained: TiArrayVec<Id, u32, 8> = vec.drain(Id::from(1)..Id::from(3)).collect();
+        assert_eq!(drained, TiSlice::from_ref(&[1, 2]));
+        assert_eq!(vec, TiSlice::from_ref(&[0, 3, 4, 5]));
+        {
+            let mut iter = vec.drain_enumerated(Id::from(1)..);
+            assert_eq!(iter.next(), Some((Id::from(0), 3)));
+        }
+        assert_eq!(vec, TiSlice::from_ref(&[0]));
+        let mut iter = vec.into_iter_enumerated();
+        assert_eq!(iter.next_back(), Some((Id::from(0), 0)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn resize_dedup_splice() {
+        let mut vec: TiArrayVec<Id, u32, 6> = [1, 1, 2, 2, 2, 3].iter().copied().collect();
+        vec.dedup();
+        assert_eq!(vec, TiSlice::from_ref(&[1, 2, 3]));
+        vec.dedup_by_key(|value| *value / 2);
+        assert_eq!(vec, TiSlice::from_ref(&[1, 2]));
+        vec.resize(4, 0);
+        assert_eq!(vec, TiSlice::from_ref(&[1, 2, 0, 0]));
+        assert!(vec.try_resize(7, 0).is_err());
+        let mut tail = vec.split_off(Id::from(1));
+        assert_eq!(tail, TiSlice::from_ref(&[2, 0, 0]));
+        vec.append(&mut tail);
+        assert!(tail.is_empty());
+        let removed = vec.splice(Id::from(1)..Id::from(3), [5, 6, 7].iter().copied());
+        assert!(removed.eq([2, 0].iter().copied()));
+        assert_eq!(vec, TiSlice::from_ref(&[1, 5, 6, 7, 0]));
+        assert!(vec
+            .try_splice(Id::from(0)..Id::from(0), [8, 9].iter().copied())
+            .is_err());
+        let removed = vec.splice(.., None);
+        assert_eq!(removed.len(), 5);
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "TiArrayVec capacity 2 exceeded")]
+    fn resize_overflow() {
+        let mut vec: TiArrayVec<Id, u32, 2> = TiArrayVec::new();
+        vec.resize(3, 0);
+    }
+
+    #[test]
+    fn drops() {
+        let counter = Cell::new(0);
+        let mut vec: TiArrayVec<Id, DropCounter<'_>, 8> =
+            (0..6).map(|_| DropCounter(&counter)).collect();
+        vec.truncate(5);
+        assert_eq!(counter.get(), 1);
+        drop(vec.drain(Id::from(0)..Id::from(2)));
+        assert_eq!(counter.get(), 3);
+        let mut index = 0;
+        vec.retain(|_| {
+            index += 1;
+            index != 2
+        });
+        assert_eq!(counter.get(), 4);
+        assert_eq!(vec.len(), 2);
+        let mut iter = vec.into_iter();
+        drop(iter.next());
+        assert_eq!(counter.get(), 5);
+        drop(iter);
+        assert_eq!(counter.get(), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "TiArrayVec capacity 2 exceeded")]
+    fn push_overflow() {
+        let mut vec: TiArrayVec<Id, u32, 2> = TiArrayVec::new();
+        vec.extend([1, 2, 3].iter());
+    }
+
+    #[cfg(any(feature = "alloc", feature = "std"))]
+    #[test]
+    fn conversions() {
+        use crate::TiVec;
+        use alloc::format;
+
+        let vec: TiArrayVec<Id, u32, 4> = [5, 7].iter().copied().collect();
+        assert_eq!(format!("{:?}", vec), "{Id(0): 5, Id(1): 7}");
+        let vec: TiVec<Id, u32> = vec.clone().into();
+        assert_eq!(vec.raw, [5, 7]);
+    }
+}
@@ -0,0 +1,402 @@
+use core::{
+    fmt,
+    iter::{FromIterator, FusedIterator},
+};
+
+use crate::{TiKey, TiSecondaryMap, TiSecondaryMapIter, TiVec};
+
+/// A bidirectional map between keys of the type `A` and keys of the type `B`
+/// that keeps a one-to-one correspondence.
+///
+/// Both directions are stored densely in [`TiSecondaryMap`]s,
+/// so the map suits compact key spaces such as ids of two intermediate representations.
+/// Every change updates both directions.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::{TiBiMap, TiVec};
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct OldId(usize);
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct NewId(usize);
+///
+/// let mut lowering: TiBiMap<OldId, NewId> = TiBiMap::new();
+/// assert_eq!(lowering.try_insert(OldId(2), NewId(0)), Ok(()));
+/// assert_eq!(lowering.try_insert(OldId(2), NewId(1)), Err((OldId(2), NewId(1))));
+/// assert_eq!(lowering.insert(OldId(0), NewId(1)), (None, None));
+/// assert_eq!(lowering.get_by_left(OldId(2)), Some(NewId(0)));
+/// assert_eq!(lowering.get_by_right(NewId(1)), Some(OldId(0)));
+///
+/// let (old_to_new, new_to_old) = lowering.into_vecs();
+/// assert_eq!(old_to_new, TiVec::from(vec![Some(NewId(1)), None, Some(NewId(0))]));
+/// assert_eq!(new_to_old, TiVec::from(vec![Some(OldId(2)), Some(OldId(0))]));
+/// ```
+///
+/// [`TiSecondaryMap`]: struct.TiSecondaryMap.html
+pub struct TiBiMap<A, B> {
+    left: TiSecondaryMap<A, B>,
+    right: TiSecondaryMap<B, A>,
+}
+
+impl<A, B> TiBiMap<A, B> {
+    /// Constructs a new, empty map.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            left: TiSecondaryMap::new(),
+            right: TiSecondaryMap::new(),
+        }
+    }
+
+    /// Constructs a new, empty map that can hold left keys below `left_capacity`
+    /// and right keys below `right_capacity` without reallocating.
+    #[inline]
+    pub fn with_capacity(left_capacity: usize, right_capacity: usize) -> Self {
+        Self {
+            left: TiSecondaryMap::with_capacity(left_capacity),
+            right: TiSecondaryMap::with_capacity(right_capacity),
+        }
+    }
+
+    /// Returns the number of pairs in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.left.len()
+    }
+
+    /// Returns `true` if the map contains no pairs.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.left.is_empty()
+    }
+
+    /// Removes all pairs from the map.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.left.clear();
+        self.right.clear();
+    }
+
+    /// Returns the map from left keys to right keys.
+    #[inline]
+    pub fn left(&self) -> &TiSecondaryMap<A, B> {
+        &self.left
+    }
+
+    /// Returns the map from right keys to left keys.
+    #[inline]
+    pub fn right(&self) -> &TiSecondaryMap<B, A> {
+        &self.right
+    }
+
+    /// Returns `true` if the left key is mapped.
+    #[inline]
+    pub fn contains_left(&self, left: A) -> bool
+    where
+        A: TiKey,
+    {
+        self.left.contains_key(left)
+    }
+
+    /// Returns `true` if the right key is mapped.
+    #[inline]
+    pub fn contains_right(&self, right: B) -> bool
+    where
+        B: TiKey,
+    {
+        self.right.contains_key(right)
+    }
+
+    /// Returns the right key mapped to the left key.
+    #[inline]
+    pub fn get_by_left(&self, left: A) -> Option<B>
+    where
+        A: TiKey,
+        B: Copy,
+    {
+        self.left.get(left).copied()
+    }
+
+    /// Returns the left key mapped to the right key.
+    #[inline]
+    pub fn get_by_right(&self, right: B) -> Option<A>
+    where
+        A: Copy,
+        B: TiKey,
+    {
+        self.right.get(right).copied()
+    }
+
+    /// Inserts the pair, removing the pairs that conflict with it.
+    ///
+    /// Returns the right key previously mapped to `left`
+    /// and the left key previously mapped to `right`.
+    pub fn insert(&mut self, left: A, right: B) -> (Option<B>, Option<A>)
+    where
+        A: TiKey + Copy,
+        B: TiKey + Copy,
+    {
+        let old_right = self.remove_by_left(left);
+        let old_left = self.remove_by_right(right);
+        let _ = self.left.insert(left, right);
+        let _ = self.right.insert(right, left);
+        (old_right, old_left)
+    }
+
+    /// Inserts the pair if neither key is mapped yet,
+    /// otherwise returns the rejected pair as an error.
+    pub fn try_insert(&mut self, left: A, right: B) -> Result<(), (A, B)>
+    where
+        A: TiKey + Copy,
+        B: TiKey + Copy,
+    {
+        if self.contains_left(left) || self.contains_right(right) {
+            Err((left, right))
+        } else {
+            let _ = self.left.insert(left, right);
+            let _ = self.right.insert(right, left);
+            Ok(())
+        }
+    }
+
+    /// Removes the pair with the left key and returns its right key.
+    #[inline]
+    pub fn remove_by_left(&mut self, left: A) -> Option<B>
+    where
+        A: TiKey,
+        B: TiKey + Copy,
+    {
+        let right = self.left.remove(left)?;
+        let _ = self.right.remove(right);
+        Some(right)
+    }
+
+    /// Removes the pair with the right key and returns its left key.
+    #[inline]
+    pub fn remove_by_right(&mut self, right: B) -> Option<A>
+    where
+        A: TiKey + Copy,
+        B: TiKey,
+    {
+        let left = self.right.remove(right)?;
+        let _ = self.left.remove(left);
+        Some(left)
+    }
+
+    /// Returns an iterator over pairs in ascending order of left keys.
+    #[inline]
+    pub fn iter(&self) -> TiBiMapIter<'_, A, B> {
+        TiBiMapIter {
+            inner: self.left.iter_enumerated(),
+        }
+    }
+
+    /// Converts the map into vectors mapping left keys to right keys and back.
+    #[inline]
+    pub fn into_vecs(self) -> (TiVec<A, Option<B>>, TiVec<B, Option<A>>) {
+        (self.left.into(), self.right.into())
+    }
+}
+
+impl<A, B> fmt::Debug for TiBiMap<A, B>
+where
+    A: fmt::Debug + TiKey,
+    B: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.left.iter_enumerated()).finish()
+    }
+}
+
+impl<A, B> Clone for TiBiMap<A, B>
+where
+    A: Clone,
+    B: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            left: self.left.clone(),
+            right: self.right.clone(),
+        }
+    }
+}
+
+impl<A, B> Default for TiBiMap<A, B> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A, B> PartialEq for TiBiMap<A, B>
+where
+    B: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.left == other.left
+    }
+}
+
+impl<A, B> Eq for TiBiMap<A, B> where B: Eq {}
+
+impl<A, B> Extend<(A, B)> for TiBiMap<A, B>
+where
+    A: TiKey + Copy,
+    B: TiKey + Copy,
+{
+    fn extend<I: IntoIterator<Item = (A, B)>>(&mut self, iter: I) {
+        for (left, right) in iter {
+            let _ = self.insert(left, right);
+        }
+    }
+}
+
+impl<A, B> FromIterator<(A, B)> for TiBiMap<A, B>
+where
+    A: TiKey + Copy,
+    B: TiKey + Copy,
+{
+    fn from_iter<I: IntoIterator<Item = (A, B)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, A, B> IntoIterator for &'a TiBiMap<A, B>
+where
+    A: TiKey,
+    B: Copy,
+{
+    type Item = (A, B);
+    type IntoIter = TiBiMapIter<'a, A, B>;
+
+    #[inline]
+    fn into_iter(self) -> TiBiMapIter<'a, A, B> {
+        self.iter()
+    }
+}
+
+/// An iterator over pairs of a [`TiBiMap`].
+///
+/// This struct is created by the [`TiBiMap::iter`] method.
+///
+/// [`TiBiMap`]: struct.TiBiMap.html
+/// [`TiBiMap::iter`]: struct.TiBiMap.html#method.iter
+pub struct TiBiMapIter<'a, A, B> {
+    inner: TiSecondaryMapIter<'a, A, B>,
+}
+
+impl<A, B> fmt::Debug for TiBiMapIter<'_, A, B>
+where
+    A: TiKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TiBiMapIter")
+            .field("len", &self.inner.len())
+            .finish()
+    }
+}
+
+impl<A, B> Iterator for TiBiMapIter<'_, A, B>
+where
+    A: TiKey,
+    B: Copy,
+{
+    type Item = (A, B);
+
+    #[inline]
+    fn next(&mut self) -> Option<(A, B)> {
+        self.inner.next().map(|(left, &right)| (left, right))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<A, B> DoubleEndedIterator for TiBiMapIter<'_, A, B>
+where
+    A: TiKey,
+    B: Copy,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<(A, B)> {
+        self.inner.next_back().map(|(left, &right)| (left, right))
+    }
+}
+
+impl<A, B> ExactSizeIterator for TiBiMapIter<'_, A, B>
+where
+    A: TiKey,
+    B: Copy,
+{
+}
+
+impl<A, B> FusedIterator for TiBiMapIter<'_, A, B>
+where
+    A: TiKey,
+    B: Copy,
+{
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::Id;
+    use crate::TiBiMap;
+    use alloc::{format, vec::Vec};
+    use core::iter::FromIterator;
+
+    #[test]
+    fn insert_conflicts() {
+        let id = Id::from;
+        let mut map: TiBiMap<Id, Id> = TiBiMap::with_capacity(4, 4);
+        assert_eq!(map.insert(id(0), id(10)), (None, None));
+        assert_eq!(map.insert(id(1), id(11)), (None, None));
+        assert_eq!(map.try_insert(id(2), id(11)), Err((id(2), id(11))));
+        assert_eq!(map.try_insert(id(1), id(12)), Err((id(1), id(12))));
+        assert_eq!(map.insert(id(0), id(11)), (Some(id(10)), Some(id(1))));
+        assert_eq!(map.len(), 1);
+        assert!(!map.contains_left(id(1)));
+        assert!(!map.contains_right(id(10)));
+        assert_eq!(map.get_by_left(id(0)), Some(id(11)));
+        assert_eq!(map.get_by_right(id(11)), Some(id(0)));
+        assert_eq!(format!("{:?}", map), "{Id(0): Id(11)}");
+    }
+
+    #[test]
+    fn remove_and_convert() {
+        let id = Id::from;
+        let mut map = TiBiMap::from_iter((0..5).map(|index| (id(index), id(4 - index))));
+        assert_eq!(map.remove_by_left(id(1)), Some(id(3)));
+        assert_eq!(map.remove_by_right(id(3)), None);
+        assert_eq!(map.remove_by_right(id(0)), Some(id(4)));
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            [(id(0), id(4)), (id(2), id(2)), (id(3), id(1))]
+        );
+        assert_eq!((&map).into_iter().next_back(), Some((id(3), id(1))));
+        assert_eq!(map.left().len(), map.right().len());
+        let clone = map.clone();
+        assert_eq!(clone, map);
+        map.clear();
+        assert!(map.is_empty());
+        assert_ne!(clone, map);
+        let (left, right) = clone.into_vecs();
+        assert_eq!(
+            left.raw,
+            [Some(id(4)), None, Some(id(2)), Some(id(1)), None]
+        );
+        assert_eq!(
+            right.raw,
+            [None, Some(id(3)), Some(id(2)), None, Some(id(0))]
+        );
+    }
+}
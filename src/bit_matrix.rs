@@ -0,0 +1,630 @@
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem,
+};
+
+use alloc::vec::Vec;
+
+use crate::bit_set::{
+    combine_words, contains_bit, insert_bit, iter_words, remove_bit, words_for, TiBitSetIter,
+};
+use crate::{TiBitVec, TiKey};
+
+/// A dense bit matrix relating row keys of the type `R` to column keys of the type `C`.
+///
+/// Every row stores a bit for every column,
+/// so the matrix is best suited for relations with many pairs.
+/// See [`TiSparseBitMatrix`] for a variant that only allocates used rows.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::TiBitMatrix;
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct BlockId(usize);
+///
+/// let mut reachable: TiBitMatrix<BlockId, BlockId> = TiBitMatrix::new(4, 4);
+/// assert!(reachable.insert(BlockId(0), BlockId(1)));
+/// assert!(reachable.insert(BlockId(1), BlockId(2)));
+/// assert!(reachable.insert(BlockId(2), BlockId(3)));
+/// reachable.transitive_closure();
+/// assert!(reachable.contains(BlockId(0), BlockId(3)));
+/// assert_eq!(
+///     reachable.iter_row(BlockId(1)).collect::<Vec<_>>(),
+///     [BlockId(2), BlockId(3)]
+/// );
+/// ```
+///
+/// [`TiSparseBitMatrix`]: struct.TiSparseBitMatrix.html
+pub struct TiBitMatrix<R, C> {
+    words: Vec<u64>,
+    num_rows: usize,
+    num_columns: usize,
+
+    /// Ties the matrix to the row and column key types `R` and `C`.
+    #[allow(clippy::type_complexity)]
+    _marker: PhantomData<(fn(R) -> R, fn(C) -> C)>,
+}
+
+/// A sparse bit matrix relating row keys of the type `R` to column keys of the type `C`.
+///
+/// Rows are stored as [`TiBitVec`]s that grow on insertion,
+/// and rows without any columns do not allocate.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::TiSparseBitMatrix;
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct LocalId(usize);
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct PointId(usize);
+///
+/// let mut live_at: TiSparseBitMatrix<LocalId, PointId> = TiSparseBitMatrix::new();
+/// assert!(live_at.insert(LocalId(7), PointId(1000)));
+/// assert!(live_at.union_rows(LocalId(7), LocalId(2)));
+/// assert!(live_at.contains(LocalId(2), PointId(1000)));
+/// assert_eq!(live_at.iter_row(LocalId(3)).count(), 0);
+/// ```
+///
+/// [`TiBitVec`]: struct.TiBitVec.html
+pub struct TiSparseBitMatrix<R, C> {
+    rows: Vec<TiBitVec<C>>,
+
+    /// Ties the matrix to the row key type `R`.
+    _marker: PhantomData<fn(R) -> R>,
+}
+
+#[cold]
+#[inline(never)]
+fn out_of_bounds(row: usize, column: usize, num_rows: usize, num_columns: usize) -> ! {
+    panic!(
+        "key indices ({}, {}) are out of bounds for the bit matrix with {} rows and {} columns",
+        row, column, num_rows, num_columns
+    )
+}
+
+#[cold]
+#[inline(never)]
+fn row_out_of_bounds(row: usize, num_rows: usize) -> ! {
+    panic!(
+        "row key index {} is out of bounds for the bit matrix with {} rows",
+        row, num_rows
+    )
+}
+
+impl<R, C> TiBitMatrix<R, C> {
+    /// Constructs a new, empty matrix with the specified numbers of rows and columns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of words of the matrix overflows `usize`.
+    #[inline]
+    pub fn new(num_rows: usize, num_columns: usize) -> Self {
+        let num_words = match num_rows.checked_mul(words_for(num_columns)) {
+            Some(num_words) => num_words,
+            None => panic!(
+                "bit matrix with {} rows and {} columns is too large",
+                num_rows, num_columns
+            ),
+        };
+        Self {
+            words: alloc::vec![0; num_words],
+            num_rows,
+            num_columns,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of rows of the matrix.
+    #[inline]
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    /// Returns the number of columns of the matrix.
+    #[inline]
+    pub fn num_columns(&self) -> usize {
+        self.num_columns
+    }
+
+    /// Returns `true` if the matrix contains the pair.
+    ///
+    /// Pairs outside of the matrix are never contained.
+    #[inline]
+    pub fn contains(&self, row: R, column: C) -> bool
+    where
+        R: TiKey,
+        C: TiKey,
+    {
+        let (row, column) = (row.to_usize(), column.to_usize());
+        row < self.num_rows
+            && column < self.num_columns
+            && contains_bit(self.row_words(row), column)
+    }
+
+    /// Adds the pair to the matrix and returns `true` if it was not present.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pair is outside of the matrix.
+    #[inline]
+    pub fn insert(&mut self, row: R, column: C) -> bool
+    where
+        R: TiKey,
+        C: TiKey,
+    {
+        let (row, column) = (row.to_usize(), column.to_usize());
+        if row >= self.num_rows || column >= self.num_columns {
+            out_of_bounds(row, column, self.num_rows, self.num_columns);
+        }
+        insert_bit(self.row_words_mut(row), column)
+    }
+
+    /// Removes the pair from the matrix and returns `true` if it was present.
+    #[inline]
+    pub fn remove(&mut self, row: R, column: C) -> bool
+    where
+        R: TiKey,
+        C: TiKey,
+    {
+        let (row, column) = (row.to_usize(), column.to_usize());
+        row < self.num_rows && remove_bit(self.row_words_mut(row), column)
+    }
+
+    /// Returns an iterator over column keys of the row in ascending order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row is outside of the matrix.
+    #[inline]
+    pub fn iter_row(&self, row: R) -> TiBitSetIter<'_, C>
+    where
+        R: TiKey,
+    {
+        let row = self.check_row(row);
+        iter_words(self.row_words(row))
+    }
+
+    /// Adds all columns of the `src` row to the `dst` row
+    /// and returns `true` if the `dst` row changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either row is outside of the matrix.
+    pub fn union_rows(&mut self, src: R, dst: R) -> bool
+    where
+        R: TiKey,
+    {
+        let src = self.check_row(src);
+        let dst = self.check_row(dst);
+        self.union_rows_at(src, dst)
+    }
+
+    /// Removes all pairs from the matrix.
+    #[inline]
+    pub fn clear(&mut self) {
+        for word in &mut self.words {
+            *word = 0;
+        }
+    }
+
+    #[inline]
+    fn check_row(&self, row: R) -> usize
+    where
+        R: TiKey,
+    {
+        let row = row.to_usize();
+        if row >= self.num_rows {
+            row_out_of_bounds(row, self.num_rows);
+        }
+        row
+    }
+
+    #[inline]
+    fn row_words(&self, row: usize) -> &[u64] {
+        let words_per_row = words_for(self.num_columns);
+        &self.words[row * words_per_row..(row + 1) * words_per_row]
+    }
+
+    #[inline]
+    fn row_words_mut(&mut self, row: usize) -> &mut [u64] {
+        let words_per_row = words_for(self.num_columns);
+        &mut self.words[row * words_per_row..(row + 1) * words_per_row]
+    }
+
+    fn union_rows_at(&mut self, src: usize, dst: usize) -> bool {
+        if src == dst {
+            return false;
+        }
+        let words_per_row = words_for(self.num_columns);
+        let (src_start, dst_start) = (src * words_per_row, dst * words_per_row);
+        if src < dst {
+            let (head, tail) = self.words.split_at_mut(dst_start);
+            combine_words(
+                &mut tail[..words_per_row],
+                &head[src_start..src_start + words_per_row],
+                |a, b| a | b,
+            )
+        } else {
+            let (head, tail) = self.words.split_at_mut(src_start);
+            combine_words(
+                &mut head[dst_start..dst_start + words_per_row],
+                &tail[..words_per_row],
+                |a, b| a | b,
+            )
+        }
+    }
+}
+
+impl<K> TiBitMatrix<K, K> {
+    /// Extends the relation to its transitive closure,
+    /// so that every row contains all columns reachable from it.
+    pub fn transitive_closure(&mut self) {
+        for via in 0..self.num_rows.min(self.num_columns) {
+            for row in 0..self.num_rows {
+                if contains_bit(self.row_words(row), via) {
+                    let _ = self.union_rows_at(via, row);
+                }
+            }
+        }
+    }
+}
+
+impl<R, C> TiSparseBitMatrix<R, C> {
+    /// Constructs a new, empty matrix.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            rows: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of rows the matrix currently spans,
+    /// one past the highest row key ever inserted into.
+    #[inline]
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns the columns of the row, or `None` if the row was never allocated.
+    #[inline]
+    pub fn row(&self, row: R) -> Option<&TiBitVec<C>>
+    where
+        R: TiKey,
+    {
+        self.rows.get(row.to_usize())
+    }
+
+    /// Returns `true` if the matrix contains the pair.
+    #[inline]
+    pub fn contains(&self, row: R, column: C) -> bool
+    where
+        R: TiKey,
+        C: TiKey,
+    {
+        self.row(row)
+            .map_or(false, |columns| columns.contains(column))
+    }
+
+    /// Adds the pair to the matrix, growing it if needed,
+    /// and returns `true` if it was not present.
+    #[inline]
+    pub fn insert(&mut self, row: R, column: C) -> bool
+    where
+        R: TiKey,
+        C: TiKey,
+    {
+        self.row_mut(row.to_usize()).insert(column)
+    }
+
+    /// Removes the pair from the matrix and returns `true` if it was present.
+    #[inline]
+    pub fn remove(&mut self, row: R, column: C) -> bool
+    where
+        R: TiKey,
+        C: TiKey,
+    {
+        self.rows
+            .get_mut(row.to_usize())
+            .map_or(false, |columns| columns.remove(column))
+    }
+
+    /// Returns an iterator over column keys of the row in ascending order.
+    ///
+    /// Rows that were never allocated are empty.
+    #[inline]
+    pub fn iter_row(&self, row: R) -> TiBitSetIter<'_, C>
+    where
+        R: TiKey,
+    {
+        match self.row(row) {
+            Some(columns) => columns.iter(),
+            None => iter_words(&[]),
+        }
+    }
+
+    /// Adds all columns of the `src` row to the `dst` row, growing the matrix if needed,
+    /// and returns `true` if the `dst` row changed.
+    pub fn union_rows(&mut self, src: R, dst: R) -> bool
+    where
+        R: TiKey,
+    {
+        self.union_rows_at(src.to_usize(), dst.to_usize())
+    }
+
+    /// Removes all rows from the matrix.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.rows.clear();
+    }
+
+    fn row_mut(&mut self, row: usize) -> &mut TiBitVec<C> {
+        if row >= self.rows.len() {
+            self.rows.resize_with(row + 1, TiBitVec::new);
+        }
+        &mut self.rows[row]
+    }
+
+    fn union_rows_at(&mut self, src: usize, dst: usize) -> bool {
+        if src == dst || self.rows.get(src).map_or(true, TiBitVec::is_empty) {
+            return false;
+        }
+        let columns = mem::take(&mut self.rows[src]);
+        let changed = self.row_mut(dst).union(&columns);
+        self.rows[src] = columns;
+        changed
+    }
+}
+
+impl<K> TiSparseBitMatrix<K, K>
+where
+    K: TiKey,
+{
+    /// Extends the relation to its transitive closure,
+    /// so that every row contains all columns reachable from it.
+    pub fn transitive_closure(&mut self) {
+        for via in 0..self.rows.len() {
+            for row in 0..self.rows.len() {
+                if self.rows[row].contains(K::from_usize(via)) {
+                    let _ = self.union_rows_at(via, row);
+                }
+            }
+        }
+    }
+}
+
+impl<R, C> fmt::Debug for TiBitMatrix<R, C>
+where
+    R: fmt::Debug + TiKey,
+    C: fmt::Debug + TiKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries((0..self.num_rows).flat_map(|row| {
+                iter_words(self.row_words(row)).map(move |column: C| (R::from_usize(row), column))
+            }))
+            .finish()
+    }
+}
+
+impl<R, C> fmt::Debug for TiSparseBitMatrix<R, C>
+where
+    R: fmt::Debug + TiKey,
+    C: fmt::Debug + TiKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(self.rows.iter().enumerate().flat_map(|(row, columns)| {
+                columns
+                    .iter()
+                    .map(move |column| (R::from_usize(row), column))
+            }))
+            .finish()
+    }
+}
+
+impl<R, C> Clone for TiBitMatrix<R, C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            words: self.words.clone(),
+            num_rows: self.num_rows,
+            num_columns: self.num_columns,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R, C> Clone for TiSparseBitMatrix<R, C> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            rows: self.rows.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R, C> Default for TiBitMatrix<R, C> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(0, 0)
+    }
+}
+
+impl<R, C> Default for TiSparseBitMatrix<R, C> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R, C> PartialEq for TiBitMatrix<R, C> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.num_rows == other.num_rows
+            && self.num_columns == other.num_columns
+            && self.words == other.words
+    }
+}
+
+impl<R, C> Eq for TiBitMatrix<R, C> {}
+
+impl<R, C> Hash for TiBitMatrix<R, C> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.num_rows.hash(state);
+        self.num_columns.hash(state);
+        self.words.hash(state);
+    }
+}
+
+impl<R, C> PartialEq for TiSparseBitMatrix<R, C> {
+    fn eq(&self, other: &Self) -> bool {
+        let (shorter, longer) = if self.rows.len() <= other.rows.len() {
+            (&self.rows, &other.rows)
+        } else {
+            (&other.rows, &self.rows)
+        };
+        shorter.iter().zip(longer).all(|(a, b)| a == b)
+            && longer[shorter.len()..].iter().all(TiBitVec::is_empty)
+    }
+}
+
+impl<R, C> Eq for TiSparseBitMatrix<R, C> {}
+
+impl<R, C> Hash for TiSparseBitMatrix<R, C> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let len = self
+            .rows
+            .iter()
+            .rposition(|columns| !columns.is_empty())
+            .map_or(0, |row| row + 1);
+        self.rows[..len].hash(state);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::{ids, Id};
+    use crate::{TiBitMatrix, TiSparseBitMatrix};
+    use alloc::{format, vec::Vec};
+
+    #[test]
+    fn dense_matrix() {
+        let mut matrix: TiBitMatrix<Id, Id> = TiBitMatrix::new(3, 70);
+        assert!(matrix.insert(Id::from(0), Id::from(1)));
+        assert!(!matrix.insert(Id::from(0), Id::from(1)));
+        assert!(matrix.insert(Id::from(2), Id::from(69)));
+        assert!(!matrix.contains(Id::from(3), Id::from(1)));
+        assert!(matrix.union_rows(Id::from(2), Id::from(0)));
+        assert!(!matrix.union_rows(Id::from(2), Id::from(0)));
+        assert!(matrix.union_rows(Id::from(0), Id::from(1)));
+        assert_eq!(
+            matrix.iter_row(Id::from(1)).collect::<Vec<_>>(),
+            ids(&[1, 69])
+        );
+        assert!(matrix.remove(Id::from(1), Id::from(1)));
+        assert_eq!(
+            format!("{:?}", matrix),
+            "{(Id(0), Id(1)), (Id(0), Id(69)), (Id(1), Id(69)), (Id(2), Id(69))}"
+        );
+        matrix.clear();
+        assert_eq!(matrix, TiBitMatrix::new(3, 70));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "key indices (1, 4) are out of bounds for the bit matrix with 2 rows and 4 columns"
+    )]
+    fn dense_out_of_bounds() {
+        let mut matrix: TiBitMatrix<Id, Id> = TiBitMatrix::new(2, 4);
+        let _ = matrix.insert(Id::from(1), Id::from(4));
+    }
+
+    #[test]
+    fn transitive_closure() {
+        let edges = [(0, 1), (1, 2), (2, 0), (3, 4)];
+        let mut dense: TiBitMatrix<Id, Id> = TiBitMatrix::new(5, 5);
+        let mut sparse: TiSparseBitMatrix<Id, Id> = TiSparseBitMatrix::new();
+        for &(from, to) in &edges {
+            let _ = dense.insert(Id::from(from), Id::from(to));
+            let _ = sparse.insert(Id::from(from), Id::from(to));
+        }
+        dense.transitive_closure();
+        sparse.transitive_closure();
+        for row in 0..3 {
+            assert_eq!(
+                dense.iter_row(Id::from(row)).collect::<Vec<_>>(),
+                ids(&[0, 1, 2])
+            );
+            assert_eq!(
+                sparse.iter_row(Id::from(row)).collect::<Vec<_>>(),
+                ids(&[0, 1, 2])
+            );
+        }
+        assert_eq!(dense.iter_row(Id::from(3)).collect::<Vec<_>>(), ids(&[4]));
+        assert_eq!(sparse.iter_row(Id::from(3)).collect::<Vec<_>>(), ids(&[4]));
+        assert_eq!(dense.iter_row(Id::from(4)).count(), 0);
+        assert_eq!(sparse.iter_row(Id::from(4)).count(), 0);
+    }
+
+    #[test]
+    fn sparse_matrix() {
+        let mut matrix: TiSparseBitMatrix<Id, Id> = TiSparseBitMatrix::new();
+        assert!(!matrix.union_rows(Id::from(5), Id::from(9)));
+        assert_eq!(matrix.num_rows(), 0);
+        assert!(matrix.insert(Id::from(1), Id::from(300)));
+        assert!(matrix.union_rows(Id::from(1), Id::from(4)));
+        assert_eq!(matrix.num_rows(), 5);
+        assert!(matrix.row(Id::from(2)).unwrap().is_empty());
+        assert!(matrix.remove(Id::from(1), Id::from(300)));
+        assert!(!matrix.remove(Id::from(8), Id::from(300)));
+        assert_eq!(format!("{:?}", matrix), "{(Id(4), Id(300))}");
+
+        let mut other = TiSparseBitMatrix::new();
+        let _ = other.insert(Id::from(4), Id::from(300));
+        assert_eq!(matrix, other);
+        let _ = other.insert(Id::from(9), Id::from(0));
+        assert!(other.remove(Id::from(9), Id::from(0)));
+        assert_eq!(matrix, other);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn sparse_hash_ignores_empty_rows() {
+        use alloc::collections::hash_map::DefaultHasher;
+        use core::hash::{Hash, Hasher};
+
+        fn hash_of(matrix: &TiSparseBitMatrix<Id, Id>) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            matrix.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let mut matrix = TiSparseBitMatrix::new();
+        let _ = matrix.insert(Id::from(1), Id::from(70));
+        let mut other = matrix.clone();
+        let _ = other.insert(Id::from(6), Id::from(2));
+        assert!(other.remove(Id::from(6), Id::from(2)));
+        assert!(other.remove(Id::from(1), Id::from(70)));
+        assert!(other.insert(Id::from(1), Id::from(70)));
+        assert_eq!(matrix, other);
+        assert_eq!(hash_of(&matrix), hash_of(&other));
+    }
+
+    #[test]
+    #[should_panic(expected = "is too large")]
+    fn dense_size_overflow() {
+        let _: TiBitMatrix<Id, Id> = TiBitMatrix::new(usize::MAX / 2, 256);
+    }
+}
@@ -0,0 +1,725 @@
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+    iter::{self, FromIterator, FusedIterator},
+    marker::PhantomData,
+    slice,
+};
+
+use alloc::vec::Vec;
+
+use crate::TiKey;
+
+pub(crate) const WORD_BITS: usize = u64::BITS as usize;
+
+/// A fixed-size set of keys of the type `K` stored as a bitmap.
+///
+/// Keys must be below the domain size passed on construction.
+/// Collecting keys from an iterator sets the domain size one past the highest key.
+/// See [`TiBitVec`] for a variant that grows on insertion.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::TiBitSet;
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct BlockId(usize);
+///
+/// let mut live: TiBitSet<BlockId> = TiBitSet::new(100);
+/// assert!(live.insert(BlockId(3)));
+/// assert!(!live.insert(BlockId(3)));
+/// let mut visited: TiBitSet<BlockId> = TiBitSet::new(100);
+/// assert!(visited.insert(BlockId(70)));
+/// assert!(live.union(&visited));
+/// assert!(!live.union(&visited));
+/// assert_eq!(live.count(), 2);
+/// assert_eq!(live.iter().collect::<Vec<_>>(), [BlockId(3), BlockId(70)]);
+/// ```
+///
+/// [`TiBitVec`]: struct.TiBitVec.html
+pub struct TiBitSet<K> {
+    words: Vec<u64>,
+    domain_size: usize,
+
+    /// Ties the container to the key type `K`.
+    _marker: PhantomData<fn(K) -> K>,
+}
+
+/// A set of keys of the type `K` stored as a bitmap that grows on insertion.
+///
+/// Bit vectors are equal if they contain the same keys, regardless of their lengths.
+/// See [`TiBitSet`] for a variant with a fixed domain size.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::TiBitVec;
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct LocalId(usize);
+///
+/// let mut used: TiBitVec<LocalId> = TiBitVec::new();
+/// assert!(used.insert(LocalId(130)));
+/// assert_eq!(used.domain_size(), 131);
+/// assert!(used.contains(LocalId(130)));
+/// assert!(!used.contains(LocalId(1000)));
+///
+/// let moved: TiBitVec<LocalId> = [LocalId(5), LocalId(130)].iter().copied().collect();
+/// assert!(used.subtract(&moved));
+/// assert!(used.is_empty());
+/// ```
+///
+/// [`TiBitSet`]: struct.TiBitSet.html
+pub struct TiBitVec<K> {
+    words: Vec<u64>,
+    domain_size: usize,
+
+    /// Ties the container to the key type `K`.
+    _marker: PhantomData<fn(K) -> K>,
+}
+
+#[inline]
+pub(crate) fn words_for(len: usize) -> usize {
+    (len + WORD_BITS - 1) / WORD_BITS
+}
+
+#[inline]
+pub(crate) fn contains_bit(words: &[u64], index: usize) -> bool {
+    words
+        .get(index / WORD_BITS)
+        .map_or(false, |word| word >> (index % WORD_BITS) & 1 != 0)
+}
+
+#[inline]
+pub(crate) fn insert_bit(words: &mut [u64], index: usize) -> bool {
+    let word = &mut words[index / WORD_BITS];
+    let old = *word;
+    *word |= 1 << (index % WORD_BITS);
+    *word != old
+}
+
+#[inline]
+pub(crate) fn remove_bit(words: &mut [u64], index: usize) -> bool {
+    match words.get_mut(index / WORD_BITS) {
+        Some(word) => {
+            let old = *word;
+            *word &= !(1 << (index % WORD_BITS));
+            *word != old
+        }
+        None => false,
+    }
+}
+
+pub(crate) fn count_bits(words: &[u64]) -> usize {
+    words.iter().map(|word| word.count_ones() as usize).sum()
+}
+
+pub(crate) fn combine_words(
+    words: &mut [u64],
+    other: &[u64],
+    op: impl Fn(u64, u64) -> u64,
+) -> bool {
+    let mut changed = false;
+    for (word, &other) in words.iter_mut().zip(other) {
+        let new = op(*word, other);
+        changed |= new != *word;
+        *word = new;
+    }
+    changed
+}
+
+fn trimmed_words(words: &[u64]) -> &[u64] {
+    let len = words
+        .iter()
+        .rposition(|&word| word != 0)
+        .map_or(0, |pos| pos + 1);
+    &words[..len]
+}
+
+pub(crate) fn iter_words<K>(words: &[u64]) -> TiBitSetIter<'_, K> {
+    TiBitSetIter {
+        words: words.iter().enumerate(),
+        word: 0,
+        base: 0,
+        len: count_bits(words),
+        _marker: PhantomData,
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn out_of_domain(index: usize, domain_size: usize) -> ! {
+    panic!(
+        "key index {} is out of bounds for the bit set with domain size {}",
+        index, domain_size
+    )
+}
+
+#[cold]
+#[inline(never)]
+fn domain_mismatch(domain_size: usize, other: usize) -> ! {
+    panic!(
+        "bit set domain sizes do not match: {} and {}",
+        domain_size, other
+    )
+}
+
+impl<K> TiBitSet<K> {
+    /// Constructs a new, empty set for keys below `domain_size`.
+    #[inline]
+    pub fn new(domain_size: usize) -> Self {
+        Self {
+            words: alloc::vec![0; words_for(domain_size)],
+            domain_size,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs a new set containing every key below `domain_size`.
+    pub fn new_filled(domain_size: usize) -> Self {
+        let mut set = Self::new(domain_size);
+        set.insert_all();
+        set
+    }
+
+    /// Returns the domain size of the set.
+    #[inline]
+    pub fn domain_size(&self) -> usize {
+        self.domain_size
+    }
+
+    /// Returns the number of keys in the set.
+    #[inline]
+    pub fn count(&self) -> usize {
+        count_bits(&self.words)
+    }
+
+    /// Returns `true` if the set contains no keys.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// Returns `true` if the set contains the key.
+    ///
+    /// Keys outside of the domain are never contained.
+    #[inline]
+    pub fn contains(&self, key: K) -> bool
+    where
+        K: TiKey,
+    {
+        contains_bit(&self.words, key.to_usize())
+    }
+
+    /// Adds the key to the set and returns `true` if it was not present.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is outside of the domain.
+    #[inline]
+    pub fn insert(&mut self, key: K) -> bool
+    where
+        K: TiKey,
+    {
+        let index = key.to_usize();
+        if index >= self.domain_size {
+            out_of_domain(index, self.domain_size);
+        }
+        insert_bit(&mut self.words, index)
+    }
+
+    /// Removes the key from the set and returns `true` if it was present.
+    #[inline]
+    pub fn remove(&mut self, key: K) -> bool
+    where
+        K: TiKey,
+    {
+        remove_bit(&mut self.words, key.to_usize())
+    }
+
+    /// Adds every key of the domain to the set.
+    pub fn insert_all(&mut self) {
+        for word in &mut self.words {
+            *word = !0;
+        }
+        let tail = self.domain_size % WORD_BITS;
+        if tail != 0 {
+            if let Some(last) = self.words.last_mut() {
+                *last = (1 << tail) - 1;
+            }
+        }
+    }
+
+    /// Removes all keys from the set.
+    #[inline]
+    pub fn clear(&mut self) {
+        for word in &mut self.words {
+            *word = 0;
+        }
+    }
+
+    /// Adds all keys of `other` to the set and returns `true` if the set changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the domain sizes differ.
+    pub fn union(&mut self, other: &Self) -> bool {
+        self.check_domain(other);
+        combine_words(&mut self.words, &other.words, |a, b| a | b)
+    }
+
+    /// Removes all keys not in `other` from the set and returns `true` if the set changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the domain sizes differ.
+    pub fn intersect(&mut self, other: &Self) -> bool {
+        self.check_domain(other);
+        combine_words(&mut self.words, &other.words, |a, b| a & b)
+    }
+
+    /// Removes all keys of `other` from the set and returns `true` if the set changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the domain sizes differ.
+    pub fn subtract(&mut self, other: &Self) -> bool {
+        self.check_domain(other);
+        combine_words(&mut self.words, &other.words, |a, b| a & !b)
+    }
+
+    /// Returns an iterator over keys of the set in ascending order.
+    #[inline]
+    pub fn iter(&self) -> TiBitSetIter<'_, K> {
+        iter_words(&self.words)
+    }
+
+    #[inline]
+    fn check_domain(&self, other: &Self) {
+        if self.domain_size != other.domain_size {
+            domain_mismatch(self.domain_size, other.domain_size);
+        }
+    }
+}
+
+impl<K> TiBitVec<K> {
+    /// Constructs a new, empty bit vector.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            words: Vec::new(),
+            domain_size: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs a new, empty bit vector
+    /// that can hold keys below `capacity` without reallocating.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            words: Vec::with_capacity(words_for(capacity)),
+            domain_size: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of bits the vector currently spans,
+    /// one past the highest key ever inserted.
+    #[inline]
+    pub fn domain_size(&self) -> usize {
+        self.domain_size
+    }
+
+    /// Returns `true` if the vector contains no keys.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// Returns the number of keys in the vector.
+    #[inline]
+    pub fn count(&self) -> usize {
+        count_bits(&self.words)
+    }
+
+    /// Returns `true` if the vector contains the key.
+    #[inline]
+    pub fn contains(&self, key: K) -> bool
+    where
+        K: TiKey,
+    {
+        contains_bit(&self.words, key.to_usize())
+    }
+
+    /// Adds the key to the vector, growing it if needed,
+    /// and returns `true` if it was not present.
+    pub fn insert(&mut self, key: K) -> bool
+    where
+        K: TiKey,
+    {
+        let index = key.to_usize();
+        self.grow(index + 1);
+        insert_bit(&mut self.words, index)
+    }
+
+    /// Removes the key from the vector and returns `true` if it was present.
+    #[inline]
+    pub fn remove(&mut self, key: K) -> bool
+    where
+        K: TiKey,
+    {
+        remove_bit(&mut self.words, key.to_usize())
+    }
+
+    /// Removes all keys from the vector and resets its domain size to zero.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.words.clear();
+        self.domain_size = 0;
+    }
+
+    /// Adds all keys of `other` to the vector, growing it if needed,
+    /// and returns `true` if the vector changed.
+    pub fn union(&mut self, other: &Self) -> bool {
+        self.grow(other.domain_size);
+        combine_words(&mut self.words, &other.words, |a, b| a | b)
+    }
+
+    /// Removes all keys not in `other` from the vector
+    /// and returns `true` if the vector changed.
+    pub fn intersect(&mut self, other: &Self) -> bool {
+        let common_len = other.words.len().min(self.words.len());
+        let (common, rest) = self.words.split_at_mut(common_len);
+        let mut changed = combine_words(common, &other.words, |a, b| a & b);
+        for word in rest {
+            changed |= *word != 0;
+            *word = 0;
+        }
+        changed
+    }
+
+    /// Removes all keys of `other` from the vector and returns `true` if the vector changed.
+    pub fn subtract(&mut self, other: &Self) -> bool {
+        combine_words(&mut self.words, &other.words, |a, b| a & !b)
+    }
+
+    /// Returns an iterator over keys of the vector in ascending order.
+    #[inline]
+    pub fn iter(&self) -> TiBitSetIter<'_, K> {
+        iter_words(&self.words)
+    }
+
+    fn grow(&mut self, domain_size: usize) {
+        if domain_size > self.domain_size {
+            self.domain_size = domain_size;
+            self.words.resize(words_for(domain_size), 0);
+        }
+    }
+}
+
+impl<K> fmt::Debug for TiBitSet<K>
+where
+    K: fmt::Debug + TiKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<K> fmt::Debug for TiBitVec<K>
+where
+    K: fmt::Debug + TiKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<K> Clone for TiBitSet<K> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            words: self.words.clone(),
+            domain_size: self.domain_size,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K> Clone for TiBitVec<K> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            words: self.words.clone(),
+            domain_size: self.domain_size,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K> Default for TiBitSet<K> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<K> Default for TiBitVec<K> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> PartialEq for TiBitSet<K> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.domain_size == other.domain_size && self.words == other.words
+    }
+}
+
+impl<K> Eq for TiBitSet<K> {}
+
+impl<K> Hash for TiBitSet<K> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.domain_size.hash(state);
+        self.words.hash(state);
+    }
+}
+
+impl<K> PartialEq for TiBitVec<K> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        trimmed_words(&self.words) == trimmed_words(&other.words)
+    }
+}
+
+impl<K> Eq for TiBitVec<K> {}
+
+impl<K> Hash for TiBitVec<K> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        trimmed_words(&self.words).hash(state);
+    }
+}
+
+impl<K> From<TiBitSet<K>> for TiBitVec<K> {
+    #[inline]
+    fn from(set: TiBitSet<K>) -> Self {
+        Self {
+            words: set.words,
+            domain_size: set.domain_size,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K> From<TiBitVec<K>> for TiBitSet<K> {
+    #[inline]
+    fn from(vec: TiBitVec<K>) -> Self {
+        Self {
+            words: vec.words,
+            domain_size: vec.domain_size,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K> Extend<K> for TiBitSet<K>
+where
+    K: TiKey,
+{
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        for key in iter {
+            let _ = self.insert(key);
+        }
+    }
+}
+
+impl<K> Extend<K> for TiBitVec<K>
+where
+    K: TiKey,
+{
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        for key in iter {
+            let _ = self.insert(key);
+        }
+    }
+}
+
+impl<K> FromIterator<K> for TiBitSet<K>
+where
+    K: TiKey,
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        TiBitVec::from_iter(iter).into()
+    }
+}
+
+impl<K> FromIterator<K> for TiBitVec<K>
+where
+    K: TiKey,
+{
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<'a, K> IntoIterator for &'a TiBitSet<K>
+where
+    K: TiKey,
+{
+    type Item = K;
+    type IntoIter = TiBitSetIter<'a, K>;
+
+    #[inline]
+    fn into_iter(self) -> TiBitSetIter<'a, K> {
+        self.iter()
+    }
+}
+
+impl<'a, K> IntoIterator for &'a TiBitVec<K>
+where
+    K: TiKey,
+{
+    type Item = K;
+    type IntoIter = TiBitSetIter<'a, K>;
+
+    #[inline]
+    fn into_iter(self) -> TiBitSetIter<'a, K> {
+        self.iter()
+    }
+}
+
+/// An iterator over keys of a [`TiBitSet`] or a [`TiBitVec`] in ascending order.
+///
+/// This struct is created by the [`TiBitSet::iter`] and [`TiBitVec::iter`] methods,
+/// as well as by the [`TiBitMatrix::iter_row`] and [`TiSparseBitMatrix::iter_row`] methods.
+///
+/// [`TiBitSet`]: struct.TiBitSet.html
+/// [`TiBitVec`]: struct.TiBitVec.html
+/// [`TiBitSet::iter`]: struct.TiBitSet.html#method.iter
+/// [`TiBitVec::iter`]: struct.TiBitVec.html#method.iter
+/// [`TiBitMatrix::iter_row`]: struct.TiBitMatrix.html#method.iter_row
+/// [`TiSparseBitMatrix::iter_row`]: struct.TiSparseBitMatrix.html#method.iter_row
+pub struct TiBitSetIter<'a, K> {
+    words: iter::Enumerate<slice::Iter<'a, u64>>,
+    word: u64,
+    base: usize,
+    len: usize,
+    _marker: PhantomData<fn(K) -> K>,
+}
+
+impl<K> fmt::Debug for TiBitSetIter<'_, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TiBitSetIter")
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl<K: TiKey> Iterator for TiBitSetIter<'_, K> {
+    type Item = K;
+
+    #[inline]
+    fn next(&mut self) -> Option<K> {
+        while self.word == 0 {
+            let (index, &word) = self.words.next()?;
+            self.word = word;
+            self.base = index * WORD_BITS;
+        }
+        let bit = self.word.trailing_zeros() as usize;
+        self.word &= self.word - 1;
+        self.len -= 1;
+        Some(K::from_usize(self.base + bit))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K: TiKey> ExactSizeIterator for TiBitSetIter<'_, K> {}
+
+impl<K: TiKey> FusedIterator for TiBitSetIter<'_, K> {}
+
+#[cfg(test)]
+mod test {
+    use crate::test::{ids, Id};
+    use crate::{TiBitSet, TiBitVec};
+    use alloc::{format, vec::Vec};
+    use core::iter::FromIterator;
+
+    #[test]
+    fn bit_set_operations() {
+        let mut a: TiBitSet<Id> = TiBitSet::new(130);
+        a.extend(ids(&[1, 64, 129]));
+        let b = TiBitSet::from_iter(ids(&[1, 2, 129]));
+        assert_eq!(b.domain_size(), 130);
+        assert!(a.contains(Id::from(64)));
+        assert!(!a.contains(Id::from(500)));
+
+        let mut union = a.clone();
+        assert!(union.union(&b));
+        assert!(!union.union(&b));
+        assert_eq!(union.iter().collect::<Vec<_>>(), ids(&[1, 2, 64, 129]));
+
+        let mut intersection = a.clone();
+        assert!(intersection.intersect(&b));
+        assert!(!intersection.intersect(&b));
+        assert_eq!(intersection.count(), 2);
+
+        assert!(a.subtract(&b));
+        assert!(!a.subtract(&b));
+        assert_eq!(format!("{:?}", a), "{Id(64)}");
+        assert!(a.remove(Id::from(64)));
+        assert!(a.is_empty());
+
+        let full: TiBitSet<Id> = TiBitSet::new_filled(70);
+        assert_eq!(full.count(), 70);
+        assert_eq!(full.iter().len(), 70);
+        assert_eq!(full.iter().last(), Some(Id::from(69)));
+    }
+
+    #[test]
+    #[should_panic(expected = "key index 10 is out of bounds for the bit set with domain size 10")]
+    fn bit_set_out_of_domain() {
+        let mut set: TiBitSet<Id> = TiBitSet::new(10);
+        let _ = set.insert(Id::from(10));
+    }
+
+    #[test]
+    fn bit_vec_grows() {
+        let mut a: TiBitVec<Id> = TiBitVec::new();
+        assert!(a.insert(Id::from(3)));
+        assert_eq!(a.domain_size(), 4);
+        let b = TiBitVec::from_iter(ids(&[3, 200]));
+        assert!(a.union(&b));
+        assert_eq!(a.domain_size(), 201);
+        assert_eq!(a, b);
+
+        let mut c = TiBitVec::from_iter(ids(&[200]));
+        assert!(a.intersect(&c));
+        assert_eq!(a.iter().collect::<Vec<_>>(), ids(&[200]));
+        assert!(c.insert(Id::from(300)));
+        assert!(!a.intersect(&c));
+        assert!(c.subtract(&a));
+        assert_eq!(c.iter().collect::<Vec<_>>(), ids(&[300]));
+
+        let set: TiBitSet<Id> = c.into();
+        assert_eq!(set.domain_size(), 301);
+        let mut vec: TiBitVec<Id> = set.into();
+        vec.clear();
+        assert!(vec.is_empty());
+        assert_eq!(vec, TiBitVec::new());
+    }
+}
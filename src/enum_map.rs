@@ -0,0 +1,417 @@
+use core::{
+    borrow::{Borrow, BorrowMut},
+    fmt,
+    hash::{Hash, Hasher},
+    iter::FromIterator,
+    marker::PhantomData,
+    ops, slice,
+};
+
+use alloc::{boxed::Box, vec, vec::Vec};
+
+#[cfg(feature = "serde")]
+use serde::ser::{Serialize, Serializer};
+
+#[cfg(any(feature = "serde-alloc", feature = "serde-std"))]
+use serde::de::{Deserialize, Deserializer, Error};
+
+use crate::{TiEnum, TiEnumerated, TiSlice};
+
+/// A total map type with a value for every variant
+/// of the enum key type `K`.
+///
+/// `TiEnumMap<K, V>` stores `K::COUNT` values in a boxed slice
+/// in the variant order defined by the [`TiEnum`] trait.
+/// It dereferences to [`TiSlice<K, V>`][`TiSlice`],
+/// so all slice methods are available with `K` used for element indices,
+/// but it can not be resized, so indexing with `K` never fails.
+///
+/// Iteration over `&TiEnumMap<K, V>` and `&mut TiEnumMap<K, V>`
+/// yields key-value pairs in variant order like [`TiSlice::iter_enumerated`].
+///
+/// Added methods:
+/// - [`from_fn`] - Creates a map where each value is produced by calling a function
+///   with its key.
+/// - [`map`] - Returns a map with a function applied to each value.
+/// - [`into_iter_enumerated`] - Converts the map into iterator over all key-value pairs.
+/// - [`into_boxed_slice`] - Converts the map into a boxed slice of values.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::{TiEnum, TiEnumMap, TiKey};
+///
+/// #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// enum Phase {
+///     Parse,
+///     Check,
+///     Emit,
+/// }
+///
+/// impl TiKey for Phase {
+///     const MAX: usize = 2;
+///
+///     fn from_usize(index: usize) -> Self {
+///         [Phase::Parse, Phase::Check, Phase::Emit][index]
+///     }
+///
+///     fn to_usize(self) -> usize {
+///         self as usize
+///     }
+/// }
+///
+/// impl TiEnum for Phase {
+///     const COUNT: usize = 3;
+/// }
+///
+/// let mut timings: TiEnumMap<Phase, u32> = TiEnumMap::default();
+/// timings[Phase::Check] += 10;
+/// timings[Phase::Emit] += 5;
+/// let mut iter = timings.iter_enumerated();
+/// assert_eq!(iter.next(), Some((Phase::Parse, &0)));
+/// assert_eq!(iter.next(), Some((Phase::Check, &10)));
+/// assert_eq!(iter.next(), Some((Phase::Emit, &5)));
+/// assert_eq!(iter.next(), None);
+/// ```
+///
+/// [`from_fn`]: #method.from_fn
+/// [`map`]: #method.map
+/// [`into_iter_enumerated`]: #method.into_iter_enumerated
+/// [`into_boxed_slice`]: #method.into_boxed_slice
+/// [`TiEnum`]: trait.TiEnum.html
+/// [`TiSlice`]: struct.TiSlice.html
+/// [`TiSlice::iter_enumerated`]: struct.TiSlice.html#method.iter_enumerated
+pub struct TiEnumMap<K, V> {
+    /// Boxed values with length equal to `K::COUNT`.
+    raw: Box<[V]>,
+
+    /// Ties the container to the key type `K`.
+    _marker: PhantomData<fn(K) -> K>,
+}
+
+impl<K, V> TiEnumMap<K, V>
+where
+    K: TiEnum,
+{
+    /// Creates a map where each value is produced by calling `f` with its key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use typed_index_collections::{TiEnum, TiEnumMap, TiKey};
+    /// # #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    /// # enum Axis { X, Y }
+    /// # impl TiKey for Axis {
+    /// #     const MAX: usize = 1;
+    /// #     fn from_usize(index: usize) -> Self { [Axis::X, Axis::Y][index] }
+    /// #     fn to_usize(self) -> usize { self as usize }
+    /// # }
+    /// # impl TiEnum for Axis { const COUNT: usize = 2; }
+    /// let names: TiEnumMap<Axis, char> = TiEnumMap::from_fn(|axis| match axis {
+    ///     Axis::X => 'x',
+    ///     Axis::Y => 'y',
+    /// });
+    /// assert_eq!(names[Axis::Y], 'y');
+    /// ```
+    pub fn from_fn<F>(f: F) -> Self
+    where
+        F: FnMut(K) -> V,
+    {
+        Self::from_raw((0..K::COUNT).map(K::from_index).map(f).collect())
+    }
+
+    /// Creates a map from a boxed slice of values in variant order.
+    ///
+    /// Returns the original boxed slice
+    /// if its length is not equal to the number of enum variants.
+    pub fn try_from_boxed_slice(raw: Box<[V]>) -> Result<Self, Box<[V]>> {
+        if raw.len() == K::COUNT {
+            Ok(Self::from_raw(raw))
+        } else {
+            Err(raw)
+        }
+    }
+
+    /// Converts the map into iterator over all key-value pairs in variant order.
+    ///
+    /// It acts like `self.into_iter().enumerate()`,
+    /// but use `K` instead of `usize` for iteration indices.
+    #[inline(always)]
+    pub fn into_iter_enumerated(self) -> TiEnumerated<vec::IntoIter<V>, K, V> {
+        self.raw
+            .into_vec()
+            .into_iter()
+            .enumerate()
+            .map(|(key, value)| (K::from_index(key), value))
+    }
+}
+
+impl<K, V> TiEnumMap<K, V> {
+    /// Creates a map from values in variant order
+    /// without checking that there is a value for every variant.
+    #[inline]
+    fn from_raw(raw: Box<[V]>) -> Self {
+        Self {
+            raw,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a map with function `f` applied to each value in variant order.
+    pub fn map<U, F>(self, f: F) -> TiEnumMap<K, U>
+    where
+        F: FnMut(V) -> U,
+    {
+        TiEnumMap::from_raw(self.raw.into_vec().into_iter().map(f).collect())
+    }
+
+    /// Converts the map into a boxed slice of values in variant order.
+    #[inline]
+    pub fn into_boxed_slice(self) -> Box<TiSlice<K, V>> {
+        self.raw.into()
+    }
+}
+
+impl<K, V> fmt::Debug for TiEnumMap<K, V>
+where
+    K: fmt::Debug + TiEnum,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter_enumerated()).finish()
+    }
+}
+
+impl<K, V> AsRef<TiSlice<K, V>> for TiEnumMap<K, V> {
+    fn as_ref(&self) -> &TiSlice<K, V> {
+        self
+    }
+}
+
+impl<K, V> AsMut<TiSlice<K, V>> for TiEnumMap<K, V> {
+    fn as_mut(&mut self) -> &mut TiSlice<K, V> {
+        self
+    }
+}
+
+impl<K, V> Borrow<TiSlice<K, V>> for TiEnumMap<K, V> {
+    fn borrow(&self) -> &TiSlice<K, V> {
+        self
+    }
+}
+
+impl<K, V> BorrowMut<TiSlice<K, V>> for TiEnumMap<K, V> {
+    fn borrow_mut(&mut self) -> &mut TiSlice<K, V> {
+        self
+    }
+}
+
+impl<K, V> ops::Deref for TiEnumMap<K, V> {
+    type Target = TiSlice<K, V>;
+
+    fn deref(&self) -> &TiSlice<K, V> {
+        Self::Target::from_ref(&self.raw)
+    }
+}
+
+impl<K, V> ops::DerefMut for TiEnumMap<K, V> {
+    fn deref_mut(&mut self) -> &mut TiSlice<K, V> {
+        Self::Target::from_mut(&mut self.raw)
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for TiEnumMap<K, V>
+where
+    K: TiEnum,
+{
+    /// Creates a map from key-value pairs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some of the keys are missing.
+    /// If a key occurs multiple times, the last value is used.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut values: Vec<Option<V>> = (0..K::COUNT).map(|_| None).collect();
+        for (key, value) in iter {
+            values[key.to_index()] = Some(value);
+        }
+        Self::from_fn(|key| match values[key.to_index()].take() {
+            Some(value) => value,
+            None => panic!("missing value for variant index {}", key.to_index()),
+        })
+    }
+}
+
+impl<K, A, B> PartialEq<TiEnumMap<K, B>> for TiEnumMap<K, A>
+where
+    A: PartialEq<B>,
+{
+    #[inline]
+    fn eq(&self, other: &TiEnumMap<K, B>) -> bool {
+        self.raw[..] == other.raw[..]
+    }
+}
+
+impl<K, V> Clone for TiEnumMap<K, V>
+where
+    V: Clone,
+{
+    #[inline]
+    fn clone(&self) -> TiEnumMap<K, V> {
+        Self::from_raw(self.raw.clone())
+    }
+}
+
+impl<K, V> Default for TiEnumMap<K, V>
+where
+    K: TiEnum,
+    V: Default,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::from_fn(|_| V::default())
+    }
+}
+
+impl<K, V> Eq for TiEnumMap<K, V> where V: Eq {}
+
+impl<K, V> Hash for TiEnumMap<K, V>
+where
+    V: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.raw.hash(state)
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a TiEnumMap<K, V>
+where
+    K: TiEnum,
+{
+    type Item = (K, &'a V);
+    type IntoIter = TiEnumerated<slice::Iter<'a, V>, K, &'a V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_enumerated()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut TiEnumMap<K, V>
+where
+    K: TiEnum,
+{
+    type Item = (K, &'a mut V);
+    type IntoIter = TiEnumerated<slice::IterMut<'a, V>, K, &'a mut V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut_enumerated()
+    }
+}
+
+impl<K, V> IntoIterator for TiEnumMap<K, V>
+where
+    K: TiEnum,
+{
+    type Item = (K, V);
+    type IntoIter = TiEnumerated<vec::IntoIter<V>, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_iter_enumerated()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K, V: Serialize> Serialize for TiEnumMap<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.raw.serialize(serializer)
+    }
+}
+
+#[cfg(any(feature = "serde-alloc", feature = "serde-std"))]
+impl<'de, K: TiEnum, V: Deserialize<'de>> Deserialize<'de> for TiEnumMap<K, V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = Box::<[V]>::deserialize(deserializer)?;
+        let len = raw.len();
+        Self::try_from_boxed_slice(raw).map_err(|_| {
+            D::Error::invalid_length(len, &"a sequence with a value for every enum variant")
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{TiEnum, TiEnumMap, TiKey};
+    use alloc::{boxed::Box, format, vec};
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    enum Color {
+        Red,
+        Green,
+        Blue,
+    }
+
+    impl TiKey for Color {
+        const MAX: usize = 2;
+
+        fn from_usize(index: usize) -> Self {
+            [Color::Red, Color::Green, Color::Blue][index]
+        }
+
+        fn to_usize(self) -> usize {
+            self as usize
+        }
+    }
+
+    impl TiEnum for Color {
+        const COUNT: usize = 3;
+    }
+
+    #[test]
+    fn construction() {
+        let map: TiEnumMap<Color, u32> =
+            TiEnumMap::from_fn(|color: Color| color.to_index() as u32 * 2);
+        assert_eq!(map[Color::Blue], 4);
+        assert_eq!(map.len(), Color::COUNT);
+        assert_eq!(map.map(|value| value + 1)[Color::Red], 1);
+
+        let map: TiEnumMap<Color, &str> =
+            vec![(Color::Blue, "b"), (Color::Red, "r"), (Color::Green, "g")]
+                .into_iter()
+                .collect();
+        assert_eq!(format!("{:?}", map), r#"{Red: "r", Green: "g", Blue: "b"}"#);
+
+        let raw: Box<[u32]> = vec![1, 2].into_boxed_slice();
+        assert!(TiEnumMap::<Color, u32>::try_from_boxed_slice(raw).is_err());
+    }
+
+    #[test]
+    fn iteration() {
+        let mut map: TiEnumMap<Color, u32> = TiEnumMap::default();
+        for (color, value) in &mut map {
+            *value = color.to_index() as u32 + 10;
+        }
+        let pairs: vec::Vec<_> = (&map).into_iter().collect();
+        assert_eq!(
+            pairs,
+            [(Color::Red, &10), (Color::Green, &11), (Color::Blue, &12)]
+        );
+        let keys: vec::Vec<_> = map.clone().into_iter().map(|(color, _)| color).collect();
+        assert_eq!(keys, [Color::Red, Color::Green, Color::Blue]);
+        assert_eq!(map.position(|&value| value == 11), Some(Color::Green));
+    }
+
+    #[test]
+    #[should_panic(expected = "missing value")]
+    fn from_iter_missing_key() {
+        let _: TiEnumMap<Color, u32> = vec![(Color::Red, 1)].into_iter().collect();
+    }
+
+    #[test]
+    #[should_panic(expected = "index 3 is out of range for the enum with 3 variants")]
+    fn from_index_out_of_range() {
+        let _ = Color::from_index(3);
+    }
+}
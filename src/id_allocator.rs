@@ -0,0 +1,338 @@
+use core::{cmp::Reverse, fmt, marker::PhantomData, ops};
+
+use alloc::{collections::BinaryHeap, vec::Vec};
+
+use crate::key::{key_from_len, offset_index};
+use crate::TiKey;
+
+/// The order in which [`TiIdAllocator`] reuses freed keys.
+///
+/// [`TiIdAllocator`]: struct.TiIdAllocator.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ReusePolicy {
+    /// Reuse the lowest freed key first, keeping allocated keys dense.
+    LowestFirst,
+    /// Reuse the most recently freed key first.
+    Lifo,
+}
+
+impl Default for ReusePolicy {
+    #[inline]
+    fn default() -> Self {
+        ReusePolicy::LowestFirst
+    }
+}
+
+/// An allocator of unique keys of the type `K` that recycles freed keys.
+///
+/// The allocator does not store any values,
+/// so it can be used to hand out ids for data kept in separate [`TiVec`]s.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::{TiIdAllocator, TiVec};
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct BodyId(usize);
+///
+/// let mut ids: TiIdAllocator<BodyId> = TiIdAllocator::new();
+/// let a = ids.alloc();
+/// let b = ids.alloc();
+/// ids.free(a);
+/// assert_eq!(ids.alloc(), a);
+/// assert_eq!(ids.reserve_range(2), BodyId(2)..BodyId(4));
+///
+/// let mut masses: TiVec<BodyId, f32> = TiVec::new();
+/// masses.resize(ids.high_water_mark().into(), 0.0);
+/// masses[b] = 1.5;
+/// ```
+///
+/// [`TiVec`]: struct.TiVec.html
+pub struct TiIdAllocator<K> {
+    next: usize,
+    free: FreeList,
+    is_free: Vec<bool>,
+
+    /// Ties the container to the key type `K`.
+    _marker: PhantomData<fn(K) -> K>,
+}
+
+#[derive(Clone, Debug)]
+enum FreeList {
+    LowestFirst(BinaryHeap<Reverse<usize>>),
+    Lifo(Vec<usize>),
+}
+
+impl FreeList {
+    fn new(policy: ReusePolicy) -> Self {
+        match policy {
+            ReusePolicy::LowestFirst => FreeList::LowestFirst(BinaryHeap::new()),
+            ReusePolicy::Lifo => FreeList::Lifo(Vec::new()),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            FreeList::LowestFirst(heap) => heap.len(),
+            FreeList::Lifo(stack) => stack.len(),
+        }
+    }
+
+    fn push(&mut self, index: usize) {
+        match self {
+            FreeList::LowestFirst(heap) => heap.push(Reverse(index)),
+            FreeList::Lifo(stack) => stack.push(index),
+        }
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        match self {
+            FreeList::LowestFirst(heap) => heap.pop().map(|Reverse(index)| index),
+            FreeList::Lifo(stack) => stack.pop(),
+        }
+    }
+
+    fn clear(&mut self) {
+        match self {
+            FreeList::LowestFirst(heap) => heap.clear(),
+            FreeList::Lifo(stack) => stack.clear(),
+        }
+    }
+}
+
+impl<K> TiIdAllocator<K> {
+    /// Constructs a new allocator that reuses the lowest freed key first.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_policy(ReusePolicy::LowestFirst)
+    }
+
+    /// Constructs a new allocator with the specified reuse policy.
+    #[inline]
+    pub fn with_policy(policy: ReusePolicy) -> Self {
+        Self {
+            next: 0,
+            free: FreeList::new(policy),
+            is_free: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the reuse policy of the allocator.
+    #[inline]
+    pub fn policy(&self) -> ReusePolicy {
+        match self.free {
+            FreeList::LowestFirst(_) => ReusePolicy::LowestFirst,
+            FreeList::Lifo(_) => ReusePolicy::Lifo,
+        }
+    }
+
+    /// Returns the number of currently allocated keys.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.next - self.free.len()
+    }
+
+    /// Returns `true` if no keys are currently allocated.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of freed keys waiting to be reused.
+    #[inline]
+    pub fn free_len(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Returns the key following the highest key ever allocated.
+    ///
+    /// Every allocated key is less than the returned key,
+    /// so it can be used as the length of a [`TiVec`] storing data for allocated keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the high-water mark can not be represented by the key type.
+    ///
+    /// [`TiVec`]: struct.TiVec.html
+    #[inline]
+    pub fn high_water_mark(&self) -> K
+    where
+        K: TiKey,
+    {
+        key_from_len(self.next)
+    }
+
+    /// Returns `true` if the key is currently allocated.
+    #[inline]
+    pub fn is_allocated(&self, key: K) -> bool
+    where
+        K: TiKey,
+    {
+        let index = key.to_usize();
+        index < self.next && !self.is_free[index]
+    }
+
+    /// Allocates a key, reusing a freed key if there is one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key can not be represented by the key type.
+    pub fn alloc(&mut self) -> K
+    where
+        K: TiKey,
+    {
+        match self.free.pop() {
+            Some(index) => {
+                self.is_free[index] = false;
+                K::from_usize(index)
+            }
+            None => {
+                let key = key_from_len(self.next);
+                self.next += 1;
+                self.is_free.push(false);
+                key
+            }
+        }
+    }
+
+    /// Allocates `n` contiguous fresh keys above the high-water mark.
+    ///
+    /// Freed keys are not used for ranges.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the end of the range can not be represented by the key type.
+    pub fn reserve_range(&mut self, n: usize) -> ops::Range<K>
+    where
+        K: TiKey,
+    {
+        let start = self.next;
+        let end = offset_index::<K>(start, n);
+        let range = key_from_len(start)..key_from_len(end);
+        self.next = end;
+        self.is_free.resize(end, false);
+        range
+    }
+
+    /// Returns the key to the allocator for later reuse.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not currently allocated.
+    pub fn free(&mut self, key: K)
+    where
+        K: TiKey,
+    {
+        let index = key.to_usize();
+        if index >= self.next || self.is_free[index] {
+            not_allocated(index);
+        }
+        self.is_free[index] = true;
+        self.free.push(index);
+    }
+
+    /// Frees all keys and resets the high-water mark to zero.
+    pub fn clear(&mut self) {
+        self.next = 0;
+        self.free.clear();
+        self.is_free.clear();
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn not_allocated(index: usize) -> ! {
+    panic!("key index {} is not allocated in the id allocator", index)
+}
+
+impl<K> fmt::Debug for TiIdAllocator<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TiIdAllocator")
+            .field("policy", &self.policy())
+            .field("len", &self.len())
+            .field("high_water_mark", &self.next)
+            .finish()
+    }
+}
+
+impl<K> Clone for TiIdAllocator<K> {
+    fn clone(&self) -> Self {
+        Self {
+            next: self.next,
+            free: self.free.clone(),
+            is_free: self.is_free.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K> Default for TiIdAllocator<K> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::Id;
+    use crate::{ReusePolicy, TiIdAllocator};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn reuse_policies() {
+        let cases = [
+            (ReusePolicy::LowestFirst, [1, 3, 4]),
+            (ReusePolicy::Lifo, [3, 1, 4]),
+        ];
+        for &(policy, expected) in &cases {
+            let mut ids: TiIdAllocator<Id> = TiIdAllocator::with_policy(policy);
+            assert_eq!(ids.policy(), policy);
+            let keys: Vec<Id> = (0..4).map(|_| ids.alloc()).collect();
+            ids.free(keys[1]);
+            ids.free(keys[3]);
+            assert_eq!(ids.len(), 2);
+            assert_eq!(ids.free_len(), 2);
+            assert!(!ids.is_allocated(keys[1]));
+            let reused: Vec<usize> = (0..3).map(|_| ids.alloc().into()).collect();
+            assert_eq!(reused, expected);
+        }
+    }
+
+    #[test]
+    fn ranges_and_high_water_mark() {
+        let mut ids: TiIdAllocator<Id> = TiIdAllocator::default();
+        assert_eq!(ids.high_water_mark(), Id::from(0));
+        let first = ids.alloc();
+        ids.free(first);
+        assert_eq!(ids.reserve_range(3), Id::from(1)..Id::from(4));
+        assert_eq!(ids.high_water_mark(), Id::from(4));
+        assert!(ids.is_allocated(Id::from(2)));
+        assert_eq!(ids.alloc(), first);
+        assert_eq!(ids.alloc(), Id::from(4));
+        assert_eq!(ids.len(), 5);
+        ids.clear();
+        assert!(ids.is_empty());
+        assert_eq!(ids.alloc(), Id::from(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "key index 0 is not allocated in the id allocator")]
+    fn double_free() {
+        let mut ids: TiIdAllocator<Id> = TiIdAllocator::new();
+        let key = ids.alloc();
+        ids.free(key);
+        ids.free(key);
+    }
+
+    #[test]
+    #[should_panic(expected = "is out of range for the key type")]
+    fn reserve_range_overflow() {
+        let mut ids: TiIdAllocator<Id> = TiIdAllocator::new();
+        let _ = ids.reserve_range(3);
+        let _ = ids.reserve_range(usize::MAX);
+    }
+}
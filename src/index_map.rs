@@ -0,0 +1,633 @@
+use core::{
+    borrow::Borrow,
+    fmt,
+    hash::{BuildHasher, Hash},
+    iter::{self, FromIterator, FusedIterator},
+    marker::PhantomData,
+    mem, slice,
+};
+
+use alloc::collections::hash_map::RandomState;
+
+use crate::index_table::{make_hash, IndexTable};
+use crate::key::key_from_len;
+use crate::{TiKey, TiSlice, TiVec};
+
+/// A hash map that preserves insertion order
+/// and identifies entries by positions of the type `P`.
+///
+/// Keys and values are stored in separate [`TiVec`]s indexed by positions,
+/// so they are available as [`TiSlice`]s.
+/// Removal swaps the last entry into the removed position
+/// and reports the former position of the moved entry.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::TiIndexMap;
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct FieldId(usize);
+///
+/// let mut fields: TiIndexMap<FieldId, &str, u32> = TiIndexMap::new();
+/// assert_eq!(fields.insert_full("x", 4), (FieldId(0), None));
+/// assert_eq!(fields.insert_full("y", 8), (FieldId(1), None));
+/// assert_eq!(fields.insert_full("z", 8), (FieldId(2), None));
+/// assert_eq!(fields.get_full("y"), Some((FieldId(1), &"y", &8)));
+/// assert_eq!(fields.get_index(FieldId(0)), Some((&"x", &4)));
+/// assert_eq!(fields.keys().last_key_value(), Some((FieldId(2), &"z")));
+///
+/// // "z" moves from position 2 into the removed position 0.
+/// assert_eq!(fields.swap_remove("x"), Some((FieldId(0), 4, Some(FieldId(2)))));
+/// assert_eq!(fields.get_index_of("z"), Some(FieldId(0)));
+/// ```
+///
+/// [`TiVec`]: struct.TiVec.html
+/// [`TiSlice`]: struct.TiSlice.html
+pub struct TiIndexMap<P, K, V, S = RandomState> {
+    keys: TiVec<P, K>,
+    values: TiVec<P, V>,
+    table: IndexTable,
+    hash_builder: S,
+}
+
+impl<P, K, V> TiIndexMap<P, K, V> {
+    /// Constructs a new, empty map.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+
+    /// Constructs a new, empty map that can hold `capacity` entries
+    /// without reallocating.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<P, K, V, S> TiIndexMap<P, K, V, S> {
+    /// Constructs a new, empty map that uses the hash builder to hash keys.
+    #[inline]
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            keys: TiVec::new(),
+            values: TiVec::new(),
+            table: IndexTable::default(),
+            hash_builder,
+        }
+    }
+
+    /// Constructs a new, empty map that can hold `capacity` entries
+    /// without reallocating and uses the hash builder to hash keys.
+    #[inline]
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        Self {
+            keys: TiVec::with_capacity(capacity),
+            values: TiVec::with_capacity(capacity),
+            table: IndexTable::with_capacity(capacity),
+            hash_builder,
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if the map contains no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Removes all entries from the map.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        self.values.clear();
+        self.table.clear();
+    }
+
+    /// Returns a slice of keys indexed by their positions.
+    #[inline]
+    pub fn keys(&self) -> &TiSlice<P, K> {
+        &self.keys
+    }
+
+    /// Returns a slice of values indexed by their positions.
+    #[inline]
+    pub fn values(&self) -> &TiSlice<P, V> {
+        &self.values
+    }
+
+    /// Returns a mutable slice of values indexed by their positions.
+    #[inline]
+    pub fn values_mut(&mut self) -> &mut TiSlice<P, V> {
+        &mut self.values
+    }
+
+    /// Returns the position of the key, or `None` if the key is not in the map.
+    #[inline]
+    pub fn get_index_of<Q>(&self, key: &Q) -> Option<P>
+    where
+        P: TiKey,
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        S: BuildHasher,
+    {
+        self.find(key).map(P::from_usize)
+    }
+
+    /// Returns `true` if the map contains the key.
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        S: BuildHasher,
+    {
+        self.find(key).is_some()
+    }
+
+    /// Returns a reference to the value for the key,
+    /// or `None` if the key is not in the map.
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        S: BuildHasher,
+    {
+        self.find(key).map(|index| &self.values.raw[index])
+    }
+
+    /// Returns a mutable reference to the value for the key,
+    /// or `None` if the key is not in the map.
+    #[inline]
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        S: BuildHasher,
+    {
+        match self.find(key) {
+            Some(index) => Some(&mut self.values.raw[index]),
+            None => None,
+        }
+    }
+
+    /// Returns the position, the stored key and the value for the key,
+    /// or `None` if the key is not in the map.
+    #[inline]
+    pub fn get_full<Q>(&self, key: &Q) -> Option<(P, &K, &V)>
+    where
+        P: TiKey,
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        S: BuildHasher,
+    {
+        self.find(key).map(|index| {
+            (
+                P::from_usize(index),
+                &self.keys.raw[index],
+                &self.values.raw[index],
+            )
+        })
+    }
+
+    /// Returns the key and the value at the position,
+    /// or `None` if the position is out of bounds.
+    #[inline]
+    pub fn get_index(&self, position: P) -> Option<(&K, &V)>
+    where
+        P: TiKey,
+    {
+        let index = position.to_usize();
+        Some((self.keys.raw.get(index)?, &self.values.raw[index]))
+    }
+
+    /// Returns the key and a mutable reference to the value at the position,
+    /// or `None` if the position is out of bounds.
+    #[inline]
+    pub fn get_index_mut(&mut self, position: P) -> Option<(&K, &mut V)>
+    where
+        P: TiKey,
+    {
+        let index = position.to_usize();
+        Some((self.keys.raw.get(index)?, &mut self.values.raw[index]))
+    }
+
+    /// Inserts the entry and returns the old value for the key, if any.
+    ///
+    /// A new key is appended to the end of the map,
+    /// while an existing key keeps its position.
+    #[inline]
+    pub fn insert(&mut self, key: K, value: V) -> Option<V>
+    where
+        P: TiKey,
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        self.insert_full(key, value).1
+    }
+
+    /// Inserts the entry and returns its position and the old value for the key, if any.
+    ///
+    /// A new key is appended to the end of the map,
+    /// while an existing key keeps its position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position of a new key can not be represented by the position type.
+    pub fn insert_full(&mut self, key: K, value: V) -> (P, Option<V>)
+    where
+        P: TiKey,
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        let hash = make_hash(&self.hash_builder, &key);
+        let keys = &self.keys.raw;
+        match self.table.find(hash, |index| keys[index] == key) {
+            Some(index) => (
+                P::from_usize(index),
+                Some(mem::replace(&mut self.values.raw[index], value)),
+            ),
+            None => {
+                let index = self.keys.len();
+                let position = key_from_len(index);
+                self.keys.raw.push(key);
+                self.values.raw.push(value);
+                let (keys, hash_builder) = (&self.keys.raw, &self.hash_builder);
+                self.table
+                    .insert(hash, index, |index| make_hash(hash_builder, &keys[index]));
+                (position, None)
+            }
+        }
+    }
+
+    /// Removes the entry for the key by swapping the last entry into its position.
+    ///
+    /// Returns the removed position and value,
+    /// and the former position of the entry moved into the removed position, if any.
+    #[inline]
+    pub fn swap_remove<Q>(&mut self, key: &Q) -> Option<(P, V, Option<P>)>
+    where
+        P: TiKey,
+        K: Borrow<Q> + Hash + Eq,
+        Q: Hash + Eq + ?Sized,
+        S: BuildHasher,
+    {
+        let index = self.find(key)?;
+        let (_, value, moved) = self.swap_remove_at(index);
+        Some((P::from_usize(index), value, moved))
+    }
+
+    /// Removes the entry at the position by swapping the last entry into its place.
+    ///
+    /// Returns the removed key and value,
+    /// and the former position of the entry moved into the removed position, if any.
+    #[inline]
+    pub fn swap_remove_index(&mut self, position: P) -> Option<(K, V, Option<P>)>
+    where
+        P: TiKey,
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        let index = position.to_usize();
+        if index < self.keys.len() {
+            Some(self.swap_remove_at(index))
+        } else {
+            None
+        }
+    }
+
+    /// Removes the last entry and returns its key and value,
+    /// or `None` if the map is empty.
+    #[inline]
+    pub fn pop(&mut self) -> Option<(K, V)>
+    where
+        K: Hash + Eq,
+        S: BuildHasher,
+    {
+        let index = self.keys.len().checked_sub(1)?;
+        self.remove_from_table(index);
+        Some((self.keys.raw.pop()?, self.values.raw.pop()?))
+    }
+
+    /// Returns an iterator over positions, keys and values in insertion order.
+    #[inline]
+    pub fn iter_enumerated(&self) -> TiIndexMapIter<'_, P, K, V> {
+        TiIndexMapIter {
+            entries: self.keys.raw.iter().zip(self.values.raw.iter()).enumerate(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over positions, keys and mutable values in insertion order.
+    #[inline]
+    pub fn iter_mut_enumerated(&mut self) -> TiIndexMapIterMut<'_, P, K, V> {
+        TiIndexMapIterMut {
+            entries: self
+                .keys
+                .raw
+                .iter()
+                .zip(self.values.raw.iter_mut())
+                .enumerate(),
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn find<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        S: BuildHasher,
+    {
+        let hash = make_hash(&self.hash_builder, key);
+        self.table
+            .find(hash, |index| self.keys.raw[index].borrow() == key)
+    }
+
+    fn remove_from_table(&mut self, index: usize)
+    where
+        K: Hash,
+        S: BuildHasher,
+    {
+        let (keys, hash_builder) = (&self.keys.raw, &self.hash_builder);
+        self.table
+            .remove(make_hash(hash_builder, &keys[index]), index, |index| {
+                make_hash(hash_builder, &keys[index])
+            });
+    }
+
+    fn swap_remove_at(&mut self, index: usize) -> (K, V, Option<P>)
+    where
+        P: TiKey,
+        K: Hash,
+        S: BuildHasher,
+    {
+        self.remove_from_table(index);
+        let last = self.keys.len() - 1;
+        let moved = if index == last {
+            None
+        } else {
+            let hash = make_hash(&self.hash_builder, &self.keys.raw[last]);
+            self.table.replace(hash, last, index);
+            Some(P::from_usize(last))
+        };
+        (
+            self.keys.raw.swap_remove(index),
+            self.values.raw.swap_remove(index),
+            moved,
+        )
+    }
+}
+
+impl<P, K, V, S> fmt::Debug for TiIndexMap<P, K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.keys.raw.iter().zip(self.values.raw.iter()))
+            .finish()
+    }
+}
+
+impl<P, K, V, S> Clone for TiIndexMap<P, K, V, S>
+where
+    K: Clone,
+    V: Clone,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            keys: self.keys.clone(),
+            values: self.values.clone(),
+            table: self.table.clone(),
+            hash_builder: self.hash_builder.clone(),
+        }
+    }
+}
+
+impl<P, K, V, S> Default for TiIndexMap<P, K, V, S>
+where
+    S: Default,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<P, K, V, S> Extend<(K, V)> for TiIndexMap<P, K, V, S>
+where
+    P: TiKey,
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            let _ = self.insert(key, value);
+        }
+    }
+}
+
+impl<P, K, V, S> FromIterator<(K, V)> for TiIndexMap<P, K, V, S>
+where
+    P: TiKey,
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, P, K, V, S> IntoIterator for &'a TiIndexMap<P, K, V, S>
+where
+    P: TiKey,
+{
+    type Item = (P, &'a K, &'a V);
+    type IntoIter = TiIndexMapIter<'a, P, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> TiIndexMapIter<'a, P, K, V> {
+        self.iter_enumerated()
+    }
+}
+
+impl<'a, P, K, V, S> IntoIterator for &'a mut TiIndexMap<P, K, V, S>
+where
+    P: TiKey,
+{
+    type Item = (P, &'a K, &'a mut V);
+    type IntoIter = TiIndexMapIterMut<'a, P, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> TiIndexMapIterMut<'a, P, K, V> {
+        self.iter_mut_enumerated()
+    }
+}
+
+macro_rules! index_map_iter {
+    (
+        $(#[$attr:meta])*
+        $name:ident,
+        $values:ty,
+        $item:ty
+    ) => {
+        $(#[$attr])*
+        pub struct $name<'a, P, K, V> {
+            entries: iter::Enumerate<iter::Zip<slice::Iter<'a, K>, $values>>,
+            _marker: PhantomData<fn(P) -> P>,
+        }
+
+        impl<P, K, V> fmt::Debug for $name<'_, P, K, V> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($name))
+                    .field("len", &self.entries.len())
+                    .finish()
+            }
+        }
+
+        impl<'a, P: TiKey, K, V> Iterator for $name<'a, P, K, V> {
+            type Item = $item;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                let (index, (key, value)) = self.entries.next()?;
+                Some((P::from_usize(index), key, value))
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.entries.size_hint()
+            }
+        }
+
+        impl<P: TiKey, K, V> DoubleEndedIterator for $name<'_, P, K, V> {
+            #[inline]
+            fn next_back(&mut self) -> Option<Self::Item> {
+                let (index, (key, value)) = self.entries.next_back()?;
+                Some((P::from_usize(index), key, value))
+            }
+        }
+
+        impl<P: TiKey, K, V> ExactSizeIterator for $name<'_, P, K, V> {}
+
+        impl<P: TiKey, K, V> FusedIterator for $name<'_, P, K, V> {}
+    };
+}
+
+index_map_iter!(
+    /// An iterator over positions, keys and values of a [`TiIndexMap`].
+    ///
+    /// This struct is created by the [`TiIndexMap::iter_enumerated`] method.
+    ///
+    /// [`TiIndexMap`]: struct.TiIndexMap.html
+    /// [`TiIndexMap::iter_enumerated`]: struct.TiIndexMap.html#method.iter_enumerated
+    TiIndexMapIter,
+    slice::Iter<'a, V>,
+    (P, &'a K, &'a V)
+);
+
+index_map_iter!(
+    /// A mutable iterator over positions, keys and values of a [`TiIndexMap`].
+    ///
+    /// This struct is created by the [`TiIndexMap::iter_mut_enumerated`] method.
+    ///
+    /// [`TiIndexMap`]: struct.TiIndexMap.html
+    /// [`TiIndexMap::iter_mut_enumerated`]: struct.TiIndexMap.html#method.iter_mut_enumerated
+    TiIndexMapIterMut,
+    slice::IterMut<'a, V>,
+    (P, &'a K, &'a mut V)
+);
+
+#[cfg(test)]
+mod test {
+    use crate::test::Id;
+    use crate::TiIndexMap;
+    use alloc::{format, vec::Vec};
+    use core::iter::FromIterator;
+
+    #[test]
+    fn insert_and_lookup() {
+        let mut map: TiIndexMap<Id, u32, char> = TiIndexMap::with_capacity(2);
+        for (value, key) in "abcdefghij".chars().enumerate() {
+            assert_eq!(
+                map.insert_full(value as u32 * 10, key),
+                (Id::from(value), None)
+            );
+        }
+        assert_eq!(map.insert_full(30, 'D'), (Id::from(3), Some('d')));
+        assert_eq!(map.insert(100, 'k'), None);
+        assert_eq!(map.len(), 11);
+        assert_eq!(map.get(&30), Some(&'D'));
+        assert_eq!(map.get(&31), None);
+        assert_eq!(map.get_full(&90), Some((Id::from(9), &90, &'j')));
+        assert_eq!(map.get_index(Id::from(10)), Some((&100, &'k')));
+        assert_eq!(map.get_index(Id::from(11)), None);
+        *map.get_index_mut(Id::from(0)).unwrap().1 = 'A';
+        *map.get_mut(&10).unwrap() = 'B';
+        map.values_mut()[Id::from(2)] = 'C';
+        assert_eq!(
+            map.values().iter().take(4).collect::<Vec<_>>(),
+            [&'A', &'B', &'C', &'D']
+        );
+        assert_eq!(map.keys().position(|&key| key == 50), Some(Id::from(5)));
+    }
+
+    #[test]
+    fn swap_remove_reports_moves() {
+        let mut map = TiIndexMap::<Id, u32, u32>::from_iter((0..40).map(|key| (key, key * 2)));
+        assert_eq!(
+            map.swap_remove(&5),
+            Some((Id::from(5), 10, Some(Id::from(39))))
+        );
+        assert_eq!(map.swap_remove(&5), None);
+        assert_eq!(map.get_index_of(&39), Some(Id::from(5)));
+        assert_eq!(map.swap_remove_index(Id::from(38)), Some((38, 76, None)));
+        assert_eq!(map.pop(), Some((37, 74)));
+        for key in 0..40 {
+            let expected = key < 37 && key != 5 || key == 39;
+            assert_eq!(map.contains_key(&key), expected, "key {}", key);
+            if let Some(position) = map.get_index_of(&key) {
+                assert_eq!(map.keys()[position], key);
+            }
+        }
+        for (position, key, value) in &mut map {
+            *value = usize::from(position) as u32 + key;
+        }
+        assert_eq!(
+            map.iter_enumerated().next_back(),
+            Some((Id::from(36), &36, &72))
+        );
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.get(&0), None);
+        assert_eq!(format!("{:?}", map), "{}");
+    }
+
+    #[test]
+    fn remove_many() {
+        let mut map: TiIndexMap<Id, u32, ()> = (0..500).map(|key| (key, ())).collect();
+        for key in (1..500).step_by(2) {
+            assert!(map.swap_remove(&key).is_some());
+        }
+        for key in 0..500 {
+            match map.get_index_of(&key) {
+                Some(position) => assert_eq!(map.keys()[position], key),
+                None => assert_eq!(key % 2, 1),
+            }
+        }
+        assert_eq!(map.len(), 250);
+    }
+}
@@ -0,0 +1,115 @@
+use core::{
+    hash::{BuildHasher, Hash, Hasher},
+    mem,
+};
+
+use alloc::vec::Vec;
+
+/// An open addressing table of indices into a separately stored sequence of entries.
+///
+/// Every slot stores either zero for an empty slot or an entry index plus one.
+/// The table does not store hashes, so methods that move slots
+/// take a `rehash` function returning the hash of the entry with the given index.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct IndexTable {
+    slots: Vec<usize>,
+}
+
+impl IndexTable {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        let mut table = Self::default();
+        table.reserve(capacity, |_| unreachable!());
+        table
+    }
+
+    /// Returns the index of the entry with the hash for which `eq` returns `true`.
+    pub(crate) fn find(&self, hash: u64, mut eq: impl FnMut(usize) -> bool) -> Option<usize> {
+        if self.slots.is_empty() {
+            return None;
+        }
+        let mask = self.slots.len() - 1;
+        let mut pos = hash as usize & mask;
+        loop {
+            match self.slots[pos] {
+                0 => return None,
+                slot if eq(slot - 1) => return Some(slot - 1),
+                _ => pos = (pos + 1) & mask,
+            }
+        }
+    }
+
+    /// Inserts the index of the last entry, that is not yet in the table.
+    pub(crate) fn insert(&mut self, hash: u64, index: usize, rehash: impl Fn(usize) -> u64) {
+        self.reserve(index + 1, rehash);
+        self.insert_slot(hash, index);
+    }
+
+    /// Removes the index of the entry with the hash from the table.
+    pub(crate) fn remove(&mut self, hash: u64, index: usize, rehash: impl Fn(usize) -> u64) {
+        let mask = self.slots.len() - 1;
+        let mut hole = self.slot_of(hash, index);
+        self.slots[hole] = 0;
+        let mut pos = (hole + 1) & mask;
+        while self.slots[pos] != 0 {
+            let ideal = rehash(self.slots[pos] - 1) as usize & mask;
+            if pos.wrapping_sub(ideal) & mask >= pos.wrapping_sub(hole) & mask {
+                self.slots[hole] = mem::replace(&mut self.slots[pos], 0);
+                hole = pos;
+            }
+            pos = (pos + 1) & mask;
+        }
+    }
+
+    /// Replaces the index of the entry with the hash by a new index.
+    pub(crate) fn replace(&mut self, hash: u64, index: usize, new_index: usize) {
+        let pos = self.slot_of(hash, index);
+        self.slots[pos] = new_index + 1;
+    }
+
+    /// Removes all indices from the table.
+    pub(crate) fn clear(&mut self) {
+        for slot in &mut self.slots {
+            *slot = 0;
+        }
+    }
+
+    fn slot_of(&self, hash: u64, index: usize) -> usize {
+        let mask = self.slots.len() - 1;
+        let mut pos = hash as usize & mask;
+        while self.slots[pos] != index + 1 {
+            pos = (pos + 1) & mask;
+        }
+        pos
+    }
+
+    fn insert_slot(&mut self, hash: u64, index: usize) {
+        let mask = self.slots.len() - 1;
+        let mut pos = hash as usize & mask;
+        while self.slots[pos] != 0 {
+            pos = (pos + 1) & mask;
+        }
+        self.slots[pos] = index + 1;
+    }
+
+    /// Grows the table so that it can hold `len` indices
+    /// with the load factor of at most 3/4.
+    fn reserve(&mut self, len: usize, rehash: impl Fn(usize) -> u64) {
+        if len * 4 <= self.slots.len() * 3 {
+            return;
+        }
+        let capacity = (len * 4 / 3 + 1).next_power_of_two().max(8);
+        let old = mem::replace(&mut self.slots, alloc::vec![0; capacity]);
+        for slot in old {
+            if slot != 0 {
+                self.insert_slot(rehash(slot - 1), slot - 1);
+            }
+        }
+    }
+}
+
+#[inline]
+pub(crate) fn make_hash<S: BuildHasher, Q: Hash + ?Sized>(hash_builder: &S, value: &Q) -> u64 {
+    let mut state = hash_builder.build_hasher();
+    value.hash(&mut state);
+    state.finish()
+}
@@ -0,0 +1,461 @@
+use core::{fmt, iter::FromIterator, mem};
+
+use alloc::vec::Vec;
+
+use crate::key::offset_index;
+use crate::{TiKey, TiVec};
+
+const NOT_IN_HEAP: usize = usize::MAX;
+
+/// The order in which [`TiIndexedHeap`] pops keys.
+///
+/// [`TiIndexedHeap`]: struct.TiIndexedHeap.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum HeapOrder {
+    /// Pop the key with the lowest priority first.
+    Min,
+    /// Pop the key with the highest priority first.
+    Max,
+}
+
+impl Default for HeapOrder {
+    #[inline]
+    fn default() -> Self {
+        HeapOrder::Min
+    }
+}
+
+/// A binary heap of keys of the type `K` with priorities of the type `P`
+/// that can update or remove any key in logarithmic time.
+///
+/// The heap keeps a back-map from every key to its position in the heap,
+/// so every key is contained at most once.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::TiIndexedHeap;
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct NodeId(usize);
+///
+/// let mut distances: TiIndexedHeap<NodeId, u32> = TiIndexedHeap::new();
+/// assert_eq!(distances.push(NodeId(0), 10), None);
+/// assert_eq!(distances.push(NodeId(1), 5), None);
+/// assert_eq!(distances.push(NodeId(2), 7), None);
+/// assert_eq!(distances.change_priority(NodeId(0), 3), Some(10));
+/// assert_eq!(distances.remove(NodeId(2)), Some(7));
+/// assert_eq!(distances.pop(), Some((NodeId(0), 3)));
+/// assert_eq!(distances.pop(), Some((NodeId(1), 5)));
+/// assert_eq!(distances.pop(), None);
+/// ```
+pub struct TiIndexedHeap<K, P> {
+    heap: Vec<(K, P)>,
+    positions: TiVec<K, usize>,
+    order: HeapOrder,
+}
+
+impl<K, P> TiIndexedHeap<K, P> {
+    /// Constructs a new, empty heap that pops the lowest priority first.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_order(HeapOrder::Min)
+    }
+
+    /// Constructs a new, empty heap with the specified order.
+    #[inline]
+    pub fn with_order(order: HeapOrder) -> Self {
+        Self {
+            heap: Vec::new(),
+            positions: TiVec::new(),
+            order,
+        }
+    }
+
+    /// Returns the order of the heap.
+    #[inline]
+    pub fn order(&self) -> HeapOrder {
+        self.order
+    }
+
+    /// Returns the number of keys in the heap.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Returns `true` if the heap contains no keys.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Removes all keys from the heap.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.heap.clear();
+        for position in &mut self.positions {
+            *position = NOT_IN_HEAP;
+        }
+    }
+
+    /// Returns keys and priorities of the heap in an arbitrary order.
+    #[inline]
+    pub fn as_slice(&self) -> &[(K, P)] {
+        &self.heap
+    }
+
+    /// Returns `true` if the heap contains the key.
+    #[inline]
+    pub fn contains(&self, key: K) -> bool
+    where
+        K: TiKey,
+    {
+        self.position(key).is_some()
+    }
+
+    /// Returns a reference to the priority of the key,
+    /// or `None` if the key is not in the heap.
+    #[inline]
+    pub fn priority(&self, key: K) -> Option<&P>
+    where
+        K: TiKey,
+    {
+        Some(&self.heap[self.position(key)?].1)
+    }
+
+    /// Returns the key that would be popped next and its priority,
+    /// or `None` if the heap is empty.
+    #[inline]
+    pub fn peek(&self) -> Option<(K, &P)>
+    where
+        K: Copy,
+    {
+        self.heap.first().map(|(key, priority)| (*key, priority))
+    }
+
+    /// Pushes the key with the priority.
+    ///
+    /// If the heap already contains the key, its priority is changed
+    /// and the old priority is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is the maximum `usize` index.
+    pub fn push(&mut self, key: K, priority: P) -> Option<P>
+    where
+        K: TiKey + Copy,
+        P: Ord,
+    {
+        if let Some(position) = self.position(key) {
+            return Some(self.replace_at(position, priority));
+        }
+        let index = key.to_usize();
+        if index >= self.positions.len() {
+            self.positions
+                .raw
+                .resize(offset_index::<K>(index, 1), NOT_IN_HEAP);
+        }
+        let position = self.heap.len();
+        self.heap.push((key, priority));
+        self.positions.raw[index] = position;
+        self.sift_up(position);
+        None
+    }
+
+    /// Changes the priority of the key and returns the old priority,
+    /// or returns `None` without pushing the key if it is not in the heap.
+    #[inline]
+    pub fn change_priority(&mut self, key: K, priority: P) -> Option<P>
+    where
+        K: TiKey + Copy,
+        P: Ord,
+    {
+        let position = self.position(key)?;
+        Some(self.replace_at(position, priority))
+    }
+
+    /// Removes the key from the heap and returns its priority,
+    /// or `None` if the key is not in the heap.
+    #[inline]
+    pub fn remove(&mut self, key: K) -> Option<P>
+    where
+        K: TiKey + Copy,
+        P: Ord,
+    {
+        let position = self.position(key)?;
+        Some(self.remove_at(position).1)
+    }
+
+    /// Removes the next key from the heap and returns it with its priority,
+    /// or `None` if the heap is empty.
+    #[inline]
+    pub fn pop(&mut self) -> Option<(K, P)>
+    where
+        K: TiKey + Copy,
+        P: Ord,
+    {
+        if self.heap.is_empty() {
+            None
+        } else {
+            Some(self.remove_at(0))
+        }
+    }
+
+    #[inline]
+    fn position(&self, key: K) -> Option<usize>
+    where
+        K: TiKey,
+    {
+        match self.positions.raw.get(key.to_usize()) {
+            Some(&position) if position != NOT_IN_HEAP => Some(position),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the entry at the position `a` must be above the entry at `b`.
+    #[inline]
+    fn is_before(&self, a: usize, b: usize) -> bool
+    where
+        P: Ord,
+    {
+        match self.order {
+            HeapOrder::Min => self.heap[a].1 < self.heap[b].1,
+            HeapOrder::Max => self.heap[a].1 > self.heap[b].1,
+        }
+    }
+
+    fn replace_at(&mut self, position: usize, priority: P) -> P
+    where
+        K: TiKey + Copy,
+        P: Ord,
+    {
+        let old = mem::replace(&mut self.heap[position].1, priority);
+        self.sift_up(position);
+        self.sift_down(position);
+        old
+    }
+
+    fn remove_at(&mut self, position: usize) -> (K, P)
+    where
+        K: TiKey + Copy,
+        P: Ord,
+    {
+        let entry = self.heap.swap_remove(position);
+        self.positions[entry.0] = NOT_IN_HEAP;
+        if position < self.heap.len() {
+            self.positions[self.heap[position].0] = position;
+            self.sift_up(position);
+            self.sift_down(position);
+        }
+        entry
+    }
+
+    fn swap(&mut self, a: usize, b: usize)
+    where
+        K: TiKey + Copy,
+    {
+        self.heap.swap(a, b);
+        self.positions[self.heap[a].0] = a;
+        self.positions[self.heap[b].0] = b;
+    }
+
+    fn sift_up(&mut self, mut position: usize)
+    where
+        K: TiKey + Copy,
+        P: Ord,
+    {
+        while position > 0 {
+            let parent = (position - 1) / 2;
+            if !self.is_before(position, parent) {
+                break;
+            }
+            self.swap(position, parent);
+            position = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut position: usize)
+    where
+        K: TiKey + Copy,
+        P: Ord,
+    {
+        loop {
+            let left = 2 * position + 1;
+            let right = left + 1;
+            let mut best = position;
+            if left < self.heap.len() && self.is_before(left, best) {
+                best = left;
+            }
+            if right < self.heap.len() && self.is_before(right, best) {
+                best = right;
+            }
+            if best == position {
+                break;
+            }
+            self.swap(position, best);
+            position = best;
+        }
+    }
+}
+
+impl<K, P> fmt::Debug for TiIndexedHeap<K, P>
+where
+    K: fmt::Debug,
+    P: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.heap.iter().map(|(key, priority)| (key, priority)))
+            .finish()
+    }
+}
+
+impl<K, P> Clone for TiIndexedHeap<K, P>
+where
+    K: Clone,
+    P: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            heap: self.heap.clone(),
+            positions: self.positions.clone(),
+            order: self.order,
+        }
+    }
+}
+
+impl<K, P> Default for TiIndexedHeap<K, P> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, P> Extend<(K, P)> for TiIndexedHeap<K, P>
+where
+    K: TiKey + Copy,
+    P: Ord,
+{
+    fn extend<I: IntoIterator<Item = (K, P)>>(&mut self, iter: I) {
+        for (key, priority) in iter {
+            let _ = self.push(key, priority);
+        }
+    }
+}
+
+impl<K, P> FromIterator<(K, P)> for TiIndexedHeap<K, P>
+where
+    K: TiKey + Copy,
+    P: Ord,
+{
+    fn from_iter<I: IntoIterator<Item = (K, P)>>(iter: I) -> Self {
+        let mut heap = Self::new();
+        heap.extend(iter);
+        heap
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::Id;
+    use crate::{HeapOrder, TiIndexedHeap, TiVec};
+    use alloc::{format, vec, vec::Vec};
+    use core::iter::FromIterator;
+
+    fn drain(heap: &mut TiIndexedHeap<Id, i32>) -> Vec<i32> {
+        let mut priorities = Vec::new();
+        while let Some((key, priority)) = heap.pop() {
+            assert!(!heap.contains(key));
+            priorities.push(priority);
+        }
+        priorities
+    }
+
+    #[test]
+    fn orders() {
+        let priorities = [5, -3, 8, 0, 5, 12, -7, 1];
+        let entries = || {
+            priorities
+                .iter()
+                .enumerate()
+                .map(|(index, &priority)| (Id::from(index), priority))
+        };
+        let mut min = TiIndexedHeap::from_iter(entries());
+        assert_eq!(min.order(), HeapOrder::Min);
+        assert_eq!(min.peek(), Some((Id::from(6), &-7)));
+        assert_eq!(drain(&mut min), [-7, -3, 0, 1, 5, 5, 8, 12]);
+
+        let mut max = TiIndexedHeap::with_order(HeapOrder::Max);
+        max.extend(entries());
+        assert_eq!(max.len(), 8);
+        assert_eq!(max.peek(), Some((Id::from(5), &12)));
+        assert_eq!(drain(&mut max), [12, 8, 5, 5, 1, 0, -3, -7]);
+        assert!(max.is_empty());
+    }
+
+    #[test]
+    fn change_and_remove() {
+        let mut heap: TiIndexedHeap<Id, i32> = (0..20)
+            .map(|index| (Id::from(index), index as i32))
+            .collect();
+        assert_eq!(heap.change_priority(Id::from(15), -1), Some(15));
+        assert_eq!(heap.change_priority(Id::from(0), 30), Some(0));
+        assert_eq!(heap.change_priority(Id::from(20), 0), None);
+        assert_eq!(heap.push(Id::from(7), 100), Some(7));
+        assert_eq!(heap.remove(Id::from(3)), Some(3));
+        assert_eq!(heap.remove(Id::from(3)), None);
+        assert_eq!(heap.priority(Id::from(7)), Some(&100));
+        assert_eq!(heap.priority(Id::from(3)), None);
+        assert!(heap.contains(Id::from(19)));
+        assert_eq!(
+            drain(&mut heap),
+            [-1, 1, 2, 4, 5, 6, 8, 9, 10, 11, 12, 13, 14, 16, 17, 18, 19, 30, 100]
+        );
+        assert_eq!(heap.push(Id::from(3), 1), None);
+        heap.clear();
+        assert!(!heap.contains(Id::from(3)));
+        assert_eq!(format!("{:?}", heap), "{}");
+    }
+
+    #[test]
+    fn dijkstra() {
+        let edges: TiVec<Id, Vec<(usize, u32)>> = vec![
+            vec![(1, 7), (2, 9), (5, 14)],
+            vec![(0, 7), (2, 10), (3, 15)],
+            vec![(0, 9), (1, 10), (3, 11), (5, 2)],
+            vec![(1, 15), (2, 11), (4, 6)],
+            vec![(3, 6), (5, 9)],
+            vec![(0, 14), (2, 2), (4, 9)],
+        ]
+        .into();
+        let mut distances: TiVec<Id, Option<u32>> = vec![None; edges.len()].into();
+        let mut queue = TiIndexedHeap::new();
+        let _ = queue.push(Id::from(0), 0);
+        while let Some((node, distance)) = queue.pop() {
+            distances[node] = Some(distance);
+            for &(next, weight) in &edges[node] {
+                let next = Id::from(next);
+                let candidate = distance + weight;
+                if distances[next].is_none()
+                    && queue.priority(next).map_or(true, |&old| candidate < old)
+                {
+                    let _ = queue.push(next, candidate);
+                }
+            }
+        }
+        assert_eq!(
+            distances.raw,
+            [Some(0), Some(7), Some(9), Some(20), Some(20), Some(11)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "+ 1 is out of range for the key type")]
+    fn push_usize_max() {
+        let mut heap: TiIndexedHeap<Id, u32> = TiIndexedHeap::new();
+        let _ = heap.push(Id::from(usize::MAX), 1);
+    }
+}
@@ -0,0 +1,543 @@
+use core::{
+    borrow::Borrow,
+    fmt,
+    hash::{BuildHasher, Hash},
+    iter::FusedIterator,
+    marker::PhantomData,
+    ops,
+};
+
+use alloc::{borrow::ToOwned, collections::hash_map::RandomState, string::String};
+
+use crate::index_table::{make_hash, IndexTable};
+use crate::key::key_from_len;
+use crate::{TiKey, TiSlice, TiVec};
+
+/// A value interner that maps values of the type `V` to stable keys of the type `K`.
+///
+/// Interned values are stored once in a [`TiVec<K, V>`],
+/// and the lookup table only stores their indices.
+/// Keys index the interned values returned by [`as_slice`].
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::TiInterner;
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct SymbolId(usize);
+///
+/// let mut symbols: TiInterner<SymbolId, String> = TiInterner::new();
+/// let foo = symbols.intern("foo");
+/// let bar = symbols.intern_owned(String::from("bar"));
+/// assert_eq!(symbols.intern("foo"), foo);
+/// assert_eq!(symbols.get("bar"), Some(bar));
+/// assert_eq!(symbols.get("baz"), None);
+/// assert_eq!(symbols[bar], "bar");
+/// assert_eq!(symbols.as_slice().len(), 2);
+/// ```
+///
+/// [`TiVec<K, V>`]: struct.TiVec.html
+/// [`as_slice`]: #method.as_slice
+pub struct TiInterner<K, V, S = RandomState> {
+    values: TiVec<K, V>,
+    table: IndexTable,
+    hash_builder: S,
+}
+
+/// A string interner that maps strings to stable keys of the type `K`
+/// and keeps all string data in one buffer.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::TiStrInterner;
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct NameId(usize);
+///
+/// let mut names: TiStrInterner<NameId> = TiStrInterner::new();
+/// let main = names.intern("main");
+/// let args = names.intern("args");
+/// assert_eq!(names.intern("main"), main);
+/// assert_eq!(names.get("args"), Some(args));
+/// assert_eq!(&names[args], "args");
+/// assert_eq!(names.as_str(), "mainargs");
+/// ```
+pub struct TiStrInterner<K, S = RandomState> {
+    buffer: String,
+    ends: TiVec<K, usize>,
+    table: IndexTable,
+    hash_builder: S,
+}
+
+impl<K, V> TiInterner<K, V> {
+    /// Constructs a new, empty interner.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+
+    /// Constructs a new, empty interner that can hold `capacity` values
+    /// without reallocating.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K, V, S> TiInterner<K, V, S> {
+    /// Constructs a new, empty interner that uses the hash builder to hash values.
+    #[inline]
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            values: TiVec::new(),
+            table: IndexTable::default(),
+            hash_builder,
+        }
+    }
+
+    /// Constructs a new, empty interner that can hold `capacity` values
+    /// without reallocating and uses the hash builder to hash values.
+    #[inline]
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        Self {
+            values: TiVec::with_capacity(capacity),
+            table: IndexTable::with_capacity(capacity),
+            hash_builder,
+        }
+    }
+
+    /// Returns the number of interned values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if the interner contains no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns a slice of interned values indexed by their keys.
+    #[inline]
+    pub fn as_slice(&self) -> &TiSlice<K, V> {
+        &self.values
+    }
+
+    /// Converts the interner into a vector of interned values indexed by their keys.
+    #[inline]
+    pub fn into_vec(self) -> TiVec<K, V> {
+        self.values
+    }
+
+    /// Returns the key of the interned value, or `None` if the value is not interned.
+    pub fn get<Q>(&self, value: &Q) -> Option<K>
+    where
+        K: TiKey,
+        V: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        S: BuildHasher,
+    {
+        let hash = make_hash(&self.hash_builder, value);
+        self.table
+            .find(hash, |index| self.values.raw[index].borrow() == value)
+            .map(K::from_usize)
+    }
+
+    /// Returns the key of the value, interning an owned copy of it if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key of a new value can not be represented by the key type.
+    pub fn intern<Q>(&mut self, value: &Q) -> K
+    where
+        K: TiKey,
+        V: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = V> + ?Sized,
+        S: BuildHasher,
+    {
+        let hash = make_hash(&self.hash_builder, value);
+        match self
+            .table
+            .find(hash, |index| self.values.raw[index].borrow() == value)
+        {
+            Some(index) => K::from_usize(index),
+            None => self.insert_new::<Q>(hash, value.to_owned()),
+        }
+    }
+
+    /// Returns the key of the value, interning the value if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key of a new value can not be represented by the key type.
+    pub fn intern_owned(&mut self, value: V) -> K
+    where
+        K: TiKey,
+        V: Hash + Eq,
+        S: BuildHasher,
+    {
+        let hash = make_hash(&self.hash_builder, &value);
+        match self
+            .table
+            .find(hash, |index| self.values.raw[index] == value)
+        {
+            Some(index) => K::from_usize(index),
+            None => self.insert_new::<V>(hash, value),
+        }
+    }
+
+    fn insert_new<Q>(&mut self, hash: u64, value: V) -> K
+    where
+        K: TiKey,
+        V: Borrow<Q>,
+        Q: Hash + ?Sized,
+        S: BuildHasher,
+    {
+        let index = self.values.len();
+        let key = key_from_len(index);
+        self.values.raw.push(value);
+        let (values, hash_builder) = (&self.values.raw, &self.hash_builder);
+        self.table.insert(hash, index, |index| {
+            make_hash::<_, Q>(hash_builder, values[index].borrow())
+        });
+        key
+    }
+}
+
+impl<K> TiStrInterner<K> {
+    /// Constructs a new, empty interner.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<K, S> TiStrInterner<K, S> {
+    /// Constructs a new, empty interner that uses the hash builder to hash strings.
+    #[inline]
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            buffer: String::new(),
+            ends: TiVec::new(),
+            table: IndexTable::default(),
+            hash_builder,
+        }
+    }
+
+    /// Returns the number of interned strings.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Returns `true` if the interner contains no strings.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Returns the buffer with all interned strings concatenated in key order.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.buffer
+    }
+
+    /// Returns the interned string for the key, or `None` if the key is out of bounds.
+    #[inline]
+    pub fn resolve(&self, key: K) -> Option<&str>
+    where
+        K: TiKey,
+    {
+        let index = key.to_usize();
+        if index < self.ends.len() {
+            Some(self.str_at(index))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the key of the interned string, or `None` if the string is not interned.
+    pub fn get(&self, value: &str) -> Option<K>
+    where
+        K: TiKey,
+        S: BuildHasher,
+    {
+        let hash = make_hash(&self.hash_builder, value);
+        self.table
+            .find(hash, |index| self.str_at(index) == value)
+            .map(K::from_usize)
+    }
+
+    /// Returns the key of the string, appending it to the buffer if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key of a new string can not be represented by the key type.
+    pub fn intern(&mut self, value: &str) -> K
+    where
+        K: TiKey,
+        S: BuildHasher,
+    {
+        let hash = make_hash(&self.hash_builder, value);
+        if let Some(index) = self.table.find(hash, |index| self.str_at(index) == value) {
+            return K::from_usize(index);
+        }
+        let index = self.ends.len();
+        let key = key_from_len(index);
+        self.buffer.push_str(value);
+        self.ends.raw.push(self.buffer.len());
+        let (buffer, ends, hash_builder) = (&self.buffer, &self.ends.raw, &self.hash_builder);
+        self.table.insert(hash, index, |index| {
+            make_hash(hash_builder, str_at(buffer, ends, index))
+        });
+        key
+    }
+
+    /// Returns an iterator over keys and interned strings in key order.
+    #[inline]
+    pub fn iter_enumerated(&self) -> TiStrInternerIter<'_, K> {
+        TiStrInternerIter {
+            buffer: &self.buffer,
+            ends: &self.ends.raw,
+            front: 0,
+            back: self.ends.len(),
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn str_at(&self, index: usize) -> &str {
+        str_at(&self.buffer, &self.ends.raw, index)
+    }
+}
+
+#[inline]
+fn str_at<'a>(buffer: &'a str, ends: &[usize], index: usize) -> &'a str {
+    let start = if index == 0 { 0 } else { ends[index - 1] };
+    &buffer[start..ends[index]]
+}
+
+impl<K, V, S> fmt::Debug for TiInterner<K, V, S>
+where
+    K: fmt::Debug + TiKey,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.values.iter_enumerated())
+            .finish()
+    }
+}
+
+impl<K, S> fmt::Debug for TiStrInterner<K, S>
+where
+    K: fmt::Debug + TiKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter_enumerated()).finish()
+    }
+}
+
+impl<K, V, S> ops::Index<K> for TiInterner<K, V, S>
+where
+    K: TiKey,
+{
+    type Output = V;
+
+    #[inline]
+    fn index(&self, key: K) -> &V {
+        &self.values[key]
+    }
+}
+
+impl<K, S> ops::Index<K> for TiStrInterner<K, S>
+where
+    K: TiKey,
+{
+    type Output = str;
+
+    #[inline]
+    fn index(&self, key: K) -> &str {
+        let index = key.to_usize();
+        if index >= self.ends.len() {
+            out_of_bounds(index, self.ends.len());
+        }
+        self.str_at(index)
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn out_of_bounds(index: usize, len: usize) -> ! {
+    panic!(
+        "key index {} is out of bounds for the string interner with {} strings",
+        index, len
+    )
+}
+
+impl<K, V, S> Clone for TiInterner<K, V, S>
+where
+    V: Clone,
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            values: self.values.clone(),
+            table: self.table.clone(),
+            hash_builder: self.hash_builder.clone(),
+        }
+    }
+}
+
+impl<K, S> Clone for TiStrInterner<K, S>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer.clone(),
+            ends: self.ends.clone(),
+            table: self.table.clone(),
+            hash_builder: self.hash_builder.clone(),
+        }
+    }
+}
+
+impl<K, V, S> Default for TiInterner<K, V, S>
+where
+    S: Default,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, S> Default for TiStrInterner<K, S>
+where
+    S: Default,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+/// An iterator over keys and strings of a [`TiStrInterner`].
+///
+/// This struct is created by the [`TiStrInterner::iter_enumerated`] method.
+///
+/// [`TiStrInterner`]: struct.TiStrInterner.html
+/// [`TiStrInterner::iter_enumerated`]: struct.TiStrInterner.html#method.iter_enumerated
+pub struct TiStrInternerIter<'a, K> {
+    buffer: &'a str,
+    ends: &'a [usize],
+    front: usize,
+    back: usize,
+    _marker: PhantomData<fn(K) -> K>,
+}
+
+impl<K> fmt::Debug for TiStrInternerIter<'_, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TiStrInternerIter")
+            .field("len", &(self.back - self.front))
+            .finish()
+    }
+}
+
+impl<'a, K: TiKey> Iterator for TiStrInternerIter<'a, K> {
+    type Item = (K, &'a str);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            let index = self.front;
+            self.front += 1;
+            Some((K::from_usize(index), str_at(self.buffer, self.ends, index)))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<K: TiKey> DoubleEndedIterator for TiStrInternerIter<'_, K> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            self.back -= 1;
+            Some((
+                K::from_usize(self.back),
+                str_at(self.buffer, self.ends, self.back),
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+impl<K: TiKey> ExactSizeIterator for TiStrInternerIter<'_, K> {}
+
+impl<K: TiKey> FusedIterator for TiStrInternerIter<'_, K> {}
+
+#[cfg(test)]
+mod test {
+    use crate::test::Id;
+    use crate::{TiInterner, TiStrInterner};
+    use alloc::{format, string::String, vec::Vec};
+
+    #[test]
+    fn intern_values() {
+        let mut interner: TiInterner<Id, u32> = TiInterner::with_capacity(2);
+        let keys: Vec<Id> = (0..100)
+            .map(|value| interner.intern(&(value % 40)))
+            .collect();
+        assert_eq!(interner.len(), 40);
+        assert_eq!(keys[45], keys[5]);
+        assert_eq!(interner.get(&39), Some(Id::from(39)));
+        assert_eq!(interner.get(&40), None);
+        assert_eq!(interner.intern_owned(40), Id::from(40));
+        assert_eq!(interner[Id::from(7)], 7);
+        assert_eq!(interner.as_slice().first_key(), Some(Id::from(0)));
+        assert_eq!(interner.clone().into_vec().len(), 41);
+    }
+
+    #[test]
+    fn intern_strings() {
+        let words = ["b", "", "alpha", "b", "", "gamma"];
+        let mut strings: TiInterner<Id, String> = TiInterner::new();
+        let mut interner: TiStrInterner<Id> = TiStrInterner::new();
+        for word in &words {
+            assert_eq!(strings.intern(*word), interner.intern(word));
+        }
+        assert_eq!(interner.len(), 4);
+        assert_eq!(interner.get(""), Some(Id::from(1)));
+        assert_eq!(interner.get("beta"), None);
+        assert_eq!(&interner[Id::from(2)], "alpha");
+        assert_eq!(interner.resolve(Id::from(4)), None);
+        assert_eq!(interner.as_str(), "balphagamma");
+        assert_eq!(
+            format!("{:?}", interner),
+            r#"{Id(0): "b", Id(1): "", Id(2): "alpha", Id(3): "gamma"}"#
+        );
+        assert_eq!(
+            interner.iter_enumerated().next_back(),
+            Some((Id::from(3), "gamma"))
+        );
+        for index in 0..200 {
+            let _ = interner.intern(&format!("s{}", index));
+        }
+        assert_eq!(interner.get("s150"), Some(Id::from(154)));
+        assert_eq!(interner.clone().get("alpha"), Some(Id::from(2)));
+    }
+}
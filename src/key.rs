@@ -0,0 +1,323 @@
+use core::fmt;
+
+/// A trait for types that can be used as [`TiSlice`] and [`TiVec`] keys.
+///
+/// The trait converts keys to `usize` indices and back
+/// and specifies the largest index a key can represent.
+///
+/// It is implemented for all types `K` implementing [`From<usize>`][`From`]
+/// with `usize: `[`From<K>`][`From`], so keys created
+/// with [`derive_more`] crate and `#[derive(From, Into)]` can be used without any changes.
+/// Keys that can not represent every `usize` value, such as `u32`-backed ids,
+/// can implement this trait directly with the appropriate [`MAX`] value
+/// instead of providing a lossy [`From<usize>`][`From`] implementation.
+///
+/// Containers use [`checked_from_usize`] when a new key is produced from their length,
+/// so methods like [`TiSlice::next_key`] and [`TiVec::push_and_get_key`]
+/// panic instead of returning a wrapped key when the key type runs out of range.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::{TiKey, TiSlice};
+///
+/// #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// struct Id(u8);
+///
+/// impl TiKey for Id {
+///     const MAX: usize = u8::MAX as usize;
+///
+///     fn from_usize(index: usize) -> Self {
+///         assert!(index <= Self::MAX, "index out of range for `Id`");
+///         Self(index as u8)
+///     }
+///
+///     fn to_usize(self) -> usize {
+///         self.0.into()
+///     }
+/// }
+///
+/// let slice: &TiSlice<Id, usize> = TiSlice::from_ref(&[1, 2, 4]);
+/// assert_eq!(slice[Id(2)], 4);
+/// assert_eq!(slice.next_key(), Id(3));
+/// assert_eq!(Id::checked_from_usize(255), Some(Id(255)));
+/// assert_eq!(Id::checked_from_usize(256), None);
+/// ```
+///
+/// [`MAX`]: #associatedconstant.MAX
+/// [`checked_from_usize`]: #method.checked_from_usize
+/// [`TiSlice`]: struct.TiSlice.html
+/// [`TiVec`]: struct.TiVec.html
+/// [`TiSlice::next_key`]: struct.TiSlice.html#method.next_key
+/// [`TiVec::push_and_get_key`]: struct.TiVec.html#method.push_and_get_key
+/// [`From`]: https://doc.rust-lang.org/std/convert/trait.From.html
+/// [`derive_more`]: https://crates.io/crates/derive_more
+pub trait TiKey: Sized {
+    /// The largest `usize` index that can be represented by the key.
+    const MAX: usize;
+
+    /// Converts a `usize` index into a key.
+    ///
+    /// The behavior for indices greater than [`MAX`] is implementation-defined,
+    /// implementations should prefer to panic.
+    ///
+    /// [`MAX`]: #associatedconstant.MAX
+    fn from_usize(index: usize) -> Self;
+
+    /// Converts the key into a `usize` index.
+    fn to_usize(self) -> usize;
+
+    /// Converts a `usize` index into a key,
+    /// or returns `None` if the index is greater than [`MAX`].
+    ///
+    /// [`MAX`]: #associatedconstant.MAX
+    #[inline]
+    fn checked_from_usize(index: usize) -> Option<Self> {
+        if index <= Self::MAX {
+            Some(Self::from_usize(index))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the key `offset` positions after this one,
+    /// or `None` if the resulting index is greater than [`MAX`].
+    ///
+    /// [`MAX`]: #associatedconstant.MAX
+    #[inline]
+    fn checked_add(self, offset: usize) -> Option<Self> {
+        self.to_usize()
+            .checked_add(offset)
+            .and_then(Self::checked_from_usize)
+    }
+}
+
+/// A trait for enum keys with a known number of variants.
+///
+/// Variant indices are `0..COUNT` in declaration order.
+/// The trait is used by [`TiEnumMap`] to store a value for every variant,
+/// so the map indexing is infallible.
+/// It can be implemented with [`TiEnum`][`TiEnum derive`] derive macro
+/// available with `derive` feature, that also implements [`TiKey`] trait.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::{TiEnum, TiKey};
+///
+/// #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// enum Axis {
+///     X,
+///     Y,
+/// }
+///
+/// impl TiKey for Axis {
+///     const MAX: usize = 1;
+///
+///     fn from_usize(index: usize) -> Self {
+///         match index {
+///             0 => Axis::X,
+///             1 => Axis::Y,
+///             _ => panic!("index {} is out of range for `Axis`", index),
+///         }
+///     }
+///
+///     fn to_usize(self) -> usize {
+///         self as usize
+///     }
+/// }
+///
+/// impl TiEnum for Axis {
+///     const COUNT: usize = 2;
+/// }
+///
+/// assert_eq!(Axis::from_index(1), Axis::Y);
+/// assert_eq!(Axis::Y.to_index(), 1);
+/// ```
+///
+/// [`TiKey`]: trait.TiKey.html
+/// [`TiEnumMap`]: struct.TiEnumMap.html
+/// [`TiEnum derive`]: derive.TiEnum.html
+pub trait TiEnum: TiKey + Copy {
+    /// The number of enum variants.
+    ///
+    /// It must be equal to `MAX + 1` of the [`TiKey`] implementation.
+    ///
+    /// [`TiKey`]: trait.TiKey.html
+    const COUNT: usize;
+
+    /// Converts a variant index into a variant.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is greater than or equal to [`COUNT`].
+    ///
+    /// [`COUNT`]: #associatedconstant.COUNT
+    #[inline]
+    fn from_index(index: usize) -> Self {
+        assert!(
+            index < Self::COUNT,
+            "index {} is out of range for the enum with {} variants",
+            index,
+            Self::COUNT
+        );
+        Self::from_usize(index)
+    }
+
+    /// Converts a variant into its index.
+    #[inline]
+    fn to_index(self) -> usize {
+        self.to_usize()
+    }
+}
+
+impl<K> TiKey for K
+where
+    K: From<usize>,
+    usize: From<K>,
+{
+    const MAX: usize = usize::MAX;
+
+    #[inline]
+    fn from_usize(index: usize) -> Self {
+        index.into()
+    }
+
+    #[inline]
+    fn to_usize(self) -> usize {
+        self.into()
+    }
+}
+
+/// Converts a container length into a key,
+/// panicking if the key type can not represent it.
+#[inline]
+pub(crate) fn key_from_len<K: TiKey>(len: usize) -> K {
+    match K::checked_from_usize(len) {
+        Some(key) => key,
+        None => key_out_of_range(&len, K::MAX),
+    }
+}
+
+/// Returns the index located `offset` positions after `base`,
+/// panicking like [`key_from_len`] if it overflows `usize`.
+#[cfg(any(feature = "alloc", feature = "std"))]
+#[inline]
+pub(crate) fn offset_index<K: TiKey>(base: usize, offset: usize) -> usize {
+    match base.checked_add(offset) {
+        Some(index) => index,
+        None => key_out_of_range(&format_args!("{} + {}", base, offset), K::MAX),
+    }
+}
+
+/// Asserts that every key of a container with the specified length
+/// whose first key is `base` can be represented by the key type.
+#[cfg(any(feature = "alloc", feature = "std"))]
+#[inline]
+pub(crate) fn assert_offset_keys_in_range<K: TiKey>(base: usize, len: usize) {
+    if let Some(offset) = len.checked_sub(1) {
+        let last = offset_index::<K>(base, offset);
+        if last > K::MAX {
+            key_out_of_range(&last, K::MAX);
+        }
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn key_out_of_range(index: &dyn fmt::Display, max: usize) -> ! {
+    panic!(
+        "index {} is out of range for the key type with maximum index {}",
+        index, max
+    )
+}
+
+/// Asserts that every index of a container with the specified length
+/// can be represented by the key type.
+#[inline]
+pub(crate) fn assert_keys_in_range<K: TiKey>(len: usize) {
+    assert!(
+        len == 0 || len - 1 <= K::MAX,
+        "container length {} is out of range for the key type with maximum index {}",
+        len,
+        K::MAX
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{TiKey, TiSlice};
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    struct SmallId(u8);
+
+    impl TiKey for SmallId {
+        const MAX: usize = u8::MAX as usize;
+
+        fn from_usize(index: usize) -> Self {
+            assert!(index <= Self::MAX);
+            Self(index as u8)
+        }
+
+        fn to_usize(self) -> usize {
+            self.0.into()
+        }
+    }
+
+    #[test]
+    fn checked_conversions() {
+        assert_eq!(SmallId::checked_from_usize(0), Some(SmallId(0)));
+        assert_eq!(SmallId::checked_from_usize(255), Some(SmallId(255)));
+        assert_eq!(SmallId::checked_from_usize(256), None);
+        assert_eq!(SmallId(254).checked_add(1), Some(SmallId(255)));
+        assert_eq!(SmallId(255).checked_add(1), None);
+        assert_eq!(<usize as TiKey>::MAX, usize::MAX);
+    }
+
+    #[test]
+    fn small_key_slice() {
+        let array = [0_u32; 256];
+        let slice: &TiSlice<SmallId, u32> = TiSlice::from_ref(&array[..255]);
+        assert_eq!(slice.next_key(), SmallId(255));
+        assert_eq!(slice.last_key(), Some(SmallId(254)));
+        assert_eq!(slice.keys().next_back(), Some(SmallId(254)));
+        assert_eq!(slice.get(SmallId(3)), Some(&0));
+    }
+
+    #[test]
+    #[should_panic(expected = "container length 257 is out of range")]
+    fn small_key_iter_enumerated_overflow() {
+        let array = [0_u32; 257];
+        let slice: &TiSlice<SmallId, u32> = TiSlice::from_ref(&array);
+        let _ = slice.iter_enumerated();
+    }
+
+    #[test]
+    #[should_panic(expected = "container length 257 is out of range")]
+    fn small_key_position_overflow() {
+        let array = [0_u32; 257];
+        let slice: &TiSlice<SmallId, u32> = TiSlice::from_ref(&array);
+        let _ = slice.position(|&value| value == 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn small_key_next_key_overflow() {
+        let array = [0_u32; 256];
+        let slice: &TiSlice<SmallId, u32> = TiSlice::from_ref(&array);
+        let _ = slice.next_key();
+    }
+
+    #[cfg(any(feature = "alloc", feature = "std"))]
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn small_key_push_overflow() {
+        use crate::TiVec;
+
+        let mut vec: TiVec<SmallId, u32> = TiVec::new();
+        for index in 0..256 {
+            assert_eq!(vec.push_and_get_key(0), SmallId(index as u8));
+        }
+        let _ = vec.push_and_get_key(0);
+    }
+}
@@ -18,13 +18,54 @@
 //! the slice primitive [`[V]`][`slice`] and the container [`std::vec::Vec<V>`][`std::vec::Vec`].
 //! Crate containers mirror the stable API of the matched Rust containers
 //! and forward to them as much as possible.
+//! Fixed-size [`TiArray<K, V, N>`][`TiArray`] container wraps the array primitive `[V; N]`
+//! and does not require memory allocation,
+//! as well as fixed-capacity [`TiArrayVec<K, V, CAP>`][`TiArrayVec`] vector
+//! that mirrors [`TiVec`] API in `#![no_std]` context without allocator.
+//! [`TiSmallVec<K, V, N>`][`TiSmallVec`] vector keeps up to `N` elements inline
+//! and moves them to the heap on overflow.
+//! [`TiVecDeque<K, V>`][`TiVecDeque`] wraps [`std::collections::VecDeque<V>`][`std::collections::VecDeque`]
+//! in the same way.
+//! [`TiRingBuffer<K, V>`][`TiRingBuffer`] is a bounded buffer that evicts old entries
+//! while keys keep counting upward.
+//! [`TiOffsetVec<K, V>`][`TiOffsetVec`] is a vector whose keys start at a runtime base.
+//! [`TiSlab<K, V>`][`TiSlab`] reuses keys of removed values without shifting other values,
+//! and [`TiArena<K, V>`][`TiArena`] additionally rejects stale generational handles.
+//! [`TiIdAllocator<K>`][`TiIdAllocator`] hands out and recycles keys for data kept elsewhere.
+//! [`TiSecondaryMap<K, V>`][`TiSecondaryMap`] attaches optional values to a subset of keys
+//! and grows on insertion.
+//! [`TiBitSet<K>`][`TiBitSet`] and growable [`TiBitVec<K>`][`TiBitVec`] are bitsets of keys.
+//! [`TiBitMatrix<R, C>`][`TiBitMatrix`] and [`TiSparseBitMatrix<R, C>`][`TiSparseBitMatrix`]
+//! are dense and sparse bit matrices relating two key types.
+//! [`TiSparseSet<K>`][`TiSparseSet`] is a sparse set of keys with constant time clearing
+//! and iteration in insertion order.
+//! [`TiInterner<K, V>`][`TiInterner`] and [`TiStrInterner<K>`][`TiStrInterner`]
+//! map interned values to stable keys and require `std` feature.
+//! [`TiIndexMap<P, K, V>`][`TiIndexMap`] is an insertion-ordered hash map
+//! that identifies entries by typed positions and requires `std` feature.
+//! [`TiBiMap<A, B>`][`TiBiMap`] keeps a one-to-one correspondence between two key types.
+//! [`TiUnionFind<K, V>`][`TiUnionFind`] partitions keys into disjoint classes
+//! and supports rolling back speculative unions.
+//! [`TiPartition<K, B>`][`TiPartition`] is a partition refinement structure
+//! that keeps members of every block contiguous.
+//! [`TiIndexedHeap<K, P>`][`TiIndexedHeap`] is a priority queue of keys
+//! that can change priorities of queued keys.
+//! [`TiPrefixSums<K, V>`][`TiPrefixSums`] is a Fenwick tree maintaining prefix sums of values
+//! under point updates.
+//! [`TiEnumMap<K, V>`][`TiEnumMap`] container stores a value for every variant
+//! of enum keys implementing [`TiEnum`] trait.
 //!
 //! [`TiSlice`] and [`TiVec`] can be easily converted to matched Rust containers and back using
 //! [`From`], [`Into`], [`AsRef`] and [`AsMut`] traits.
 //! Also, they expose `raw` property with the original data type.
-//! Containers only require the index to implement
+//! Containers only require the index to implement [`TiKey`] trait.
+//! It is implemented for all types implementing
 //! [`From<usize>`][`From`] and [`Into<usize>`][`Into`] traits
 //! that can be easily done with [`derive_more`] crate and `#[derive(From, Into)]`.
+//! Keys that can not represent every `usize` value, such as `u32`-backed ids,
+//! can implement [`TiKey`] directly to detect index overflows
+//! or be declared with [`ti_index!`] macro that does not require any extra dependencies.
+//! Keys backed by [`NonMaxU32`] and other `NonMax` types keep `Option<K>` the same size as `K`.
 //!
 //! # Usage
 //!
@@ -141,6 +182,9 @@
 //! - `std` (enabled by default): Enables all [`std`] features
 //!   such as memory allocations, [`std::error::Error`] trait and
 //!   [`std::panic::UnwindSafe`] trait implementations.
+//! - `derive`: Enables [`TiIndex`] derive macro that implements [`TiKey`] trait
+//!   for single-field index structs and `TiEnum` derive macro
+//!   that implements [`TiEnum`] trait for fieldless enums.
 //! - `serde`: Implements [`Serialize`] trait for [`TiSlice`] and [`TiVec`] containers.
 //! - `serde-alloc`: Enables [`alloc`] and `serde/alloc` features and
 //!   implements [`Deserialize`] trait for [`Box`]`<`[`TiSlice`]`>` and [`TiVec`].
@@ -185,6 +229,35 @@
 //!
 //! [`TiSlice`]: struct.TiSlice.html
 //! [`TiVec`]: struct.TiVec.html
+//! [`TiArray`]: struct.TiArray.html
+//! [`TiArrayVec`]: struct.TiArrayVec.html
+//! [`TiSmallVec`]: struct.TiSmallVec.html
+//! [`TiVecDeque`]: struct.TiVecDeque.html
+//! [`TiRingBuffer`]: struct.TiRingBuffer.html
+//! [`TiOffsetVec`]: struct.TiOffsetVec.html
+//! [`TiSlab`]: struct.TiSlab.html
+//! [`TiArena`]: struct.TiArena.html
+//! [`TiIdAllocator`]: struct.TiIdAllocator.html
+//! [`TiSecondaryMap`]: struct.TiSecondaryMap.html
+//! [`TiBitSet`]: struct.TiBitSet.html
+//! [`TiBitVec`]: struct.TiBitVec.html
+//! [`TiBitMatrix`]: struct.TiBitMatrix.html
+//! [`TiSparseBitMatrix`]: struct.TiSparseBitMatrix.html
+//! [`TiSparseSet`]: struct.TiSparseSet.html
+//! [`TiInterner`]: struct.TiInterner.html
+//! [`TiStrInterner`]: struct.TiStrInterner.html
+//! [`TiIndexMap`]: struct.TiIndexMap.html
+//! [`TiBiMap`]: struct.TiBiMap.html
+//! [`TiUnionFind`]: struct.TiUnionFind.html
+//! [`TiPartition`]: struct.TiPartition.html
+//! [`TiIndexedHeap`]: struct.TiIndexedHeap.html
+//! [`TiPrefixSums`]: struct.TiPrefixSums.html
+//! [`TiEnumMap`]: struct.TiEnumMap.html
+//! [`TiEnum`]: trait.TiEnum.html
+//! [`TiKey`]: trait.TiKey.html
+//! [`TiIndex`]: derive.TiIndex.html
+//! [`ti_index!`]: macro.ti_index.html
+//! [`NonMaxU32`]: struct.NonMaxU32.html
 //! [`std`]: https://doc.rust-lang.org/std/index.html
 //! [`alloc`]: https://doc.rust-lang.org/alloc/index.html
 //! [`slice`]: https://doc.rust-lang.org/std/primitive.slice.html
@@ -192,6 +265,7 @@
 //! [`Rc`]: https://doc.rust-lang.org/std/rc/struct.Rc.html
 //! [`Weak`]: https://doc.rust-lang.org/std/rc/struct.Weak.html
 //! [`std::vec::Vec`]: https://doc.rust-lang.org/std/vec/struct.Vec.html
+//! [`std::collections::VecDeque`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html
 //! [`std::error::Error`]: https://doc.rust-lang.org/std/error/trait.Error.html
 //! [`std::panic::UnwindSafe`]: https://doc.rust-lang.org/std/panic/trait.UnwindSafe.html
 //! [`From`]: https://doc.rust-lang.org/std/convert/trait.From.html
@@ -229,16 +303,111 @@ extern crate std as alloc;
 #[macro_use]
 mod test;
 
+#[macro_use]
+mod macros;
+
+mod array;
+mod array_vec;
 mod iter;
+mod key;
+mod nonmax;
 mod range;
 mod slice;
 
+#[cfg(any(feature = "alloc", feature = "std"))]
+mod arena;
+#[cfg(any(feature = "alloc", feature = "std"))]
+mod bi_map;
+#[cfg(any(feature = "alloc", feature = "std"))]
+mod bit_matrix;
+#[cfg(any(feature = "alloc", feature = "std"))]
+mod bit_set;
+#[cfg(any(feature = "alloc", feature = "std"))]
+mod enum_map;
+#[cfg(any(feature = "alloc", feature = "std"))]
+mod id_allocator;
+#[cfg(feature = "std")]
+mod index_map;
+#[cfg(feature = "std")]
+mod index_table;
+#[cfg(any(feature = "alloc", feature = "std"))]
+mod indexed_heap;
+#[cfg(feature = "std")]
+mod interner;
+#[cfg(any(feature = "alloc", feature = "std"))]
+mod offset_vec;
+#[cfg(any(feature = "alloc", feature = "std"))]
+mod partition;
+#[cfg(any(feature = "alloc", feature = "std"))]
+mod prefix_sums;
+#[cfg(any(feature = "alloc", feature = "std"))]
+mod ring_buffer;
+#[cfg(any(feature = "alloc", feature = "std"))]
+mod secondary_map;
+#[cfg(any(feature = "alloc", feature = "std"))]
+mod slab;
+#[cfg(any(feature = "alloc", feature = "std"))]
+mod small_vec;
+#[cfg(any(feature = "alloc", feature = "std"))]
+mod sparse_set;
+#[cfg(any(feature = "alloc", feature = "std"))]
+mod union_find;
 #[cfg(any(feature = "alloc", feature = "std"))]
 mod vec;
+#[cfg(any(feature = "alloc", feature = "std"))]
+mod vec_deque;
 
+pub use array::TiArray;
+pub use array_vec::{CapacityError, TiArrayVec, TiArrayVecDrain, TiArrayVecIntoIter};
 pub use iter::{TiEnumerated, TiSliceKeys, TiSliceMutMap, TiSliceRefMap};
+pub use key::{TiEnum, TiKey};
+pub use nonmax::{NonMaxU16, NonMaxU32, NonMaxU8, NonMaxUsize};
 pub use range::TiRangeBounds;
 pub use slice::{TiSlice, TiSliceIndex};
 
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub use arena::{TiArena, TiArenaIntoIter, TiArenaIter, TiArenaIterMut, TiHandle};
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub use bi_map::{TiBiMap, TiBiMapIter};
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub use bit_matrix::{TiBitMatrix, TiSparseBitMatrix};
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub use bit_set::{TiBitSet, TiBitSetIter, TiBitVec};
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub use enum_map::TiEnumMap;
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub use id_allocator::{ReusePolicy, TiIdAllocator};
+#[cfg(feature = "std")]
+pub use index_map::{TiIndexMap, TiIndexMapIter, TiIndexMapIterMut};
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub use indexed_heap::{HeapOrder, TiIndexedHeap};
+#[cfg(feature = "std")]
+pub use interner::{TiInterner, TiStrInterner, TiStrInternerIter};
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub use offset_vec::{TiOffsetVec, TiOffsetVecKeys};
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub use partition::{TiPartition, TiPartitionIter};
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub use prefix_sums::TiPrefixSums;
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub use ring_buffer::TiRingBuffer;
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub use secondary_map::{
+    TiSecondaryMap, TiSecondaryMapEntry, TiSecondaryMapIntoIter, TiSecondaryMapIter,
+    TiSecondaryMapIterMut, TiSecondaryMapOccupiedEntry, TiSecondaryMapVacantEntry,
+};
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub use slab::{TiSlab, TiSlabDrain, TiSlabIntoIter, TiSlabIter, TiSlabIterMut};
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub use small_vec::{TiSmallVec, TiSmallVecDrain, TiSmallVecIntoIter, TiSmallVecSplice};
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub use sparse_set::TiSparseSet;
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub use union_find::{TiUnionFind, TiUnionFindSnapshot};
 #[cfg(any(feature = "alloc", feature = "std"))]
 pub use vec::TiVec;
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub use vec_deque::TiVecDeque;
+
+#[cfg(feature = "derive")]
+pub use typed_index_collections_derive::{TiEnum, TiIndex};
@@ -0,0 +1,227 @@
+/// Declares an index newtype that can be used as [`TiSlice`] and [`TiVec`] key.
+///
+/// The macro emits the struct itself and the [`TiKey`] trait implementation for it,
+/// so it does not require any proc-macro dependencies and works in `#![no_std]` context
+/// without `alloc` feature.
+/// Attributes, including doc comments and derives, are passed to the struct as is.
+///
+/// The backing type can be `u8`, `u16`, `u32`, `usize`
+/// or any other type implementing [`TiKey`].
+/// `usize` indices greater than the backing type maximum
+/// cause a panic when converted to the key.
+///
+/// The struct can be followed by a block with optional items in the following order:
+/// - `const MAX = value;` - Limits the largest key value reported by [`TiKey::MAX`]
+///   and adds `LAST` associated constant with the largest key.
+/// - `const START = value;` - Adds `START` associated constant.
+/// - `debug = "format";` - Implements [`Debug`] trait with the specified format string
+///   that takes the backing value as its only argument.
+///
+/// `MAX` and `START` values have the backing type and are only supported
+/// for primitive backing types.
+/// `START` greater than `MAX` fails to compile.
+/// The largest key is named `LAST` so that `MAX` always refers to [`TiKey::MAX`]
+/// of the type `usize`.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::{ti_index, TiKey, TiSlice};
+///
+/// ti_index! {
+///     /// Basic block index.
+///     #[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+///     pub struct BlockId(u32) {
+///         const MAX = 0xFFFF_FF00;
+///         const START = 0;
+///         debug = "bb{}";
+///     }
+/// }
+///
+/// let blocks: &TiSlice<BlockId, &str> = TiSlice::from_ref(&["entry", "exit"]);
+/// assert_eq!(blocks[BlockId::START], "entry");
+/// assert_eq!(blocks.last_key(), Some(BlockId::from_usize(1)));
+/// assert_eq!(BlockId::LAST.to_usize(), 0xFFFF_FF00);
+/// assert_eq!(BlockId::MAX, 0xFFFF_FF00);
+/// assert_eq!(BlockId::checked_from_usize(0xFFFF_FF01), None);
+/// assert_eq!(format!("{:?}", blocks), r#"{bb0: "entry", bb1: "exit"}"#);
+/// ```
+///
+/// ```compile_fail
+/// use typed_index_collections::ti_index;
+///
+/// ti_index! {
+///     struct Id(u32) {
+///         const MAX = 10;
+///         const START = 20;
+///     }
+/// }
+/// ```
+///
+/// [`TiSlice`]: struct.TiSlice.html
+/// [`TiVec`]: struct.TiVec.html
+/// [`TiKey`]: trait.TiKey.html
+/// [`TiKey::MAX`]: trait.TiKey.html#associatedconstant.MAX
+/// [`Debug`]: https://doc.rust-lang.org/std/fmt/trait.Debug.html
+#[macro_export]
+macro_rules! ti_index {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident($field_vis:vis $repr:ident);
+    ) => {
+        $crate::ti_index! {
+            $(#[$attr])*
+            $vis struct $name($field_vis $repr) {}
+        }
+    };
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident($field_vis:vis $repr:ident) {
+            $(const MAX = $max:expr;)?
+            $(const START = $start:expr;)?
+            $(debug = $debug:literal;)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $name($field_vis $repr);
+
+        impl $crate::TiKey for $name {
+            const MAX: usize = $crate::__ti_index_repr!(max $repr $(, $max)?);
+
+            #[inline]
+            fn from_usize(index: usize) -> Self {
+                assert!(
+                    index <= <Self as $crate::TiKey>::MAX,
+                    "index {} is out of range for `{}`",
+                    index,
+                    stringify!($name),
+                );
+                Self($crate::__ti_index_repr!(from $repr, index))
+            }
+
+            #[inline]
+            fn to_usize(self) -> usize {
+                $crate::__ti_index_repr!(to $repr, self.0)
+            }
+        }
+
+        $(
+            impl $name {
+                /// The largest key value.
+                $vis const LAST: Self = Self($max);
+            }
+        )?
+
+        $(
+            impl $name {
+                /// The first key value.
+                $vis const START: Self = Self($start);
+            }
+
+            const _: () = assert!(
+                $crate::__ti_index_repr!(to $repr, $start) <= <$name as $crate::TiKey>::MAX,
+                concat!("`START` of `", stringify!($name), "` is greater than its `MAX`"),
+            );
+        )?
+
+        $(
+            impl ::core::fmt::Debug for $name {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    write!(f, $debug, self.0)
+                }
+            }
+        )?
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ti_index_repr {
+    (max $repr:ident, $max:expr) => {
+        $crate::__ti_index_repr!(to $repr, $max)
+    };
+    (max u8) => { u8::MAX as usize };
+    (max u16) => { u16::MAX as usize };
+    (max u32) => { u32::MAX as usize };
+    (max $repr:ident) => { <$repr as $crate::TiKey>::MAX };
+    (from u8, $index:expr) => { $index as u8 };
+    (from u16, $index:expr) => { $index as u16 };
+    (from u32, $index:expr) => { $index as u32 };
+    (from $repr:ident, $index:expr) => { <$repr as $crate::TiKey>::from_usize($index) };
+    (to u8, $value:expr) => { $value as usize };
+    (to u16, $value:expr) => { $value as usize };
+    (to u32, $value:expr) => { $value as usize };
+    (to $repr:ident, $value:expr) => { <$repr as $crate::TiKey>::to_usize($value) };
+}
+
+#[cfg(test)]
+mod test {
+    use crate::TiKey;
+
+    ti_index! {
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        struct SmallId(u8);
+    }
+
+    ti_index! {
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        struct UsizeId(usize);
+    }
+
+    ti_index! {
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        struct NestedId(SmallId);
+    }
+
+    ti_index! {
+        #[derive(Clone, Copy, Eq, PartialEq)]
+        pub struct BlockId(pub u16) {
+            const MAX = 1000;
+            const START = 1;
+            debug = "bb{}";
+        }
+    }
+
+    #[test]
+    fn key_conversions() {
+        assert_eq!(SmallId::MAX, 255);
+        assert_eq!(SmallId::from_usize(255), SmallId(255));
+        assert_eq!(SmallId(7).to_usize(), 7);
+        assert_eq!(SmallId::checked_from_usize(256), None);
+        assert_eq!(<UsizeId as TiKey>::MAX, usize::MAX);
+        assert_eq!(UsizeId::from_usize(9), UsizeId(9));
+        assert_eq!(<NestedId as TiKey>::MAX, 255);
+        assert_eq!(NestedId::from_usize(4), NestedId(SmallId(4)));
+        assert_eq!(NestedId(SmallId(4)).to_usize(), 4);
+    }
+
+    #[test]
+    fn constants() {
+        assert_eq!(<BlockId as TiKey>::MAX, 1000);
+        assert_eq!(BlockId::MAX, 1000);
+        assert_eq!(BlockId::LAST.0, 1000);
+        assert_eq!(BlockId::START.0, 1);
+        assert_eq!(
+            BlockId::checked_from_usize(1000).map(|key| key.0),
+            Some(1000)
+        );
+        assert!(BlockId::checked_from_usize(1001).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "index 256 is out of range for `SmallId`")]
+    fn key_overflow() {
+        let _ = SmallId::from_usize(256);
+    }
+
+    #[cfg(any(feature = "alloc", feature = "std"))]
+    #[test]
+    fn debug_format() {
+        use crate::TiSlice;
+        use alloc::format;
+
+        let slice: &TiSlice<BlockId, u32> = TiSlice::from_ref(&[1, 2]);
+        assert_eq!(format!("{:?}", BlockId(12)), "bb12");
+        assert_eq!(format!("{:?}", slice), "{bb0: 1, bb1: 2}");
+    }
+}
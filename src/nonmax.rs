@@ -0,0 +1,251 @@
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::num::{NonZeroU16, NonZeroU32, NonZeroU8, NonZeroUsize};
+
+#[cfg(feature = "serde")]
+use serde::{
+    de::{Deserialize, Deserializer, Error, Unexpected},
+    ser::{Serialize, Serializer},
+};
+
+use crate::TiKey;
+
+macro_rules! nonmax {
+    ($(#[$attr:meta])* $name:ident, $inner:ident, $int:ident) => {
+        $(#[$attr])*
+        ///
+        /// The value is stored as its bitwise complement in a non-zero integer,
+        /// so `Option<K>` has the same size as `K`.
+        /// It implements [`TiKey`] trait with `MAX` equal to the largest representable value,
+        /// so it can be used as [`TiSlice`] and [`TiVec`] key directly
+        /// or as a backing type of [`ti_index!`] and [`TiIndex`] keys.
+        /// Producing a key for the reserved maximum value panics.
+        ///
+        /// [`TiKey`]: trait.TiKey.html
+        /// [`TiSlice`]: struct.TiSlice.html
+        /// [`TiVec`]: struct.TiVec.html
+        /// [`ti_index!`]: macro.ti_index.html
+        /// [`TiIndex`]: derive.TiIndex.html
+        #[derive(Clone, Copy, Eq, PartialEq)]
+        #[repr(transparent)]
+        pub struct $name($inner);
+
+        impl $name {
+            /// The largest value that can be represented.
+            pub const MAX: $int = $int::MAX - 1;
+
+            /// Creates a value if the given value is not the reserved maximum.
+            #[inline]
+            pub fn new(value: $int) -> Option<Self> {
+                $inner::new(!value).map(Self)
+            }
+
+            /// Creates a value without checking that it is not the reserved maximum.
+            ///
+            /// # Safety
+            ///
+            /// The value must not be the reserved maximum value of the primitive type.
+            #[inline]
+            pub unsafe fn new_unchecked(value: $int) -> Self {
+                // SAFETY: The caller guarantees that the value is not the maximum,
+                // so its complement is not zero.
+                Self($inner::new_unchecked(!value))
+            }
+
+            /// Returns the value as a primitive type.
+            #[inline]
+            pub fn get(self) -> $int {
+                !self.0.get()
+            }
+        }
+
+        impl TiKey for $name {
+            const MAX: usize = $name::MAX as usize;
+
+            #[inline]
+            fn from_usize(index: usize) -> Self {
+                assert!(
+                    index <= <Self as TiKey>::MAX,
+                    "index {} is out of range for `{}`",
+                    index,
+                    stringify!($name),
+                );
+                // SAFETY: Checked `index <= MAX` above, and `MAX` is below the reserved maximum.
+                unsafe { Self::new_unchecked(index as $int) }
+            }
+
+            #[inline]
+            fn to_usize(self) -> usize {
+                self.get() as usize
+            }
+        }
+
+        impl Default for $name {
+            #[inline]
+            fn default() -> Self {
+                // SAFETY: 0 is never the reserved maximum value.
+                unsafe { Self::new_unchecked(0) }
+            }
+        }
+
+        impl From<$name> for $int {
+            #[inline]
+            fn from(value: $name) -> Self {
+                value.get()
+            }
+        }
+
+        impl Ord for $name {
+            #[inline]
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.get().cmp(&other.get())
+            }
+        }
+
+        impl PartialOrd for $name {
+            #[inline]
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Hash for $name {
+            #[inline]
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.get().hash(state)
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Debug::fmt(&self.get(), f)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(&self.get(), f)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.get().serialize(serializer)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = $int::deserialize(deserializer)?;
+                Self::new(value).ok_or_else(|| {
+                    D::Error::invalid_value(
+                        Unexpected::Unsigned(value as u64),
+                        &concat!("a value less than `", stringify!($int), "::MAX`"),
+                    )
+                })
+            }
+        }
+    };
+}
+
+nonmax!(
+    /// An `u8` value that is known not to equal `u8::MAX`.
+    NonMaxU8,
+    NonZeroU8,
+    u8
+);
+nonmax!(
+    /// An `u16` value that is known not to equal `u16::MAX`.
+    NonMaxU16,
+    NonZeroU16,
+    u16
+);
+nonmax!(
+    /// An `u32` value that is known not to equal `u32::MAX`.
+    NonMaxU32,
+    NonZeroU32,
+    u32
+);
+nonmax!(
+    /// An `usize` value that is known not to equal `usize::MAX`.
+    NonMaxUsize,
+    NonZeroUsize,
+    usize
+);
+
+#[cfg(test)]
+mod test {
+    use crate::{NonMaxU16, NonMaxU32, NonMaxU8, NonMaxUsize, TiKey, TiSlice};
+    use core::mem::size_of;
+
+    #[test]
+    fn niche() {
+        assert_eq!(size_of::<Option<NonMaxU8>>(), size_of::<u8>());
+        assert_eq!(size_of::<Option<NonMaxU16>>(), size_of::<u16>());
+        assert_eq!(size_of::<Option<NonMaxU32>>(), size_of::<u32>());
+        assert_eq!(size_of::<Option<NonMaxUsize>>(), size_of::<usize>());
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(NonMaxU8::new(0).map(NonMaxU8::get), Some(0));
+        assert_eq!(NonMaxU8::new(254).map(NonMaxU8::get), Some(254));
+        assert_eq!(NonMaxU8::new(255), None);
+        assert_eq!(NonMaxU32::default().get(), 0);
+        assert_eq!(u32::from(NonMaxU32::from_usize(7)), 7);
+        assert_eq!(<NonMaxU8 as TiKey>::MAX, 254);
+        assert_eq!(NonMaxU8::checked_from_usize(254), NonMaxU8::new(254));
+        assert_eq!(NonMaxU8::checked_from_usize(255), None);
+        assert_eq!(NonMaxUsize::checked_from_usize(usize::MAX), None);
+        assert!(NonMaxU16::from_usize(1) < NonMaxU16::from_usize(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "index 255 is out of range for `NonMaxU8`")]
+    fn reserved_value() {
+        let _ = NonMaxU8::from_usize(255);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn next_key_overflow() {
+        let array = [0_u32; 255];
+        let slice: &TiSlice<NonMaxU8, u32> = TiSlice::from_ref(&array);
+        assert_eq!(slice.last_key(), NonMaxU8::new(254));
+        let _ = slice.next_key();
+    }
+
+    #[cfg(any(feature = "alloc", feature = "std"))]
+    #[test]
+    fn vec_with_parent_links() {
+        use crate::TiVec;
+
+        ti_index! {
+            #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+            struct NodeId(NonMaxU32);
+        }
+
+        assert_eq!(size_of::<Option<NodeId>>(), size_of::<u32>());
+        let mut parents: TiVec<NodeId, Option<NodeId>> = TiVec::new();
+        let root = parents.push_and_get_key(None);
+        let child = parents.push_and_get_key(Some(root));
+        assert_eq!(parents[child], Some(root));
+        assert_eq!(root.to_usize(), 0);
+    }
+
+    #[cfg(any(feature = "alloc", feature = "std"))]
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn push_overflow() {
+        use crate::TiVec;
+
+        let mut vec: TiVec<NonMaxU8, u32> = TiVec::new();
+        for index in 0..255 {
+            assert_eq!(vec.push_and_get_key(0).get(), index);
+        }
+        let _ = vec.push_and_get_key(0);
+    }
+}
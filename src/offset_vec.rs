@@ -0,0 +1,705 @@
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+    iter,
+    marker::PhantomData,
+    ops, slice,
+};
+
+use alloc::vec::{self, Vec};
+
+use crate::key::{assert_offset_keys_in_range, key_from_len, offset_index};
+use crate::{TiKey, TiVec};
+
+/// An iterator over all keys of a [`TiOffsetVec`].
+///
+/// This struct is created by the [`TiOffsetVec::keys`] method.
+///
+/// [`TiOffsetVec`]: struct.TiOffsetVec.html
+/// [`TiOffsetVec::keys`]: struct.TiOffsetVec.html#method.keys
+pub type TiOffsetVecKeys<K> = iter::Map<ops::RangeInclusive<usize>, fn(usize) -> K>;
+
+/// A contiguous growable array type whose keys of the type `K`
+/// start at a runtime base instead of zero.
+///
+/// `TiOffsetVec<K, V>` stores a base key together with a [`std::vec::Vec<V>`].
+/// The first element has the base key,
+/// so all methods take and return absolute keys.
+///
+/// `TiOffsetVec<K, V>` does not dereference to [`TiSlice<K, V>`][`TiSlice`]
+/// because slice keys always start from zero.
+/// Use [`as_slice`] for the raw elements or convert it to a zero-based [`TiVec<K, V>`][`TiVec`]
+/// with [`From`] and [`Into`].
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::TiOffsetVec;
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct LineNo(usize);
+///
+/// let mut lines: TiOffsetVec<LineNo, &str> = TiOffsetVec::new(LineNo(1));
+/// assert_eq!(lines.push_and_get_key("fn main() {"), LineNo(1));
+/// assert_eq!(lines.push_and_get_key("}"), LineNo(2));
+/// assert_eq!(lines[LineNo(2)], "}");
+/// assert_eq!(lines.get(LineNo(0)), None);
+/// assert_eq!(lines.first_key(), Some(LineNo(1)));
+/// ```
+///
+/// [`as_slice`]: #method.as_slice
+/// [`TiSlice`]: struct.TiSlice.html
+/// [`TiVec`]: struct.TiVec.html
+/// [`std::vec::Vec<V>`]: https://doc.rust-lang.org/std/vec/struct.Vec.html
+/// [`From`]: https://doc.rust-lang.org/std/convert/trait.From.html
+/// [`Into`]: https://doc.rust-lang.org/std/convert/trait.Into.html
+pub struct TiOffsetVec<K, V> {
+    raw: Vec<V>,
+    base: usize,
+
+    /// Ties the container to the key type `K`.
+    _marker: PhantomData<fn(K) -> K>,
+}
+
+impl<K, V> TiOffsetVec<K, V> {
+    /// Constructs a new, empty `TiOffsetVec<K, V>` whose first element gets the `base` key.
+    #[inline]
+    pub fn new(base: K) -> Self
+    where
+        K: TiKey,
+    {
+        Self::from_vec(base, Vec::new())
+    }
+
+    /// Constructs a new, empty `TiOffsetVec<K, V>` with the specified capacity
+    /// whose first element gets the `base` key.
+    #[inline]
+    pub fn with_capacity(base: K, capacity: usize) -> Self
+    where
+        K: TiKey,
+    {
+        Self::from_vec(base, Vec::with_capacity(capacity))
+    }
+
+    /// Constructs a `TiOffsetVec<K, V>` from the elements of `raw`
+    /// with the first element at the `base` key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiOffsetVec;
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let window: TiOffsetVec<Id, u32> = TiOffsetVec::from_vec(Id(10), vec![1, 2, 4]);
+    /// assert_eq!(window[Id(12)], 4);
+    /// assert_eq!(window.last_key(), Some(Id(12)));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key of the last element can not be represented by the key type.
+    #[inline]
+    pub fn from_vec(base: K, raw: Vec<V>) -> Self
+    where
+        K: TiKey,
+    {
+        let base = base.to_usize();
+        assert_offset_keys_in_range::<K>(base, raw.len());
+        Self {
+            raw,
+            base,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the key of the first element.
+    #[inline]
+    pub fn base(&self) -> K
+    where
+        K: TiKey,
+    {
+        K::from_usize(self.base)
+    }
+
+    /// Changes the key of the first element to `base`, shifting the keys of all elements.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiOffsetVec;
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut lines: TiOffsetVec<Id, char> = TiOffsetVec::from_vec(Id(1), vec!['a', 'b']);
+    /// lines.rebase(Id(0));
+    /// assert_eq!(lines[Id(1)], 'b');
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key of the last element can not be represented by the key type.
+    #[inline]
+    pub fn rebase(&mut self, base: K)
+    where
+        K: TiKey,
+    {
+        let base = base.to_usize();
+        assert_offset_keys_in_range::<K>(base, self.raw.len());
+        self.base = base;
+    }
+
+    /// Returns the elements as a raw slice.
+    #[inline]
+    pub fn as_slice(&self) -> &[V] {
+        &self.raw
+    }
+
+    /// Returns the elements as a mutable raw slice.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [V] {
+        &mut self.raw
+    }
+
+    /// Returns the number of elements the vector can hold without
+    /// reallocating.
+    ///
+    /// See [`Vec::capacity`] for more details.
+    ///
+    /// [`Vec::capacity`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.capacity
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.raw.capacity()
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    ///
+    /// See [`Vec::reserve`] for more details.
+    ///
+    /// [`Vec::reserve`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.reserve
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.raw.reserve(additional)
+    }
+
+    /// Returns the number of elements in the vector.
+    ///
+    /// See [`Vec::len`] for more details.
+    ///
+    /// [`Vec::len`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.len
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.raw.len()
+    }
+
+    /// Returns `true` if the vector contains no elements.
+    ///
+    /// See [`Vec::is_empty`] for more details.
+    ///
+    /// [`Vec::is_empty`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.is_empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    #[inline]
+    fn position(&self, index: usize) -> Option<usize> {
+        index
+            .checked_sub(self.base)
+            .filter(|&position| position < self.raw.len())
+    }
+
+    /// Returns the key of the first element, or [`None`] if the vector is empty.
+    #[inline]
+    pub fn first_key(&self) -> Option<K>
+    where
+        K: TiKey,
+    {
+        if self.raw.is_empty() {
+            None
+        } else {
+            Some(K::from_usize(self.base))
+        }
+    }
+
+    /// Returns the key of the last element, or [`None`] if the vector is empty.
+    #[inline]
+    pub fn last_key(&self) -> Option<K>
+    where
+        K: TiKey,
+    {
+        if self.raw.is_empty() {
+            None
+        } else {
+            Some(key_from_len(offset_index::<K>(
+                self.base,
+                self.raw.len() - 1,
+            )))
+        }
+    }
+
+    /// Returns the key that the next pushed element receives.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key can not be represented by the key type.
+    #[inline]
+    pub fn next_key(&self) -> K
+    where
+        K: TiKey,
+    {
+        key_from_len(offset_index::<K>(self.base, self.raw.len()))
+    }
+
+    /// Returns `true` if the vector contains an element with the given key.
+    #[inline]
+    pub fn contains_key(&self, key: K) -> bool
+    where
+        K: TiKey,
+    {
+        self.position(key.to_usize()).is_some()
+    }
+
+    /// Returns a reference to the element with the given key,
+    /// or [`None`] if the key is out of bounds.
+    #[inline]
+    pub fn get(&self, key: K) -> Option<&V>
+    where
+        K: TiKey,
+    {
+        self.position(key.to_usize())
+            .map(|position| &self.raw[position])
+    }
+
+    /// Returns a mutable reference to the element with the given key,
+    /// or [`None`] if the key is out of bounds.
+    #[inline]
+    pub fn get_mut(&mut self, key: K) -> Option<&mut V>
+    where
+        K: TiKey,
+    {
+        self.position(key.to_usize())
+            .map(move |position| &mut self.raw[position])
+    }
+
+    /// Returns an iterator over all keys.
+    #[inline]
+    pub fn keys(&self) -> TiOffsetVecKeys<K>
+    where
+        K: TiKey,
+    {
+        assert_offset_keys_in_range::<K>(self.base, self.raw.len());
+        // An inclusive range also covers a last key at `usize::MAX`.
+        let keys = match self.raw.len().checked_sub(1) {
+            Some(offset) => self.base..=self.base + offset,
+            None => ops::RangeInclusive::new(1, 0),
+        };
+        keys.map(K::from_usize)
+    }
+
+    /// Returns an iterator over the elements.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, V> {
+        self.raw.iter()
+    }
+
+    /// Returns an iterator that allows modifying each element.
+    #[inline]
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, V> {
+        self.raw.iter_mut()
+    }
+
+    /// Returns an iterator over all key-value pairs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiOffsetVec;
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let vec: TiOffsetVec<Id, char> = TiOffsetVec::from_vec(Id(1), vec!['a', 'b']);
+    /// let mut iterator = vec.iter_enumerated();
+    /// assert_eq!(iterator.next(), Some((Id(1), &'a')));
+    /// assert_eq!(iterator.next(), Some((Id(2), &'b')));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    #[inline]
+    pub fn iter_enumerated(&self) -> iter::Zip<TiOffsetVecKeys<K>, slice::Iter<'_, V>>
+    where
+        K: TiKey,
+    {
+        self.keys().zip(self.raw.iter())
+    }
+
+    /// Returns an iterator over all key-value pairs,
+    /// with mutable references to the values.
+    #[inline]
+    pub fn iter_mut_enumerated(&mut self) -> iter::Zip<TiOffsetVecKeys<K>, slice::IterMut<'_, V>>
+    where
+        K: TiKey,
+    {
+        self.keys().zip(self.raw.iter_mut())
+    }
+
+    /// Appends an element to the back of the vector.
+    ///
+    /// See [`Vec::push`] for more details.
+    ///
+    /// [`Vec::push`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.push
+    #[inline]
+    pub fn push(&mut self, value: V) {
+        self.raw.push(value)
+    }
+
+    /// Appends an element to the back of the vector and returns its key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key can not be represented by the key type.
+    #[inline]
+    pub fn push_and_get_key(&mut self, value: V) -> K
+    where
+        K: TiKey,
+    {
+        let key = self.next_key();
+        self.raw.push(value);
+        key
+    }
+
+    /// Removes the last element from the vector and returns it, or [`None`] if it
+    /// is empty.
+    ///
+    /// See [`Vec::pop`] for more details.
+    ///
+    /// [`Vec::pop`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.pop
+    #[inline]
+    pub fn pop(&mut self) -> Option<V> {
+        self.raw.pop()
+    }
+
+    /// Removes the last element from the vector and returns it with its key,
+    /// or [`None`] if the vector is empty.
+    #[inline]
+    pub fn pop_key_value(&mut self) -> Option<(K, V)>
+    where
+        K: TiKey,
+    {
+        self.raw.pop().map(|value| {
+            (
+                key_from_len(offset_index::<K>(self.base, self.raw.len())),
+                value,
+            )
+        })
+    }
+
+    /// Inserts an element at the `key` position,
+    /// shifting all elements after it to the right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is less than the base key or greater than the next key.
+    pub fn insert(&mut self, key: K, value: V)
+    where
+        K: TiKey,
+    {
+        let index = key.to_usize();
+        match index.checked_sub(self.base) {
+            Some(position) if position <= self.raw.len() => self.raw.insert(position, value),
+            _ => out_of_bounds(index, self.base, self.raw.len()),
+        }
+    }
+
+    /// Removes and returns the element with the given key,
+    /// shifting all elements after it to the left.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is out of bounds.
+    pub fn remove(&mut self, key: K) -> V
+    where
+        K: TiKey,
+    {
+        let index = key.to_usize();
+        match self.position(index) {
+            Some(position) => self.raw.remove(position),
+            None => out_of_bounds(index, self.base, self.raw.len()),
+        }
+    }
+
+    /// Shortens the vector, keeping the first `len` elements and dropping
+    /// the rest.
+    ///
+    /// See [`Vec::truncate`] for more details.
+    ///
+    /// [`Vec::truncate`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.truncate
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.raw.truncate(len)
+    }
+
+    /// Clears the vector, removing all values.
+    ///
+    /// The base key is kept.
+    ///
+    /// See [`Vec::clear`] for more details.
+    ///
+    /// [`Vec::clear`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.clear
+    #[inline]
+    pub fn clear(&mut self) {
+        self.raw.clear()
+    }
+
+    /// Converts the vector into iterator over all key-value pairs.
+    #[inline]
+    pub fn into_iter_enumerated(self) -> iter::Zip<TiOffsetVecKeys<K>, vec::IntoIter<V>>
+    where
+        K: TiKey,
+    {
+        self.keys().zip(self.raw)
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn out_of_bounds(index: usize, base: usize, len: usize) -> ! {
+    match base.checked_add(len) {
+        Some(end) => panic!(
+            "key index {} is out of bounds for the offset vector with keys {}..{}",
+            index, base, end
+        ),
+        None => panic!(
+            "key index {} is out of bounds for the offset vector with keys {}..{} + {}",
+            index, base, base, len
+        ),
+    }
+}
+
+impl<K, V> fmt::Debug for TiOffsetVec<K, V>
+where
+    K: fmt::Debug + TiKey,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter_enumerated()).finish()
+    }
+}
+
+impl<K, V> ops::Index<K> for TiOffsetVec<K, V>
+where
+    K: TiKey,
+{
+    type Output = V;
+
+    #[inline]
+    fn index(&self, key: K) -> &V {
+        let index = key.to_usize();
+        match self.position(index) {
+            Some(position) => &self.raw[position],
+            None => out_of_bounds(index, self.base, self.raw.len()),
+        }
+    }
+}
+
+impl<K, V> ops::IndexMut<K> for TiOffsetVec<K, V>
+where
+    K: TiKey,
+{
+    #[inline]
+    fn index_mut(&mut self, key: K) -> &mut V {
+        let index = key.to_usize();
+        match self.position(index) {
+            Some(position) => &mut self.raw[position],
+            None => out_of_bounds(index, self.base, self.raw.len()),
+        }
+    }
+}
+
+impl<K, V> Extend<V> for TiOffsetVec<K, V> {
+    fn extend<I: IntoIterator<Item = V>>(&mut self, iter: I) {
+        self.raw.extend(iter)
+    }
+}
+
+impl<'a, K, V: 'a + Copy> Extend<&'a V> for TiOffsetVec<K, V> {
+    fn extend<I: IntoIterator<Item = &'a V>>(&mut self, iter: I) {
+        self.raw.extend(iter)
+    }
+}
+
+impl<K, V> PartialEq for TiOffsetVec<K, V>
+where
+    V: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.base == other.base && self.raw == other.raw
+    }
+}
+
+impl<K, V> Eq for TiOffsetVec<K, V> where V: Eq {}
+
+impl<K, V> Hash for TiOffsetVec<K, V>
+where
+    V: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.base.hash(state);
+        self.raw.hash(state);
+    }
+}
+
+impl<K, V> Clone for TiOffsetVec<K, V>
+where
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            base: self.base,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> From<TiVec<K, V>> for TiOffsetVec<K, V> {
+    /// Converts a `TiVec` into a `TiOffsetVec` with zero base key.
+    fn from(vec: TiVec<K, V>) -> Self {
+        Self {
+            raw: vec.raw,
+            base: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> From<TiOffsetVec<K, V>> for TiVec<K, V> {
+    /// Converts a `TiOffsetVec` into a zero-based `TiVec`,
+    /// shifting the key of every element by the base key.
+    fn from(vec: TiOffsetVec<K, V>) -> Self {
+        vec.raw.into()
+    }
+}
+
+impl<K, V> IntoIterator for TiOffsetVec<K, V> {
+    type Item = V;
+    type IntoIter = vec::IntoIter<V>;
+
+    #[inline]
+    fn into_iter(self) -> vec::IntoIter<V> {
+        self.raw.into_iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a TiOffsetVec<K, V> {
+    type Item = &'a V;
+    type IntoIter = slice::Iter<'a, V>;
+
+    #[inline]
+    fn into_iter(self) -> slice::Iter<'a, V> {
+        self.raw.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut TiOffsetVec<K, V> {
+    type Item = &'a mut V;
+    type IntoIter = slice::IterMut<'a, V>;
+
+    #[inline]
+    fn into_iter(self) -> slice::IterMut<'a, V> {
+        self.raw.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::Id;
+    use crate::{TiOffsetVec, TiVec};
+    use alloc::{format, vec, vec::Vec};
+
+    #[test]
+    fn absolute_keys() {
+        let mut vec: TiOffsetVec<Id, u32> = TiOffsetVec::new(Id::from(5));
+        assert_eq!(vec.first_key(), None);
+        assert_eq!(vec.next_key(), Id::from(5));
+        assert_eq!(vec.push_and_get_key(1), Id::from(5));
+        vec.push(2);
+        vec.insert(Id::from(7), 4);
+        vec.insert(Id::from(5), 0);
+        assert_eq!(vec.as_slice(), [0, 1, 2, 4]);
+        assert_eq!(vec.last_key(), Some(Id::from(8)));
+        assert_eq!(vec.get(Id::from(4)), None);
+        assert_eq!(vec.get(Id::from(9)), None);
+        assert!(vec.contains_key(Id::from(6)));
+        assert_eq!(vec.remove(Id::from(5)), 0);
+        vec[Id::from(7)] = 3;
+        assert_eq!(format!("{:?}", vec), "{Id(5): 1, Id(6): 2, Id(7): 3}");
+        assert_eq!(vec.pop_key_value(), Some((Id::from(7), 3)));
+        assert_eq!(vec.keys().collect::<Vec<_>>(), [Id::from(5), Id::from(6)]);
+    }
+
+    #[test]
+    fn rebase_and_convert() {
+        let mut vec: TiOffsetVec<Id, u32> = TiOffsetVec::from_vec(Id::from(1), vec![1, 2]);
+        for (key, value) in vec.iter_mut_enumerated() {
+            *value += usize::from(key) as u32;
+        }
+        vec.rebase(Id::from(10));
+        assert_eq!(vec.base(), Id::from(10));
+        assert_eq!(
+            vec.clone().into_iter_enumerated().collect::<Vec<_>>(),
+            [(Id::from(10), 2), (Id::from(11), 4)]
+        );
+        let zero_based: TiVec<Id, u32> = vec.into();
+        assert_eq!(zero_based[Id::from(1)], 4);
+        let vec: TiOffsetVec<Id, u32> = zero_based.into();
+        assert_eq!(vec.base(), Id::from(0));
+    }
+
+    #[test]
+    fn keys_near_usize_max() {
+        let vec: TiOffsetVec<Id, u32> = TiOffsetVec::from_vec(Id::from(usize::MAX - 1), vec![1, 2]);
+        assert_eq!(vec.last_key(), Some(Id::from(usize::MAX)));
+        assert_eq!(vec[Id::from(usize::MAX)], 2);
+        assert_eq!(
+            vec.iter_enumerated().collect::<Vec<_>>(),
+            [(Id::from(usize::MAX - 1), &1), (Id::from(usize::MAX), &2)]
+        );
+        assert_eq!(vec.keys().next_back(), Some(Id::from(usize::MAX)));
+        assert_eq!(
+            format!("{:?}", vec),
+            format!("{{Id({}): 1, Id({}): 2}}", usize::MAX - 1, usize::MAX)
+        );
+        let empty: TiOffsetVec<Id, u32> = TiOffsetVec::new(Id::from(usize::MAX));
+        assert_eq!(empty.keys().next(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "+ 2 is out of range for the key type")]
+    fn from_vec_overflow() {
+        let _: TiOffsetVec<Id, u32> =
+            TiOffsetVec::from_vec(Id::from(usize::MAX - 1), vec![1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "+ 1 is out of range for the key type")]
+    fn rebase_overflow() {
+        let mut vec: TiOffsetVec<Id, u32> = TiOffsetVec::from_vec(Id::from(0), vec![1, 2]);
+        vec.rebase(Id::from(usize::MAX));
+    }
+
+    #[test]
+    #[should_panic(expected = "+ 2 is out of range for the key type")]
+    fn next_key_overflow() {
+        let vec: TiOffsetVec<Id, u32> = TiOffsetVec::from_vec(Id::from(usize::MAX - 1), vec![1, 2]);
+        let _ = vec.next_key();
+    }
+
+    #[test]
+    #[should_panic(expected = "key index 0 is out of bounds for the offset vector with keys 1..3")]
+    fn index_before_base() {
+        let vec: TiOffsetVec<Id, u32> = TiOffsetVec::from_vec(Id::from(1), vec![1, 2]);
+        let _ = vec[Id::from(0)];
+    }
+}
@@ -0,0 +1,518 @@
+use core::{
+    fmt,
+    iter::{self, FusedIterator},
+    marker::PhantomData,
+    slice,
+};
+
+use alloc::vec::Vec;
+
+use crate::key::assert_keys_in_range;
+use crate::{TiKey, TiSlice, TiVec};
+
+/// A partition of keys of the type `K` into blocks identified by keys of the type `B`
+/// that supports efficient refinement.
+///
+/// Keys are stored in a single array where members of every block form a contiguous range,
+/// so block members are available as slices.
+/// Refining by a set of keys splits every block containing some but not all of them
+/// in time proportional to the size of the set.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::{TiPartition, TiSlice};
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct StateId(usize);
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct BlockId(usize);
+///
+/// let accepting = [BlockId(0), BlockId(1), BlockId(0), BlockId(1)];
+/// let mut partition = TiPartition::from_labels(TiSlice::<StateId, _>::from_ref(&accepting));
+/// assert_eq!(partition.num_blocks(), 2);
+///
+/// let splits = partition.refine(&[StateId(3), StateId(1)]);
+/// assert_eq!(splits, []);
+///
+/// let splits = partition.refine(&[StateId(3), StateId(0)]);
+/// assert_eq!(splits, [(BlockId(1), BlockId(2)), (BlockId(0), BlockId(3))]);
+/// assert_eq!(partition.block_of(StateId(3)), BlockId(2));
+/// assert_eq!(partition.members(BlockId(1)), [StateId(1)]);
+/// assert_eq!(partition.members(BlockId(3)), [StateId(0)]);
+/// ```
+pub struct TiPartition<K, B> {
+    elements: Vec<K>,
+    positions: TiVec<K, usize>,
+    block_ids: TiVec<K, usize>,
+    blocks: TiVec<B, Block>,
+    touched: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+struct Block {
+    start: usize,
+    /// The end of the range of marked members at the start of the block.
+    marked_end: usize,
+    end: usize,
+}
+
+impl Block {
+    #[inline]
+    fn new(start: usize, end: usize) -> Self {
+        Self {
+            start,
+            marked_end: start,
+            end,
+        }
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn out_of_bounds(index: usize, len: usize) -> ! {
+    panic!(
+        "key index {} is out of bounds for the partition of {} keys",
+        index, len
+    )
+}
+
+impl<K, B> TiPartition<K, B> {
+    /// Constructs a new partition of `len` keys in a single block.
+    ///
+    /// The partition of zero keys has no blocks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some keys below `len` can not be represented by the key type.
+    pub fn new(len: usize) -> Self
+    where
+        K: TiKey,
+    {
+        assert_keys_in_range::<K>(len);
+        let mut blocks = TiVec::new();
+        if len != 0 {
+            blocks.raw.push(Block::new(0, len));
+        }
+        Self {
+            elements: (0..len).map(K::from_usize).collect(),
+            positions: (0..len).collect(),
+            block_ids: alloc::vec![0; len].into(),
+            blocks,
+            touched: Vec::new(),
+        }
+    }
+
+    /// Constructs a new partition with every key in the block of its label.
+    ///
+    /// The partition has a block for every label up to the maximum one,
+    /// so labels without keys get empty blocks.
+    pub fn from_labels(labels: &TiSlice<K, B>) -> Self
+    where
+        K: TiKey,
+        B: TiKey + Copy,
+    {
+        let block_ids: TiVec<K, usize> = labels.iter().map(|&label| label.to_usize()).collect();
+        let num_blocks = block_ids.iter().max().map_or(0, |&max| max + 1);
+        let mut starts = alloc::vec![0; num_blocks + 1];
+        for &block in &block_ids {
+            starts[block + 1] += 1;
+        }
+        for block in 0..num_blocks {
+            starts[block + 1] += starts[block];
+        }
+        let blocks = (0..num_blocks)
+            .map(|block| Block::new(starts[block], starts[block + 1]))
+            .collect();
+        let mut order = alloc::vec![0; labels.len()];
+        let mut positions = alloc::vec![0; labels.len()];
+        for (index, &block) in block_ids.iter().enumerate() {
+            positions[index] = starts[block];
+            order[starts[block]] = index;
+            starts[block] += 1;
+        }
+        Self {
+            elements: order.into_iter().map(K::from_usize).collect(),
+            positions: positions.into(),
+            block_ids,
+            blocks,
+            touched: Vec::new(),
+        }
+    }
+
+    /// Returns the number of keys in the partition.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns `true` if the partition contains no keys.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns the number of blocks in the partition.
+    #[inline]
+    pub fn num_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Returns the block containing the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is out of bounds.
+    #[inline]
+    pub fn block_of(&self, key: K) -> B
+    where
+        K: TiKey,
+        B: TiKey,
+    {
+        B::from_usize(self.block_ids[key])
+    }
+
+    /// Returns members of the block as a contiguous slice.
+    ///
+    /// The slice is the range of [`as_slice`] occupied by the block,
+    /// so positions within it change when the block is refined.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the block is out of bounds.
+    ///
+    /// [`as_slice`]: #method.as_slice
+    #[inline]
+    pub fn members(&self, block: B) -> &[K]
+    where
+        B: TiKey,
+    {
+        let block = &self.blocks[block];
+        &self.elements[block.start..block.end]
+    }
+
+    /// Returns all keys ordered so that members of every block are contiguous.
+    #[inline]
+    pub fn as_slice(&self) -> &[K] {
+        &self.elements
+    }
+
+    /// Splits every block containing some but not all keys of the splitter.
+    ///
+    /// Keys of the splitter move to a new block,
+    /// while the other members keep the old block.
+    /// Returns the pairs of split and new blocks in the order
+    /// in which the splitter first touches the split blocks.
+    /// Duplicate keys in the splitter are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any key of the splitter is out of bounds,
+    /// or if a new block can not be represented by the block key type.
+    pub fn refine(&mut self, splitter: &[K]) -> Vec<(B, B)>
+    where
+        K: TiKey + Copy,
+        B: TiKey,
+    {
+        // Splitter keys are checked before any key is marked,
+        // so a panic leaves the partition unrefined but consistent.
+        let len = self.positions.len();
+        if let Some(index) = splitter
+            .iter()
+            .map(|&key| key.to_usize())
+            .find(|&index| index >= len)
+        {
+            out_of_bounds(index, len);
+        }
+
+        for &key in splitter {
+            let position = self.positions[key];
+            let block_id = self.block_ids[key];
+            let block = &mut self.blocks.raw[block_id];
+            if position < block.marked_end {
+                continue;
+            }
+            if block.marked_end == block.start {
+                self.touched.push(block_id);
+            }
+            let marked_end = block.marked_end;
+            block.marked_end += 1;
+            let moved = self.elements[marked_end];
+            self.elements.swap(position, marked_end);
+            self.positions[moved] = position;
+            self.positions[key] = marked_end;
+        }
+
+        // New block keys are checked before any block changes,
+        // so a panic also leaves the partition unrefined.
+        let num_splits = self
+            .touched
+            .iter()
+            .filter(|&&block_id| {
+                let block = &self.blocks.raw[block_id];
+                block.marked_end != block.end
+            })
+            .count();
+        let num_blocks = self.blocks.len() + num_splits;
+        if num_blocks > 0 && B::checked_from_usize(num_blocks - 1).is_none() {
+            for block_id in self.touched.drain(..) {
+                let block = &mut self.blocks.raw[block_id];
+                block.marked_end = block.start;
+            }
+        }
+        assert_keys_in_range::<B>(num_blocks);
+
+        let mut splits = Vec::with_capacity(num_splits);
+        for block_id in self.touched.drain(..) {
+            let block = &mut self.blocks.raw[block_id];
+            let (start, marked_end) = (block.start, block.marked_end);
+            block.marked_end = block.start;
+            if marked_end == block.end {
+                continue;
+            }
+            block.start = marked_end;
+            block.marked_end = marked_end;
+            let new_id = self.blocks.len();
+            self.blocks.raw.push(Block::new(start, marked_end));
+            for &key in &self.elements[start..marked_end] {
+                self.block_ids[key] = new_id;
+            }
+            splits.push((B::from_usize(block_id), B::from_usize(new_id)));
+        }
+        splits
+    }
+
+    /// Returns an iterator over blocks and their members.
+    #[inline]
+    pub fn iter_enumerated(&self) -> TiPartitionIter<'_, K, B> {
+        TiPartitionIter {
+            elements: &self.elements,
+            blocks: self.blocks.raw.iter().enumerate(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K, B> fmt::Debug for TiPartition<K, B>
+where
+    K: fmt::Debug,
+    B: fmt::Debug + TiKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter_enumerated()).finish()
+    }
+}
+
+impl<K, B> Clone for TiPartition<K, B>
+where
+    K: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            elements: self.elements.clone(),
+            positions: self.positions.clone(),
+            block_ids: self.block_ids.clone(),
+            blocks: self.blocks.clone(),
+            touched: Vec::new(),
+        }
+    }
+}
+
+impl<K, B> Default for TiPartition<K, B> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            elements: Vec::new(),
+            positions: TiVec::new(),
+            block_ids: TiVec::new(),
+            blocks: TiVec::new(),
+            touched: Vec::new(),
+        }
+    }
+}
+
+impl<'a, K, B> IntoIterator for &'a TiPartition<K, B>
+where
+    B: TiKey,
+{
+    type Item = (B, &'a [K]);
+    type IntoIter = TiPartitionIter<'a, K, B>;
+
+    #[inline]
+    fn into_iter(self) -> TiPartitionIter<'a, K, B> {
+        self.iter_enumerated()
+    }
+}
+
+/// An iterator over blocks of a [`TiPartition`] and their members.
+///
+/// This struct is created by the [`TiPartition::iter_enumerated`] method.
+///
+/// [`TiPartition`]: struct.TiPartition.html
+/// [`TiPartition::iter_enumerated`]: struct.TiPartition.html#method.iter_enumerated
+pub struct TiPartitionIter<'a, K, B> {
+    elements: &'a [K],
+    blocks: iter::Enumerate<slice::Iter<'a, Block>>,
+    _marker: PhantomData<fn(B) -> B>,
+}
+
+impl<K, B> TiPartitionIter<'_, K, B> {
+    #[inline]
+    fn item<'a>(elements: &'a [K], (index, block): (usize, &Block)) -> (B, &'a [K])
+    where
+        B: TiKey,
+    {
+        (B::from_usize(index), &elements[block.start..block.end])
+    }
+}
+
+impl<K, B> fmt::Debug for TiPartitionIter<'_, K, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TiPartitionIter")
+            .field("len", &self.blocks.len())
+            .finish()
+    }
+}
+
+impl<'a, K, B: TiKey> Iterator for TiPartitionIter<'a, K, B> {
+    type Item = (B, &'a [K]);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let elements = self.elements;
+        self.blocks.next().map(|item| Self::item(elements, item))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.blocks.size_hint()
+    }
+}
+
+impl<K, B: TiKey> DoubleEndedIterator for TiPartitionIter<'_, K, B> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let elements = self.elements;
+        self.blocks
+            .next_back()
+            .map(|item| Self::item(elements, item))
+    }
+}
+
+impl<K, B: TiKey> ExactSizeIterator for TiPartitionIter<'_, K, B> {}
+
+impl<K, B: TiKey> FusedIterator for TiPartitionIter<'_, K, B> {}
+
+#[cfg(test)]
+mod test {
+    use crate::test::{ids, Id, SmallId};
+    use crate::{TiPartition, TiSlice};
+    use alloc::{format, vec::Vec};
+
+    fn sorted(members: &[Id]) -> Vec<usize> {
+        let mut members: Vec<usize> = members.iter().map(|&key| key.into()).collect();
+        members.sort_unstable();
+        members
+    }
+
+    #[test]
+    fn refine_single_block() {
+        let mut partition: TiPartition<Id, Id> = TiPartition::new(6);
+        assert_eq!(partition.num_blocks(), 1);
+        assert_eq!(partition.refine(&ids(&[0, 1, 2, 3, 4, 5])), []);
+        assert_eq!(
+            partition.refine(&ids(&[4, 1, 4])),
+            [(Id::from(0), Id::from(1))]
+        );
+        assert_eq!(sorted(partition.members(Id::from(0))), [0, 2, 3, 5]);
+        assert_eq!(sorted(partition.members(Id::from(1))), [1, 4]);
+        assert_eq!(
+            partition.refine(&ids(&[2, 1, 5])),
+            [(Id::from(0), Id::from(2)), (Id::from(1), Id::from(3))]
+        );
+        assert_eq!(sorted(partition.members(Id::from(0))), [0, 3]);
+        assert_eq!(sorted(partition.members(Id::from(2))), [2, 5]);
+        assert_eq!(sorted(partition.members(Id::from(3))), [1]);
+        for (block, members) in &partition {
+            for &key in members {
+                assert_eq!(partition.block_of(key), block);
+            }
+        }
+        assert_eq!(
+            partition
+                .iter_enumerated()
+                .map(|(_, members)| members.len())
+                .sum::<usize>(),
+            partition.len()
+        );
+        assert_eq!(format!("{:?}", TiPartition::<Id, Id>::new(0)), "{}");
+    }
+
+    #[test]
+    fn from_labels() {
+        let labels = [2, 0, 2, 2, 0].map(Id::from);
+        let mut partition = TiPartition::from_labels(TiSlice::<Id, Id>::from_ref(&labels));
+        assert_eq!(partition.num_blocks(), 3);
+        assert_eq!(partition.members(Id::from(0)), [Id::from(1), Id::from(4)]);
+        assert!(partition.members(Id::from(1)).is_empty());
+        assert_eq!(
+            partition.members(Id::from(2)),
+            [Id::from(0), Id::from(2), Id::from(3)]
+        );
+        assert_eq!(
+            partition.refine(&[Id::from(3), Id::from(4)]),
+            [(Id::from(2), Id::from(3)), (Id::from(0), Id::from(4))]
+        );
+        assert_eq!(partition.block_of(Id::from(3)), Id::from(3));
+        assert_eq!(partition.block_of(Id::from(0)), Id::from(2));
+        assert_eq!(partition.as_slice().len(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn refine_block_key_overflow() {
+        let mut partition: TiPartition<Id, SmallId> = TiPartition::new(3);
+        assert_eq!(partition.refine(&ids(&[0])), [(SmallId(0), SmallId(1))]);
+        let _ = partition.refine(&ids(&[1]));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn refine_block_key_overflow_keeps_partition() {
+        use alloc::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut partition: TiPartition<Id, SmallId> = TiPartition::new(3);
+        let _ = partition.refine(&ids(&[0]));
+        let result = catch_unwind(AssertUnwindSafe(|| partition.refine(&ids(&[2]))));
+        assert!(result.is_err());
+        assert_eq!(partition.num_blocks(), 2);
+        assert_eq!(sorted(partition.members(SmallId(0))), [1, 2]);
+        assert_eq!(partition.block_of(Id::from(2)), SmallId(0));
+        assert_eq!(partition.refine(&ids(&[1, 2])), []);
+        assert_eq!(partition.refine(&ids(&[0])), []);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn refine_out_of_bounds_keeps_partition() {
+        use alloc::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut partition: TiPartition<Id, Id> = TiPartition::new(6);
+        let result = catch_unwind(AssertUnwindSafe(|| partition.refine(&ids(&[0, 100]))));
+        assert!(result.is_err());
+        assert_eq!(partition.num_blocks(), 1);
+        assert_eq!(partition.refine(&ids(&[1])), [(Id::from(0), Id::from(1))]);
+        assert_eq!(partition.members(Id::from(1)), ids(&[1]));
+        assert_eq!(sorted(partition.members(Id::from(0))), [0, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "key index 100 is out of bounds for the partition of 6 keys")]
+    fn refine_out_of_bounds() {
+        let mut partition: TiPartition<Id, Id> = TiPartition::new(6);
+        let _ = partition.refine(&ids(&[0, 100]));
+    }
+}
@@ -0,0 +1,463 @@
+use core::{
+    fmt,
+    iter::FromIterator,
+    marker::PhantomData,
+    ops::{Add, Sub},
+};
+
+use alloc::vec::Vec;
+
+use crate::key::{assert_keys_in_range, key_from_len};
+use crate::range::range_to_bounds;
+use crate::{TiKey, TiRangeBounds, TiSlice, TiVec};
+
+/// A Fenwick tree of values of the type `V` indexed by keys of the type `K`
+/// that maintains prefix sums under point updates.
+///
+/// Updates, prefix and range sums, and searches by a cumulative target
+/// take logarithmic time.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::{TiPrefixSums, TiSlice};
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct LineId(usize);
+///
+/// let line_lengths: &TiSlice<LineId, usize> = TiSlice::from_ref(&[10, 4, 7, 12]);
+/// let mut offsets = TiPrefixSums::from_slice(line_lengths);
+/// assert_eq!(offsets.prefix(LineId(2)), 14);
+/// assert_eq!(offsets.range_sum(LineId(1)..=LineId(2)), 11);
+///
+/// offsets.add(LineId(1), 3);
+/// assert_eq!(offsets.prefix(LineId(3)), 24);
+/// assert_eq!(offsets.total(), 36);
+///
+/// // The line containing byte offset 20 is the first one ending after it.
+/// assert_eq!(offsets.lower_bound(21), LineId(2));
+/// ```
+pub struct TiPrefixSums<K, V> {
+    /// Fenwick tree nodes, where the node at `index` stores the sum of values
+    /// with indices in `index + 1 - lowbit(index + 1)..=index`.
+    tree: Vec<V>,
+
+    /// Ties the container to the key type `K`.
+    _marker: PhantomData<fn(K) -> K>,
+}
+
+#[inline]
+fn lowbit(index: usize) -> usize {
+    index & index.wrapping_neg()
+}
+
+#[cold]
+#[inline(never)]
+fn index_out_of_bounds(index: usize, len: usize) -> ! {
+    panic!(
+        "key index {} is out of bounds for the prefix sums of length {}",
+        index, len
+    )
+}
+
+impl<K, V> TiPrefixSums<K, V> {
+    /// Constructs new prefix sums of `len` default values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some keys below `len` can not be represented by the key type.
+    #[inline]
+    pub fn new(len: usize) -> Self
+    where
+        K: TiKey,
+        V: Default + Copy,
+    {
+        assert_keys_in_range::<K>(len);
+        Self {
+            tree: alloc::vec![V::default(); len],
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs prefix sums of the values in linear time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some keys of the values can not be represented by the key type.
+    pub fn from_slice(values: &TiSlice<K, V>) -> Self
+    where
+        K: TiKey,
+        V: Add<Output = V> + Copy,
+    {
+        assert_keys_in_range::<K>(values.len());
+        let mut tree = values.raw.to_vec();
+        for node in 1..=tree.len() {
+            let parent = node + lowbit(node);
+            if parent <= tree.len() {
+                tree[parent - 1] = tree[parent - 1] + tree[node - 1];
+            }
+        }
+        Self {
+            tree,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns `true` if there are no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Appends the value and returns its key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new key can not be represented by the key type.
+    pub fn push(&mut self, value: V) -> K
+    where
+        K: TiKey,
+        V: Add<Output = V> + Sub<Output = V> + Default + Copy,
+    {
+        let index = self.tree.len();
+        let key = key_from_len(index);
+        let node = index + 1;
+        let covered = self.prefix_at(index) - self.prefix_at(node - lowbit(node));
+        self.tree.push(covered + value);
+        key
+    }
+
+    /// Adds the delta to the value of the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is out of bounds.
+    pub fn add(&mut self, key: K, delta: V)
+    where
+        K: TiKey,
+        V: Add<Output = V> + Copy,
+    {
+        let index = key.to_usize();
+        self.check_index(index);
+        self.add_at(index, delta);
+    }
+
+    #[inline]
+    fn check_index(&self, index: usize) {
+        if index >= self.tree.len() {
+            index_out_of_bounds(index, self.tree.len());
+        }
+    }
+
+    fn add_at(&mut self, index: usize, delta: V)
+    where
+        V: Add<Output = V> + Copy,
+    {
+        let mut node = index + 1;
+        while node <= self.tree.len() {
+            self.tree[node - 1] = self.tree[node - 1] + delta;
+            node += lowbit(node);
+        }
+    }
+
+    /// Sets the value of the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is out of bounds.
+    #[inline]
+    pub fn set(&mut self, key: K, value: V)
+    where
+        K: TiKey,
+        V: Add<Output = V> + Sub<Output = V> + Default + Copy,
+    {
+        let index = key.to_usize();
+        self.check_index(index);
+        let delta = value - self.range_sum_at(index, index + 1);
+        self.add_at(index, delta);
+    }
+
+    /// Returns the value of the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is out of bounds.
+    #[inline]
+    pub fn get(&self, key: K) -> V
+    where
+        K: TiKey,
+        V: Add<Output = V> + Sub<Output = V> + Default + Copy,
+    {
+        let index = key.to_usize();
+        self.check_index(index);
+        self.range_sum_at(index, index + 1)
+    }
+
+    /// Returns the sum of values of keys before the key.
+    ///
+    /// The key equal to the length returns the sum of all values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is greater than the length.
+    #[inline]
+    pub fn prefix(&self, key: K) -> V
+    where
+        K: TiKey,
+        V: Add<Output = V> + Default + Copy,
+    {
+        let index = key.to_usize();
+        if index > self.tree.len() {
+            index_out_of_bounds(index, self.tree.len());
+        }
+        self.prefix_at(index)
+    }
+
+    /// Returns the sum of all values.
+    #[inline]
+    pub fn total(&self) -> V
+    where
+        V: Add<Output = V> + Default + Copy,
+    {
+        self.prefix_at(self.tree.len())
+    }
+
+    /// Returns the sum of values of keys in the range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    #[inline]
+    pub fn range_sum<R>(&self, range: R) -> V
+    where
+        R: TiRangeBounds<K>,
+        V: Add<Output = V> + Sub<Output = V> + Default + Copy,
+    {
+        let range = range_to_bounds(range.into_range(), self.tree.len());
+        self.range_sum_at(range.start, range.end)
+    }
+
+    /// Returns the first key for which the sum of values up to and including it
+    /// is at least the target, or the key equal to the length if there is none.
+    ///
+    /// All values must be non-negative for the search to be correct.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the result is the length and it can not be represented by the key type.
+    pub fn lower_bound(&self, target: V) -> K
+    where
+        K: TiKey,
+        V: Sub<Output = V> + Ord + Copy,
+    {
+        let len = self.tree.len();
+        let mut index = 0;
+        let mut remaining = target;
+        let mut step = if len == 0 {
+            0
+        } else {
+            1 << (usize::BITS - 1 - len.leading_zeros())
+        };
+        while step != 0 {
+            let node = index + step;
+            if node <= len && self.tree[node - 1] < remaining {
+                index = node;
+                remaining = remaining - self.tree[node - 1];
+            }
+            step >>= 1;
+        }
+        key_from_len(index)
+    }
+
+    /// Converts the prefix sums into a vector of the values in linear time.
+    pub fn into_vec(self) -> TiVec<K, V>
+    where
+        V: Sub<Output = V> + Copy,
+    {
+        let mut values = self.tree;
+        for node in (1..=values.len()).rev() {
+            let parent = node + lowbit(node);
+            if parent <= values.len() {
+                values[parent - 1] = values[parent - 1] - values[node - 1];
+            }
+        }
+        values.into()
+    }
+
+    #[inline]
+    fn prefix_at(&self, index: usize) -> V
+    where
+        V: Add<Output = V> + Default + Copy,
+    {
+        let mut sum = V::default();
+        let mut node = index;
+        while node != 0 {
+            sum = sum + self.tree[node - 1];
+            node -= lowbit(node);
+        }
+        sum
+    }
+
+    #[inline]
+    fn range_sum_at(&self, start: usize, end: usize) -> V
+    where
+        V: Add<Output = V> + Sub<Output = V> + Default + Copy,
+    {
+        if end > self.tree.len() {
+            index_out_of_bounds(end - 1, self.tree.len());
+        }
+        self.prefix_at(end) - self.prefix_at(start)
+    }
+}
+
+impl<K, V> fmt::Debug for TiPrefixSums<K, V>
+where
+    V: fmt::Debug + Add<Output = V> + Sub<Output = V> + Default + Copy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries((0..self.tree.len()).map(|index| self.range_sum_at(index, index + 1)))
+            .finish()
+    }
+}
+
+impl<K, V> Clone for TiPrefixSums<K, V>
+where
+    V: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> Default for TiPrefixSums<K, V> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            tree: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> PartialEq for TiPrefixSums<K, V>
+where
+    V: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.tree == other.tree
+    }
+}
+
+impl<K, V> Eq for TiPrefixSums<K, V> where V: Eq {}
+
+impl<K, V> From<&TiSlice<K, V>> for TiPrefixSums<K, V>
+where
+    K: TiKey,
+    V: Add<Output = V> + Copy,
+{
+    #[inline]
+    fn from(values: &TiSlice<K, V>) -> Self {
+        Self::from_slice(values)
+    }
+}
+
+impl<K, V> FromIterator<V> for TiPrefixSums<K, V>
+where
+    K: TiKey,
+    V: Add<Output = V> + Copy,
+{
+    fn from_iter<I: IntoIterator<Item = V>>(iter: I) -> Self {
+        let values: Vec<V> = iter.into_iter().collect();
+        Self::from_slice(TiSlice::from_ref(&values))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::{Id, SmallId};
+    use crate::{TiPrefixSums, TiSlice};
+    use alloc::{format, vec::Vec};
+    use core::iter::FromIterator;
+
+    #[test]
+    fn sums_match_naive() {
+        let mut values: Vec<i64> = (0..37).map(|index| (index * 7 % 11) - 5).collect();
+        let mut sums = TiPrefixSums::from_slice(TiSlice::<Id, _>::from_ref(&values));
+        for step in 0..37 {
+            let index = step * 5 % 37;
+            let delta = step as i64 - 18;
+            values[index] += delta;
+            sums.add(Id::from(index), delta);
+            for end in 0..=values.len() {
+                let expected: i64 = values[..end].iter().sum();
+                assert_eq!(sums.prefix(Id::from(end)), expected);
+            }
+        }
+        assert_eq!(
+            sums.range_sum(Id::from(3)..Id::from(30)),
+            values[3..30].iter().sum::<i64>()
+        );
+        assert_eq!(sums.range_sum(..), sums.total());
+        assert_eq!(sums.range_sum(Id::from(36)..), values[36]);
+        sums.set(Id::from(10), 100);
+        values[10] = 100;
+        assert_eq!(sums.get(Id::from(10)), 100);
+        assert_eq!(sums.clone().into_vec().raw, values);
+    }
+
+    #[test]
+    fn push_and_lower_bound() {
+        let mut sums: TiPrefixSums<Id, u32> = TiPrefixSums::new(0);
+        assert_eq!(sums.lower_bound(1), Id::from(0));
+        for (index, &value) in [3, 0, 2, 5, 1, 4, 0, 6].iter().enumerate() {
+            assert_eq!(sums.push(value), Id::from(index));
+        }
+        assert_eq!(sums, TiPrefixSums::from_iter([3, 0, 2, 5, 1, 4, 0, 6]));
+        assert_eq!(format!("{:?}", sums), "[3, 0, 2, 5, 1, 4, 0, 6]");
+        assert_eq!(sums.total(), 21);
+        assert_eq!(sums.lower_bound(0), Id::from(0));
+        assert_eq!(sums.lower_bound(3), Id::from(0));
+        assert_eq!(sums.lower_bound(4), Id::from(2));
+        assert_eq!(sums.lower_bound(11), Id::from(4));
+        assert_eq!(sums.lower_bound(15), Id::from(5));
+        assert_eq!(sums.lower_bound(16), Id::from(7));
+        assert_eq!(sums.lower_bound(21), Id::from(7));
+        assert_eq!(sums.lower_bound(22), Id::from(8));
+        let zeros: TiPrefixSums<Id, u32> = TiPrefixSums::new(4);
+        assert_eq!(zeros.lower_bound(0), Id::from(0));
+        assert_eq!(zeros.lower_bound(1), Id::from(4));
+    }
+
+    #[test]
+    #[should_panic(expected = "is out of bounds for the prefix sums of length 3")]
+    fn set_usize_max() {
+        let mut sums: TiPrefixSums<Id, u32> = TiPrefixSums::new(3);
+        sums.set(Id::from(usize::MAX), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "is out of bounds for the prefix sums of length 3")]
+    fn get_usize_max() {
+        let sums: TiPrefixSums<Id, u32> = TiPrefixSums::new(3);
+        let _ = sums.get(Id::from(usize::MAX));
+    }
+
+    #[test]
+    #[should_panic(expected = "container length 3 is out of range")]
+    fn from_iter_key_overflow() {
+        let _: TiPrefixSums<SmallId, u32> = TiPrefixSums::from_iter([1, 2, 3]);
+    }
+}
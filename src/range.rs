@@ -1,11 +1,13 @@
 use core::ops;
 
+use crate::TiKey;
+
 /// A helper trait used to convert typed index ranges to `usize` ranges.
-/// The trait is implemented for Rust's built-in range types with `K where usize: `[`From<K>`] used as bound endpoints.
+/// The trait is implemented for Rust's built-in range types with `K where K: `[`TiKey`] used as bound endpoints.
 ///
 /// See [`core::ops::RangeBounds`] for more details.
 ///
-/// [`From<K>`]: https://doc.rust-lang.org/std/convert/trait.From.html
+/// [`TiKey`]: trait.TiKey.html
 /// [`core::ops::RangeBounds`]: https://doc.rust-lang.org/core/ops/trait.RangeBounds.html
 pub trait TiRangeBounds<K> {
     /// Appropriate usize range
@@ -16,29 +18,29 @@ pub trait TiRangeBounds<K> {
 
 impl<K> TiRangeBounds<K> for ops::Range<K>
 where
-    usize: From<K>,
+    K: TiKey,
 {
     type Range = ops::Range<usize>;
     #[inline]
     fn into_range(self) -> Self::Range {
-        self.start.into()..self.end.into()
+        self.start.to_usize()..self.end.to_usize()
     }
 }
 
 impl<K> TiRangeBounds<K> for ops::RangeFrom<K>
 where
-    usize: From<K>,
+    K: TiKey,
 {
     type Range = ops::RangeFrom<usize>;
     #[inline]
     fn into_range(self) -> Self::Range {
-        self.start.into()..
+        self.start.to_usize()..
     }
 }
 
 impl<K> TiRangeBounds<K> for ops::RangeFull
 where
-    usize: From<K>,
+    K: TiKey,
 {
     type Range = ops::RangeFull;
     #[inline]
@@ -49,34 +51,69 @@ where
 
 impl<K> TiRangeBounds<K> for ops::RangeInclusive<K>
 where
-    usize: From<K>,
+    K: TiKey,
 {
     type Range = ops::RangeInclusive<usize>;
     #[inline]
     fn into_range(self) -> Self::Range {
         let (start, end) = self.into_inner();
-        start.into()..=end.into()
+        start.to_usize()..=end.to_usize()
     }
 }
 
 impl<K> TiRangeBounds<K> for ops::RangeTo<K>
 where
-    usize: From<K>,
+    K: TiKey,
 {
     type Range = ops::RangeTo<usize>;
     #[inline]
     fn into_range(self) -> Self::Range {
-        ..self.end.into()
+        ..self.end.to_usize()
     }
 }
 
 impl<K> TiRangeBounds<K> for ops::RangeToInclusive<K>
 where
-    usize: From<K>,
+    K: TiKey,
 {
     type Range = ops::RangeToInclusive<usize>;
     #[inline]
     fn into_range(self) -> Self::Range {
-        ..=self.end.into()
+        ..=self.end.to_usize()
     }
 }
+
+/// Converts a `usize` range into a `start..end` range
+/// checked against the container length.
+pub(crate) fn range_to_bounds<R>(range: R, len: usize) -> ops::Range<usize>
+where
+    R: ops::RangeBounds<usize>,
+{
+    let start = match range.start_bound() {
+        ops::Bound::Included(&start) => start,
+        ops::Bound::Excluded(&start) => start
+            .checked_add(1)
+            .unwrap_or_else(|| panic!("attempted to index slice from after maximum usize")),
+        ops::Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        ops::Bound::Included(&end) => end
+            .checked_add(1)
+            .unwrap_or_else(|| panic!("attempted to index slice up to maximum usize")),
+        ops::Bound::Excluded(&end) => end,
+        ops::Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end,
+        "slice index starts at {} but ends at {}",
+        start,
+        end
+    );
+    assert!(
+        end <= len,
+        "range end index {} out of range for slice of length {}",
+        end,
+        len
+    );
+    start..end
+}
@@ -0,0 +1,502 @@
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+    iter,
+    marker::PhantomData,
+    ops,
+};
+
+use alloc::collections::vec_deque::{self, Iter, IterMut, VecDeque};
+
+use crate::key::{assert_keys_in_range, key_from_len};
+use crate::{TiKey, TiSliceKeys};
+
+/// A bounded buffer with absolute, monotonically increasing keys of the type `K`.
+///
+/// `TiRingBuffer<K, V>` stores at most [`capacity`] entries.
+/// Pushing to a full buffer evicts the oldest entry.
+/// Unlike [`TiVec`], keys are never renumbered:
+/// the `n`-th pushed value keeps key `K::from_usize(n)` until it is evicted,
+/// and lookups of evicted keys return [`None`].
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::TiRingBuffer;
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct EventId(usize);
+///
+/// let mut log: TiRingBuffer<EventId, &str> = TiRingBuffer::new(2);
+/// assert_eq!(log.push_and_get_key("start"), (EventId(0), None));
+/// assert_eq!(log.push_and_get_key("tick"), (EventId(1), None));
+/// assert_eq!(
+///     log.push_and_get_key("stop"),
+///     (EventId(2), Some((EventId(0), "start")))
+/// );
+/// assert_eq!(log.get(EventId(0)), None);
+/// assert_eq!(log[EventId(2)], "stop");
+/// assert_eq!(log.first_key(), Some(EventId(1)));
+/// ```
+///
+/// [`capacity`]: #method.capacity
+/// [`TiVec`]: struct.TiVec.html
+pub struct TiRingBuffer<K, V> {
+    raw: VecDeque<V>,
+    offset: usize,
+    capacity: usize,
+
+    /// Ties the container to the key type `K`.
+    _marker: PhantomData<fn(K) -> K>,
+}
+
+impl<K, V> TiRingBuffer<K, V> {
+    /// Creates an empty `TiRingBuffer<K, V>` that holds at most `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "TiRingBuffer capacity must be non-zero");
+        Self {
+            raw: VecDeque::with_capacity(capacity),
+            offset: 0,
+            capacity,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the maximum number of live entries in the buffer.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of live entries in the buffer.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.raw.len()
+    }
+
+    /// Returns `true` if the buffer contains no live entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    /// Returns `true` if the next push evicts the oldest entry.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.raw.len() == self.capacity
+    }
+
+    /// Returns the number of entries evicted or removed from the front so far.
+    #[inline]
+    pub fn evicted_len(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the key of the oldest live entry, or [`None`] if the buffer is empty.
+    #[inline]
+    pub fn first_key(&self) -> Option<K>
+    where
+        K: TiKey,
+    {
+        if self.raw.is_empty() {
+            None
+        } else {
+            Some(K::from_usize(self.offset))
+        }
+    }
+
+    /// Returns the key of the newest live entry, or [`None`] if the buffer is empty.
+    #[inline]
+    pub fn last_key(&self) -> Option<K>
+    where
+        K: TiKey,
+    {
+        if self.raw.is_empty() {
+            None
+        } else {
+            Some(K::from_usize(self.offset + self.raw.len() - 1))
+        }
+    }
+
+    /// Returns the key that the next pushed value receives.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key can not be represented by the key type.
+    #[inline]
+    pub fn next_key(&self) -> K
+    where
+        K: TiKey,
+    {
+        key_from_len(self.offset + self.raw.len())
+    }
+
+    #[inline]
+    fn position(&self, index: usize) -> Option<usize> {
+        index
+            .checked_sub(self.offset)
+            .filter(|&position| position < self.raw.len())
+    }
+
+    /// Returns `true` if the entry with the given key is live.
+    #[inline]
+    pub fn contains_key(&self, key: K) -> bool
+    where
+        K: TiKey,
+    {
+        self.position(key.to_usize()).is_some()
+    }
+
+    /// Returns a reference to the entry with the given key,
+    /// or [`None`] if it was evicted or not pushed yet.
+    #[inline]
+    pub fn get(&self, key: K) -> Option<&V>
+    where
+        K: TiKey,
+    {
+        self.position(key.to_usize())
+            .map(|position| &self.raw[position])
+    }
+
+    /// Returns a mutable reference to the entry with the given key,
+    /// or [`None`] if it was evicted or not pushed yet.
+    #[inline]
+    pub fn get_mut(&mut self, key: K) -> Option<&mut V>
+    where
+        K: TiKey,
+    {
+        self.position(key.to_usize())
+            .map(move |position| &mut self.raw[position])
+    }
+
+    /// Returns a reference to the oldest live entry, or [`None`] if the buffer is empty.
+    #[inline]
+    pub fn front(&self) -> Option<&V> {
+        self.raw.front()
+    }
+
+    /// Returns a reference to the newest live entry, or [`None`] if the buffer is empty.
+    #[inline]
+    pub fn back(&self) -> Option<&V> {
+        self.raw.back()
+    }
+
+    /// Appends a value to the buffer, evicting the oldest entry if the buffer is full.
+    ///
+    /// Returns the evicted value, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key of the value can not be represented by the key type.
+    #[inline]
+    pub fn push(&mut self, value: V) -> Option<V>
+    where
+        K: TiKey,
+    {
+        self.push_and_get_key(value).1.map(|(_, evicted)| evicted)
+    }
+
+    /// Appends a value to the buffer, evicting the oldest entry if the buffer is full.
+    ///
+    /// Returns the key of the appended value and the evicted entry, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key of the value can not be represented by the key type.
+    pub fn push_and_get_key(&mut self, value: V) -> (K, Option<(K, V)>)
+    where
+        K: TiKey,
+    {
+        let key = self.next_key();
+        let evicted = if self.is_full() {
+            self.pop_front()
+        } else {
+            None
+        };
+        self.raw.push_back(value);
+        (key, evicted)
+    }
+
+    /// Removes the oldest live entry and returns it with its key,
+    /// or [`None`] if the buffer is empty.
+    ///
+    /// Keys of the remaining entries do not change.
+    pub fn pop_front(&mut self) -> Option<(K, V)>
+    where
+        K: TiKey,
+    {
+        let value = self.raw.pop_front()?;
+        let key = K::from_usize(self.offset);
+        self.offset += 1;
+        Some((key, value))
+    }
+
+    /// Removes all live entries with keys less than `key`.
+    ///
+    /// Keys of the remaining entries do not change.
+    pub fn evict_before(&mut self, key: K)
+    where
+        K: TiKey,
+    {
+        let count = key
+            .to_usize()
+            .saturating_sub(self.offset)
+            .min(self.raw.len());
+        drop(self.raw.drain(..count));
+        self.offset += count;
+    }
+
+    /// Removes all live entries.
+    ///
+    /// The buffer keeps counting keys from where it stopped.
+    pub fn clear(&mut self) {
+        self.offset += self.raw.len();
+        self.raw.clear();
+    }
+
+    /// Returns an iterator over live values from the oldest to the newest.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, V> {
+        self.raw.iter()
+    }
+
+    /// Returns an iterator that allows modifying each live value.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, V> {
+        self.raw.iter_mut()
+    }
+
+    /// Returns an iterator over the keys of live entries.
+    #[inline]
+    pub fn keys(&self) -> TiSliceKeys<K>
+    where
+        K: TiKey,
+    {
+        let end = self.offset + self.raw.len();
+        assert_keys_in_range::<K>(end);
+        (self.offset..end).map(K::from_usize)
+    }
+
+    /// Returns an iterator over all live key-value pairs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiRingBuffer;
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut buffer: TiRingBuffer<Id, char> = TiRingBuffer::new(2);
+    /// buffer.extend(vec!['a', 'b', 'c']);
+    /// let mut iterator = buffer.iter_enumerated();
+    /// assert_eq!(iterator.next(), Some((Id(1), &'b')));
+    /// assert_eq!(iterator.next(), Some((Id(2), &'c')));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    #[inline]
+    pub fn iter_enumerated(&self) -> iter::Zip<TiSliceKeys<K>, Iter<'_, V>>
+    where
+        K: TiKey,
+    {
+        self.keys().zip(self.raw.iter())
+    }
+
+    /// Returns an iterator over all live key-value pairs,
+    /// with mutable references to the values.
+    #[inline]
+    pub fn iter_mut_enumerated(&mut self) -> iter::Zip<TiSliceKeys<K>, IterMut<'_, V>>
+    where
+        K: TiKey,
+    {
+        self.keys().zip(self.raw.iter_mut())
+    }
+
+    /// Converts the buffer into iterator over all live key-value pairs.
+    #[inline]
+    pub fn into_iter_enumerated(self) -> iter::Zip<TiSliceKeys<K>, vec_deque::IntoIter<V>>
+    where
+        K: TiKey,
+    {
+        self.keys().zip(self.raw)
+    }
+}
+
+impl<K, V> fmt::Debug for TiRingBuffer<K, V>
+where
+    K: fmt::Debug + TiKey,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter_enumerated()).finish()
+    }
+}
+
+impl<K, V> ops::Index<K> for TiRingBuffer<K, V>
+where
+    K: TiKey,
+{
+    type Output = V;
+
+    #[inline]
+    fn index(&self, key: K) -> &V {
+        let index = key.to_usize();
+        match self.position(index) {
+            Some(position) => &self.raw[position],
+            None => panic!("key index {} is not live in the ring buffer", index),
+        }
+    }
+}
+
+impl<K, V> ops::IndexMut<K> for TiRingBuffer<K, V>
+where
+    K: TiKey,
+{
+    #[inline]
+    fn index_mut(&mut self, key: K) -> &mut V {
+        let index = key.to_usize();
+        match self.position(index) {
+            Some(position) => &mut self.raw[position],
+            None => panic!("key index {} is not live in the ring buffer", index),
+        }
+    }
+}
+
+impl<K, V> Extend<V> for TiRingBuffer<K, V>
+where
+    K: TiKey,
+{
+    fn extend<I: IntoIterator<Item = V>>(&mut self, iter: I) {
+        for value in iter {
+            let _ = self.push(value);
+        }
+    }
+}
+
+impl<K, V> PartialEq for TiRingBuffer<K, V>
+where
+    V: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.offset == other.offset && self.raw == other.raw
+    }
+}
+
+impl<K, V> Eq for TiRingBuffer<K, V> where V: Eq {}
+
+impl<K, V> Hash for TiRingBuffer<K, V>
+where
+    V: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.offset.hash(state);
+        self.raw.hash(state);
+    }
+}
+
+impl<K, V> Clone for TiRingBuffer<K, V>
+where
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            offset: self.offset,
+            capacity: self.capacity,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> IntoIterator for TiRingBuffer<K, V> {
+    type Item = V;
+    type IntoIter = vec_deque::IntoIter<V>;
+
+    #[inline]
+    fn into_iter(self) -> vec_deque::IntoIter<V> {
+        self.raw.into_iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a TiRingBuffer<K, V> {
+    type Item = &'a V;
+    type IntoIter = Iter<'a, V>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, V> {
+        self.raw.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut TiRingBuffer<K, V> {
+    type Item = &'a mut V;
+    type IntoIter = IterMut<'a, V>;
+
+    #[inline]
+    fn into_iter(self) -> IterMut<'a, V> {
+        self.raw.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::Id;
+    use crate::TiRingBuffer;
+    use alloc::{format, vec::Vec};
+
+    #[test]
+    fn keys_survive_eviction() {
+        let mut buffer: TiRingBuffer<Id, u32> = TiRingBuffer::new(3);
+        assert_eq!(buffer.first_key(), None);
+        assert_eq!(buffer.last_key(), None);
+        for value in 0..5 {
+            let _ = buffer.push(value);
+        }
+        assert!(buffer.is_full());
+        assert_eq!(buffer.evicted_len(), 2);
+        assert_eq!(buffer.first_key(), Some(Id::from(2)));
+        assert_eq!(buffer.last_key(), Some(Id::from(4)));
+        assert_eq!(buffer.get(Id::from(1)), None);
+        assert_eq!(buffer.get(Id::from(5)), None);
+        assert_eq!(buffer[Id::from(3)], 3);
+        assert_eq!(
+            buffer.push_and_get_key(5),
+            (Id::from(5), Some((Id::from(2), 2)))
+        );
+        assert_eq!(format!("{:?}", buffer), "{Id(3): 3, Id(4): 4, Id(5): 5}");
+    }
+
+    #[test]
+    fn front_removal() {
+        let mut buffer: TiRingBuffer<Id, u32> = TiRingBuffer::new(4);
+        buffer.extend(0..4);
+        assert_eq!(buffer.pop_front(), Some((Id::from(0), 0)));
+        buffer.evict_before(Id::from(2));
+        assert_eq!(
+            buffer.keys().collect::<Vec<_>>(),
+            [Id::from(2), Id::from(3)]
+        );
+        for (key, value) in buffer.iter_mut_enumerated() {
+            *value += usize::from(key) as u32;
+        }
+        assert_eq!(buffer.iter().copied().collect::<Vec<_>>(), [4, 6]);
+        buffer.clear();
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.next_key(), Id::from(4));
+        buffer.evict_before(Id::from(10));
+        assert_eq!(buffer.next_key(), Id::from(4));
+    }
+
+    #[test]
+    #[should_panic(expected = "key index 0 is not live in the ring buffer")]
+    fn index_evicted() {
+        let mut buffer: TiRingBuffer<Id, u32> = TiRingBuffer::new(1);
+        buffer.extend(0..2);
+        let _ = buffer[Id::from(0)];
+    }
+}
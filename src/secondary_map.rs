@@ -0,0 +1,869 @@
+use core::{
+    fmt,
+    iter::{self, FromIterator, FusedIterator},
+    marker::PhantomData,
+    mem::{self, MaybeUninit},
+    ops, slice,
+};
+
+use alloc::vec::Vec;
+
+use crate::bit_set::{contains_bit, insert_bit, remove_bit, words_for};
+use crate::{TiKey, TiVec};
+
+/// A map from keys of the type `K` to values of the type `V`
+/// that grows on insertion and tracks present keys in a bitmap.
+///
+/// It is intended for optional side data attached to a subset of keys
+/// of a primary [`TiVec`].
+/// Unlike `TiVec<K, Option<V>>` it does not need to be resized by hand
+/// and values do not pay for the `Option` discriminant.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::{TiSecondaryMap, TiVec};
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct ExprId(usize);
+///
+/// let exprs: TiVec<ExprId, &str> = vec!["1", "x", "1 + x"].into();
+/// let mut names: TiSecondaryMap<ExprId, &str> = TiSecondaryMap::new();
+/// assert_eq!(names.insert(ExprId(1), "x"), None);
+/// *names.entry(ExprId(2)).or_insert("sum") = "total";
+/// assert_eq!(names.get(ExprId(0)), None);
+/// assert_eq!(names[ExprId(2)], "total");
+/// assert_eq!(names.len(), 2);
+///
+/// let names: TiVec<ExprId, Option<&str>> = names.into();
+/// assert_eq!(names, TiVec::from(vec![None, Some("x"), Some("total")]));
+/// assert_eq!(exprs.len(), names.len());
+/// ```
+///
+/// [`TiVec`]: struct.TiVec.html
+pub struct TiSecondaryMap<K, V> {
+    values: Vec<MaybeUninit<V>>,
+    present: Vec<u64>,
+    len: usize,
+
+    /// Ties the container to the key type `K`.
+    _marker: PhantomData<fn(K) -> K>,
+}
+
+impl<K, V> TiSecondaryMap<K, V> {
+    /// Constructs a new, empty map.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            present: Vec::new(),
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs a new, empty map that can hold keys below `capacity`
+    /// without reallocating.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            values: Vec::with_capacity(capacity),
+            present: Vec::with_capacity(words_for(capacity)),
+            len: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of present values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the map contains no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all values from the map.
+    pub fn clear(&mut self) {
+        self.drop_values();
+        self.values.clear();
+        self.present.clear();
+    }
+
+    /// Returns `true` if the map contains a value for the key.
+    #[inline]
+    pub fn contains_key(&self, key: K) -> bool
+    where
+        K: TiKey,
+    {
+        contains_bit(&self.present, key.to_usize())
+    }
+
+    /// Returns a reference to the value for the key,
+    /// or `None` if the key is not present.
+    #[inline]
+    pub fn get(&self, key: K) -> Option<&V>
+    where
+        K: TiKey,
+    {
+        self.get_at(key.to_usize())
+    }
+
+    /// Returns a mutable reference to the value for the key,
+    /// or `None` if the key is not present.
+    #[inline]
+    pub fn get_mut(&mut self, key: K) -> Option<&mut V>
+    where
+        K: TiKey,
+    {
+        self.get_at_mut(key.to_usize())
+    }
+
+    /// Inserts a value for the key, growing the map if needed.
+    ///
+    /// Returns the previous value for the key, if any.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V>
+    where
+        K: TiKey,
+    {
+        let index = key.to_usize();
+        match self.get_at_mut(index) {
+            Some(old) => Some(mem::replace(old, value)),
+            None => {
+                let _ = self.insert_vacant(index, value);
+                None
+            }
+        }
+    }
+
+    /// Removes and returns the value for the key,
+    /// or `None` if the key is not present.
+    #[inline]
+    pub fn remove(&mut self, key: K) -> Option<V>
+    where
+        K: TiKey,
+    {
+        self.remove_at(key.to_usize())
+    }
+
+    /// Gets the entry for the key for in-place manipulation.
+    #[inline]
+    pub fn entry(&mut self, key: K) -> TiSecondaryMapEntry<'_, K, V>
+    where
+        K: TiKey,
+    {
+        let index = key.to_usize();
+        if contains_bit(&self.present, index) {
+            TiSecondaryMapEntry::Occupied(TiSecondaryMapOccupiedEntry { map: self, index })
+        } else {
+            TiSecondaryMapEntry::Vacant(TiSecondaryMapVacantEntry { map: self, index })
+        }
+    }
+
+    /// Retains only the values specified by the predicate.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        K: TiKey,
+        F: FnMut(K, &mut V) -> bool,
+    {
+        for index in 0..self.values.len() {
+            if let Some(value) = self.get_at_mut(index) {
+                if !f(K::from_usize(index), value) {
+                    let _ = self.remove_at(index);
+                }
+            }
+        }
+    }
+
+    /// Returns an iterator over present keys and references to their values.
+    #[inline]
+    pub fn iter_enumerated(&self) -> TiSecondaryMapIter<'_, K, V> {
+        TiSecondaryMapIter {
+            values: self.values.iter().enumerate(),
+            present: &self.present,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over present keys and mutable references to their values.
+    #[inline]
+    pub fn iter_mut_enumerated(&mut self) -> TiSecondaryMapIterMut<'_, K, V> {
+        TiSecondaryMapIterMut {
+            values: self.values.iter_mut().enumerate(),
+            present: &self.present,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates a consuming iterator over present keys and their values.
+    #[inline]
+    pub fn into_iter_enumerated(self) -> TiSecondaryMapIntoIter<K, V> {
+        TiSecondaryMapIntoIter {
+            front: 0,
+            back: self.values.len(),
+            map: self,
+        }
+    }
+
+    #[inline]
+    fn get_at(&self, index: usize) -> Option<&V> {
+        if contains_bit(&self.present, index) {
+            // SAFETY: Values of present keys are initialized.
+            Some(unsafe { self.values[index].assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn get_at_mut(&mut self, index: usize) -> Option<&mut V> {
+        if contains_bit(&self.present, index) {
+            // SAFETY: Values of present keys are initialized.
+            Some(unsafe { self.values[index].assume_init_mut() })
+        } else {
+            None
+        }
+    }
+
+    fn insert_vacant(&mut self, index: usize, value: V) -> &mut V {
+        if index >= self.values.len() {
+            self.values.resize_with(index + 1, MaybeUninit::uninit);
+            self.present.resize(words_for(index + 1), 0);
+        }
+        let inserted = insert_bit(&mut self.present, index);
+        debug_assert!(inserted);
+        self.len += 1;
+        self.values[index].write(value)
+    }
+
+    fn remove_at(&mut self, index: usize) -> Option<V> {
+        if remove_bit(&mut self.present, index) {
+            self.len -= 1;
+            // SAFETY: The value was initialized and its key is no longer present.
+            Some(unsafe { self.values[index].assume_init_read() })
+        } else {
+            None
+        }
+    }
+
+    fn drop_values(&mut self) {
+        for index in 0..self.values.len() {
+            if remove_bit(&mut self.present, index) {
+                self.len -= 1;
+                // SAFETY: The value was initialized and its key is no longer present.
+                unsafe { self.values[index].assume_init_drop() };
+            }
+        }
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn not_present(index: usize) -> ! {
+    panic!("key index {} is not present in the secondary map", index)
+}
+
+impl<K, V> Drop for TiSecondaryMap<K, V> {
+    fn drop(&mut self) {
+        if mem::needs_drop::<V>() {
+            self.drop_values();
+        }
+    }
+}
+
+impl<K, V> fmt::Debug for TiSecondaryMap<K, V>
+where
+    K: fmt::Debug + TiKey,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter_enumerated()).finish()
+    }
+}
+
+impl<K, V> ops::Index<K> for TiSecondaryMap<K, V>
+where
+    K: TiKey,
+{
+    type Output = V;
+
+    #[inline]
+    fn index(&self, key: K) -> &V {
+        let index = key.to_usize();
+        match self.get_at(index) {
+            Some(value) => value,
+            None => not_present(index),
+        }
+    }
+}
+
+impl<K, V> ops::IndexMut<K> for TiSecondaryMap<K, V>
+where
+    K: TiKey,
+{
+    #[inline]
+    fn index_mut(&mut self, key: K) -> &mut V {
+        let index = key.to_usize();
+        match self.get_at_mut(index) {
+            Some(value) => value,
+            None => not_present(index),
+        }
+    }
+}
+
+impl<K, V> Clone for TiSecondaryMap<K, V>
+where
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        let values = (0..self.values.len())
+            .map(|index| match self.get_at(index) {
+                Some(value) => MaybeUninit::new(value.clone()),
+                None => MaybeUninit::uninit(),
+            })
+            .collect();
+        Self {
+            values,
+            present: self.present.clone(),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> Default for TiSecondaryMap<K, V> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> PartialEq for TiSecondaryMap<K, V>
+where
+    V: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && (0..self.values.len()).all(|index| match self.get_at(index) {
+                Some(value) => other.get_at(index) == Some(value),
+                None => true,
+            })
+    }
+}
+
+impl<K, V> Eq for TiSecondaryMap<K, V> where V: Eq {}
+
+impl<K, V> From<TiVec<K, Option<V>>> for TiSecondaryMap<K, V> {
+    fn from(vec: TiVec<K, Option<V>>) -> Self {
+        let mut map = Self::with_capacity(vec.len());
+        map.values.resize_with(vec.len(), MaybeUninit::uninit);
+        map.present.resize(words_for(vec.len()), 0);
+        for (index, value) in vec.raw.into_iter().enumerate() {
+            if let Some(value) = value {
+                let _ = map.insert_vacant(index, value);
+            }
+        }
+        map
+    }
+}
+
+impl<K, V> From<TiSecondaryMap<K, V>> for TiVec<K, Option<V>> {
+    fn from(mut map: TiSecondaryMap<K, V>) -> Self {
+        (0..map.values.len())
+            .map(|index| map.remove_at(index))
+            .collect()
+    }
+}
+
+impl<K, V> Extend<(K, V)> for TiSecondaryMap<K, V>
+where
+    K: TiKey,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            let _ = self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for TiSecondaryMap<K, V>
+where
+    K: TiKey,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V> IntoIterator for TiSecondaryMap<K, V>
+where
+    K: TiKey,
+{
+    type Item = (K, V);
+    type IntoIter = TiSecondaryMapIntoIter<K, V>;
+
+    #[inline]
+    fn into_iter(self) -> TiSecondaryMapIntoIter<K, V> {
+        self.into_iter_enumerated()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a TiSecondaryMap<K, V>
+where
+    K: TiKey,
+{
+    type Item = (K, &'a V);
+    type IntoIter = TiSecondaryMapIter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> TiSecondaryMapIter<'a, K, V> {
+        self.iter_enumerated()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut TiSecondaryMap<K, V>
+where
+    K: TiKey,
+{
+    type Item = (K, &'a mut V);
+    type IntoIter = TiSecondaryMapIterMut<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> TiSecondaryMapIterMut<'a, K, V> {
+        self.iter_mut_enumerated()
+    }
+}
+
+/// A view into a single entry of a [`TiSecondaryMap`].
+///
+/// This enum is created by the [`TiSecondaryMap::entry`] method.
+///
+/// [`TiSecondaryMap`]: struct.TiSecondaryMap.html
+/// [`TiSecondaryMap::entry`]: struct.TiSecondaryMap.html#method.entry
+pub enum TiSecondaryMapEntry<'a, K, V> {
+    /// An occupied entry.
+    Occupied(TiSecondaryMapOccupiedEntry<'a, K, V>),
+    /// A vacant entry.
+    Vacant(TiSecondaryMapVacantEntry<'a, K, V>),
+}
+
+/// A view into an occupied entry of a [`TiSecondaryMap`].
+///
+/// [`TiSecondaryMap`]: struct.TiSecondaryMap.html
+pub struct TiSecondaryMapOccupiedEntry<'a, K, V> {
+    map: &'a mut TiSecondaryMap<K, V>,
+    index: usize,
+}
+
+/// A view into a vacant entry of a [`TiSecondaryMap`].
+///
+/// [`TiSecondaryMap`]: struct.TiSecondaryMap.html
+pub struct TiSecondaryMapVacantEntry<'a, K, V> {
+    map: &'a mut TiSecondaryMap<K, V>,
+    index: usize,
+}
+
+impl<'a, K, V> TiSecondaryMapEntry<'a, K, V> {
+    /// Returns the key of the entry.
+    #[inline]
+    pub fn key(&self) -> K
+    where
+        K: TiKey,
+    {
+        match self {
+            TiSecondaryMapEntry::Occupied(entry) => entry.key(),
+            TiSecondaryMapEntry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Inserts the default value if the entry is vacant
+    /// and returns a mutable reference to the value.
+    #[inline]
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Inserts the result of the function if the entry is vacant
+    /// and returns a mutable reference to the value.
+    #[inline]
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            TiSecondaryMapEntry::Occupied(entry) => entry.into_mut(),
+            TiSecondaryMapEntry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Inserts `V::default()` if the entry is vacant
+    /// and returns a mutable reference to the value.
+    #[inline]
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Calls the function on the value if the entry is occupied.
+    #[inline]
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let TiSecondaryMapEntry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V> TiSecondaryMapOccupiedEntry<'a, K, V> {
+    /// Returns the key of the entry.
+    #[inline]
+    pub fn key(&self) -> K
+    where
+        K: TiKey,
+    {
+        K::from_usize(self.index)
+    }
+
+    /// Returns a reference to the value of the entry.
+    #[inline]
+    pub fn get(&self) -> &V {
+        match self.map.get_at(self.index) {
+            Some(value) => value,
+            None => not_present(self.index),
+        }
+    }
+
+    /// Returns a mutable reference to the value of the entry.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut V {
+        match self.map.get_at_mut(self.index) {
+            Some(value) => value,
+            None => not_present(self.index),
+        }
+    }
+
+    /// Converts the entry into a mutable reference to its value
+    /// with the lifetime of the map.
+    #[inline]
+    pub fn into_mut(self) -> &'a mut V {
+        match self.map.get_at_mut(self.index) {
+            Some(value) => value,
+            None => not_present(self.index),
+        }
+    }
+
+    /// Replaces the value of the entry and returns the old value.
+    #[inline]
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the entry from the map and returns its value.
+    #[inline]
+    pub fn remove(self) -> V {
+        match self.map.remove_at(self.index) {
+            Some(value) => value,
+            None => not_present(self.index),
+        }
+    }
+}
+
+impl<'a, K, V> TiSecondaryMapVacantEntry<'a, K, V> {
+    /// Returns the key of the entry.
+    #[inline]
+    pub fn key(&self) -> K
+    where
+        K: TiKey,
+    {
+        K::from_usize(self.index)
+    }
+
+    /// Inserts the value into the map and returns a mutable reference to it.
+    #[inline]
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.map.insert_vacant(self.index, value)
+    }
+}
+
+impl<K, V> fmt::Debug for TiSecondaryMapEntry<'_, K, V>
+where
+    K: fmt::Debug + TiKey,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TiSecondaryMapEntry::Occupied(entry) => f.debug_tuple("Occupied").field(entry).finish(),
+            TiSecondaryMapEntry::Vacant(entry) => f.debug_tuple("Vacant").field(entry).finish(),
+        }
+    }
+}
+
+impl<K, V> fmt::Debug for TiSecondaryMapOccupiedEntry<'_, K, V>
+where
+    K: fmt::Debug + TiKey,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TiSecondaryMapOccupiedEntry")
+            .field("key", &self.key())
+            .field("value", self.get())
+            .finish()
+    }
+}
+
+impl<K, V> fmt::Debug for TiSecondaryMapVacantEntry<'_, K, V>
+where
+    K: fmt::Debug + TiKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TiSecondaryMapVacantEntry")
+            .field(&self.key())
+            .finish()
+    }
+}
+
+macro_rules! secondary_map_iter {
+    (
+        $(#[$attr:meta])*
+        $name:ident,
+        $values:ty,
+        $item:ty,
+        |$value:ident| $get:expr
+    ) => {
+        $(#[$attr])*
+        pub struct $name<'a, K, V> {
+            values: iter::Enumerate<$values>,
+            present: &'a [u64],
+            len: usize,
+            _marker: PhantomData<fn(K) -> K>,
+        }
+
+        impl<K, V> fmt::Debug for $name<'_, K, V> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($name)).field("len", &self.len).finish()
+            }
+        }
+
+        impl<'a, K: TiKey, V> Iterator for $name<'a, K, V> {
+            type Item = $item;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                for (index, $value) in &mut self.values {
+                    if contains_bit(self.present, index) {
+                        self.len -= 1;
+                        return Some((K::from_usize(index), $get));
+                    }
+                }
+                None
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (self.len, Some(self.len))
+            }
+        }
+
+        impl<K: TiKey, V> DoubleEndedIterator for $name<'_, K, V> {
+            #[inline]
+            fn next_back(&mut self) -> Option<Self::Item> {
+                while let Some((index, $value)) = self.values.next_back() {
+                    if contains_bit(self.present, index) {
+                        self.len -= 1;
+                        return Some((K::from_usize(index), $get));
+                    }
+                }
+                None
+            }
+        }
+
+        impl<K: TiKey, V> ExactSizeIterator for $name<'_, K, V> {}
+
+        impl<K: TiKey, V> FusedIterator for $name<'_, K, V> {}
+    };
+}
+
+secondary_map_iter!(
+    /// An iterator over present keys and values of a [`TiSecondaryMap`].
+    ///
+    /// This struct is created by the [`TiSecondaryMap::iter_enumerated`] method.
+    ///
+    /// [`TiSecondaryMap`]: struct.TiSecondaryMap.html
+    /// [`TiSecondaryMap::iter_enumerated`]: struct.TiSecondaryMap.html#method.iter_enumerated
+    TiSecondaryMapIter,
+    slice::Iter<'a, MaybeUninit<V>>,
+    (K, &'a V),
+    // SAFETY: Values of present keys are initialized.
+    |value| unsafe { value.assume_init_ref() }
+);
+
+secondary_map_iter!(
+    /// A mutable iterator over present keys and values of a [`TiSecondaryMap`].
+    ///
+    /// This struct is created by the [`TiSecondaryMap::iter_mut_enumerated`] method.
+    ///
+    /// [`TiSecondaryMap`]: struct.TiSecondaryMap.html
+    /// [`TiSecondaryMap::iter_mut_enumerated`]: struct.TiSecondaryMap.html#method.iter_mut_enumerated
+    TiSecondaryMapIterMut,
+    slice::IterMut<'a, MaybeUninit<V>>,
+    (K, &'a mut V),
+    // SAFETY: Values of present keys are initialized.
+    |value| unsafe { value.assume_init_mut() }
+);
+
+/// An iterator that moves out of present keys and values of a [`TiSecondaryMap`].
+///
+/// This struct is created by the [`TiSecondaryMap::into_iter_enumerated`] method.
+///
+/// [`TiSecondaryMap`]: struct.TiSecondaryMap.html
+/// [`TiSecondaryMap::into_iter_enumerated`]: struct.TiSecondaryMap.html#method.into_iter_enumerated
+pub struct TiSecondaryMapIntoIter<K, V> {
+    map: TiSecondaryMap<K, V>,
+    front: usize,
+    back: usize,
+}
+
+impl<K, V> fmt::Debug for TiSecondaryMapIntoIter<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TiSecondaryMapIntoIter")
+            .field("len", &self.map.len)
+            .finish()
+    }
+}
+
+impl<K: TiKey, V> Iterator for TiSecondaryMapIntoIter<K, V> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.front < self.back {
+            let index = self.front;
+            self.front += 1;
+            if let Some(value) = self.map.remove_at(index) {
+                return Some((K::from_usize(index), value));
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.map.len, Some(self.map.len))
+    }
+}
+
+impl<K: TiKey, V> DoubleEndedIterator for TiSecondaryMapIntoIter<K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while self.front < self.back {
+            self.back -= 1;
+            if let Some(value) = self.map.remove_at(self.back) {
+                return Some((K::from_usize(self.back), value));
+            }
+        }
+        None
+    }
+}
+
+impl<K: TiKey, V> ExactSizeIterator for TiSecondaryMapIntoIter<K, V> {}
+
+impl<K: TiKey, V> FusedIterator for TiSecondaryMapIntoIter<K, V> {}
+
+#[cfg(test)]
+mod test {
+    use crate::test::Id;
+    use crate::{TiSecondaryMap, TiSecondaryMapEntry, TiVec};
+    use alloc::{format, rc::Rc, vec, vec::Vec};
+    use core::iter::FromIterator;
+
+    #[test]
+    fn insert_get_remove() {
+        let mut map: TiSecondaryMap<Id, u32> = TiSecondaryMap::new();
+        assert_eq!(map.insert(Id::from(70), 7), None);
+        assert_eq!(map.insert(Id::from(2), 2), None);
+        assert_eq!(map.insert(Id::from(70), 70), Some(7));
+        assert_eq!(map.len(), 2);
+        assert!(map.contains_key(Id::from(2)));
+        assert!(!map.contains_key(Id::from(3)));
+        assert!(!map.contains_key(Id::from(500)));
+        assert_eq!(map.get(Id::from(69)), None);
+        map[Id::from(2)] += 1;
+        assert_eq!(map.remove(Id::from(2)), Some(3));
+        assert_eq!(map.remove(Id::from(2)), None);
+        assert_eq!(format!("{:?}", map), "{Id(70): 70}");
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.get(Id::from(70)), None);
+    }
+
+    #[test]
+    fn entries() {
+        let mut map: TiSecondaryMap<Id, u32> = TiSecondaryMap::new();
+        *map.entry(Id::from(1)).or_default() += 5;
+        let _ = map.entry(Id::from(1)).and_modify(|value| *value *= 2);
+        assert_eq!(map[Id::from(1)], 10);
+        match map.entry(Id::from(1)) {
+            TiSecondaryMapEntry::Occupied(entry) => assert_eq!(entry.remove(), 10),
+            TiSecondaryMapEntry::Vacant(_) => unreachable!(),
+        }
+        match map.entry(Id::from(4)) {
+            TiSecondaryMapEntry::Occupied(_) => unreachable!(),
+            TiSecondaryMapEntry::Vacant(entry) => {
+                assert_eq!(entry.key(), Id::from(4));
+                let _ = entry.insert(4);
+            }
+        }
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get(Id::from(4)), Some(&4));
+    }
+
+    #[test]
+    fn iterate_and_convert() {
+        let vec: TiVec<Id, Option<u32>> = vec![None, Some(1), None, Some(3), None].into();
+        let mut map: TiSecondaryMap<Id, u32> = vec.clone().into();
+        assert_eq!(map.len(), 2);
+        assert_eq!(
+            map.iter_enumerated().collect::<Vec<_>>(),
+            [(Id::from(1), &1), (Id::from(3), &3)]
+        );
+        assert_eq!(
+            map.iter_enumerated()
+                .rev()
+                .map(|(key, _)| key)
+                .collect::<Vec<_>>(),
+            [Id::from(3), Id::from(1)]
+        );
+        for (_, value) in &mut map {
+            *value *= 10;
+        }
+        map.retain(|key, _| key != Id::from(1));
+        assert_eq!(map.clone(), TiSecondaryMap::from_iter([(Id::from(3), 30)]));
+        let vec: TiVec<Id, Option<u32>> = map.into();
+        assert_eq!(vec.raw, [None, None, None, Some(30), None]);
+    }
+
+    #[test]
+    fn drops_present_values() {
+        let value = Rc::new(());
+        let mut map: TiSecondaryMap<Id, Rc<()>> = TiSecondaryMap::new();
+        for index in [0, 3, 200] {
+            let _ = map.insert(Id::from(index), Rc::clone(&value));
+        }
+        let clone = map.clone();
+        assert_eq!(Rc::strong_count(&value), 7);
+        let mut iter = clone.into_iter();
+        assert_eq!(iter.next().map(|(key, _)| key), Some(Id::from(0)));
+        drop(iter);
+        drop(map.remove(Id::from(3)));
+        assert_eq!(Rc::strong_count(&value), 3);
+        drop(map);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+}
@@ -0,0 +1,665 @@
+use core::{
+    fmt,
+    iter::{self, FusedIterator},
+    marker::PhantomData,
+    mem, ops, slice,
+};
+
+use alloc::vec::{self, Vec};
+
+use crate::key::key_from_len;
+use crate::{TiKey, TiVec};
+
+/// A pre-allocated storage for a uniform data type
+/// that returns keys of the type `K` on insertion.
+///
+/// Removing a value leaves a vacant slot instead of shifting later values,
+/// so keys of the remaining values stay valid.
+/// Vacant keys are reused by later insertions.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::TiSlab;
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct ConnId(usize);
+///
+/// let mut connections: TiSlab<ConnId, &str> = TiSlab::new();
+/// let alice = connections.insert("alice");
+/// let bob = connections.insert("bob");
+/// assert_eq!(connections.remove(alice), "alice");
+/// assert_eq!(connections[bob], "bob");
+/// assert!(!connections.contains_key(alice));
+/// assert_eq!(connections.insert("carol"), alice);
+/// ```
+pub struct TiSlab<K, V> {
+    entries: Vec<Entry<V>>,
+    len: usize,
+    next_vacant: usize,
+
+    /// Ties the container to the key type `K`.
+    _marker: PhantomData<fn(K) -> K>,
+}
+
+#[derive(Clone)]
+enum Entry<V> {
+    Occupied(V),
+    /// Vacant slot with the index of the next vacant slot.
+    /// The last vacant slot points to the end of the entries.
+    Vacant(usize),
+}
+
+impl<K, V> TiSlab<K, V> {
+    /// Constructs a new, empty `TiSlab<K, V>`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Constructs a new, empty `TiSlab<K, V>` with the specified capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            len: 0,
+            next_vacant: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of values the slab can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity()
+    }
+
+    /// Reserves capacity for at least `additional` more values.
+    ///
+    /// Vacant slots are not taken into account.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.entries.reserve(additional)
+    }
+
+    /// Returns the number of stored values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the slab contains no values.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all values and vacant slots.
+    ///
+    /// Later insertions return keys starting from `K::from_usize(0)`.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.len = 0;
+        self.next_vacant = 0;
+    }
+
+    /// Returns the key that the next inserted value receives.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key can not be represented by the key type.
+    #[inline]
+    pub fn vacant_key(&self) -> K
+    where
+        K: TiKey,
+    {
+        key_from_len(self.next_vacant)
+    }
+
+    /// Inserts a value into the slab and returns its key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key can not be represented by the key type.
+    pub fn insert(&mut self, value: V) -> K
+    where
+        K: TiKey,
+    {
+        let key = self.vacant_key();
+        let index = self.next_vacant;
+        if index == self.entries.len() {
+            self.entries.push(Entry::Occupied(value));
+            self.next_vacant = index + 1;
+        } else {
+            match mem::replace(&mut self.entries[index], Entry::Occupied(value)) {
+                Entry::Vacant(next) => self.next_vacant = next,
+                Entry::Occupied(_) => unreachable!(),
+            }
+        }
+        self.len += 1;
+        key
+    }
+
+    /// Removes the value with the given key and returns it,
+    /// or [`None`] if the slot is vacant.
+    pub fn try_remove(&mut self, key: K) -> Option<V>
+    where
+        K: TiKey,
+    {
+        self.remove_at(key.to_usize())
+    }
+
+    fn remove_at(&mut self, index: usize) -> Option<V> {
+        let entry = self.entries.get_mut(index)?;
+        match mem::replace(entry, Entry::Vacant(self.next_vacant)) {
+            Entry::Occupied(value) => {
+                self.next_vacant = index;
+                self.len -= 1;
+                Some(value)
+            }
+            vacant => {
+                *entry = vacant;
+                None
+            }
+        }
+    }
+
+    /// Removes the value with the given key and returns it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slot is vacant.
+    pub fn remove(&mut self, key: K) -> V
+    where
+        K: TiKey,
+    {
+        let index = key.to_usize();
+        self.remove_at(index).unwrap_or_else(|| vacant_key(index))
+    }
+
+    /// Returns `true` if a value is stored with the given key.
+    #[inline]
+    pub fn contains_key(&self, key: K) -> bool
+    where
+        K: TiKey,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Returns a reference to the value with the given key,
+    /// or [`None`] if the slot is vacant.
+    #[inline]
+    pub fn get(&self, key: K) -> Option<&V>
+    where
+        K: TiKey,
+    {
+        match self.entries.get(key.to_usize()) {
+            Some(Entry::Occupied(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the value with the given key,
+    /// or [`None`] if the slot is vacant.
+    #[inline]
+    pub fn get_mut(&mut self, key: K) -> Option<&mut V>
+    where
+        K: TiKey,
+    {
+        match self.entries.get_mut(key.to_usize()) {
+            Some(Entry::Occupied(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Retains only the values specified by the predicate.
+    ///
+    /// Removed values leave vacant slots and keys of retained values do not change.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        K: TiKey,
+        F: FnMut(K, &mut V) -> bool,
+    {
+        for index in 0..self.entries.len() {
+            let keep = match self.entries[index] {
+                Entry::Occupied(ref mut value) => f(K::from_usize(index), value),
+                Entry::Vacant(_) => true,
+            };
+            if !keep {
+                self.entries[index] = Entry::Vacant(self.next_vacant);
+                self.next_vacant = index;
+                self.len -= 1;
+            }
+        }
+    }
+
+    /// Returns an iterator over occupied slots as key-value pairs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiSlab;
+    /// #[derive(Clone, Copy, Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut slab: TiSlab<Id, char> = TiSlab::new();
+    /// let a = slab.insert('a');
+    /// let _ = slab.insert('b');
+    /// let _ = slab.insert('c');
+    /// slab.remove(Id(1));
+    /// let mut iterator = slab.iter_enumerated();
+    /// assert_eq!(iterator.next(), Some((Id(0), &'a')));
+    /// assert_eq!(iterator.next(), Some((Id(2), &'c')));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    #[inline]
+    pub fn iter_enumerated(&self) -> TiSlabIter<'_, K, V> {
+        TiSlabIter {
+            entries: self.entries.iter().enumerate(),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over occupied slots as key-value pairs,
+    /// with mutable references to the values.
+    #[inline]
+    pub fn iter_mut_enumerated(&mut self) -> TiSlabIterMut<'_, K, V> {
+        TiSlabIterMut {
+            entries: self.entries.iter_mut().enumerate(),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Converts the slab into iterator over occupied slots as key-value pairs.
+    #[inline]
+    pub fn into_iter_enumerated(self) -> TiSlabIntoIter<K, V> {
+        TiSlabIntoIter {
+            entries: self.entries.into_iter().enumerate(),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Removes all values from the slab and returns them as key-value pairs.
+    ///
+    /// The slab is empty after the iterator is dropped,
+    /// and later insertions return keys starting from `K::from_usize(0)`.
+    #[inline]
+    pub fn drain(&mut self) -> TiSlabDrain<'_, K, V> {
+        let len = mem::replace(&mut self.len, 0);
+        self.next_vacant = 0;
+        TiSlabDrain {
+            entries: self.entries.drain(..).enumerate(),
+            len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Moves all values to the front of the slab, removes vacant slots and
+    /// shrinks the capacity as much as possible.
+    ///
+    /// The relative order of values is preserved.
+    /// Returns the remap from old keys to new keys,
+    /// with [`None`] for slots that were vacant.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::{TiSlab, TiVec};
+    /// #[derive(Clone, Copy, Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut slab: TiSlab<Id, char> = TiSlab::new();
+    /// let _ = slab.insert('a');
+    /// let _ = slab.insert('b');
+    /// let c = slab.insert('c');
+    /// slab.remove(Id(1));
+    /// let remap = slab.shrink_to_fit();
+    /// assert_eq!(remap.raw, [Some(Id(0)), None, Some(Id(1))]);
+    /// assert_eq!(slab[remap[c].unwrap()], 'c');
+    /// ```
+    pub fn shrink_to_fit(&mut self) -> TiVec<K, Option<K>>
+    where
+        K: TiKey,
+    {
+        let entries = mem::take(&mut self.entries);
+        let mut remap = TiVec::with_capacity(entries.len());
+        self.entries.reserve_exact(self.len);
+        for entry in entries {
+            match entry {
+                Entry::Occupied(value) => {
+                    remap.push(Some(K::from_usize(self.entries.len())));
+                    self.entries.push(Entry::Occupied(value));
+                }
+                Entry::Vacant(_) => remap.push(None),
+            }
+        }
+        self.next_vacant = self.entries.len();
+        remap
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn vacant_key(index: usize) -> ! {
+    panic!("key index {} is vacant in the slab", index)
+}
+
+impl<K, V> fmt::Debug for TiSlab<K, V>
+where
+    K: fmt::Debug + TiKey,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter_enumerated()).finish()
+    }
+}
+
+impl<K, V> ops::Index<K> for TiSlab<K, V>
+where
+    K: TiKey,
+{
+    type Output = V;
+
+    #[inline]
+    fn index(&self, key: K) -> &V {
+        let index = key.to_usize();
+        match self.entries.get(index) {
+            Some(Entry::Occupied(value)) => value,
+            _ => vacant_key(index),
+        }
+    }
+}
+
+impl<K, V> ops::IndexMut<K> for TiSlab<K, V>
+where
+    K: TiKey,
+{
+    #[inline]
+    fn index_mut(&mut self, key: K) -> &mut V {
+        let index = key.to_usize();
+        match self.entries.get_mut(index) {
+            Some(Entry::Occupied(value)) => value,
+            _ => vacant_key(index),
+        }
+    }
+}
+
+impl<K, V> Clone for TiSlab<K, V>
+where
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            len: self.len,
+            next_vacant: self.next_vacant,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> Default for TiSlab<K, V> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> IntoIterator for TiSlab<K, V>
+where
+    K: TiKey,
+{
+    type Item = (K, V);
+    type IntoIter = TiSlabIntoIter<K, V>;
+
+    #[inline]
+    fn into_iter(self) -> TiSlabIntoIter<K, V> {
+        self.into_iter_enumerated()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a TiSlab<K, V>
+where
+    K: TiKey,
+{
+    type Item = (K, &'a V);
+    type IntoIter = TiSlabIter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> TiSlabIter<'a, K, V> {
+        self.iter_enumerated()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut TiSlab<K, V>
+where
+    K: TiKey,
+{
+    type Item = (K, &'a mut V);
+    type IntoIter = TiSlabIterMut<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> TiSlabIterMut<'a, K, V> {
+        self.iter_mut_enumerated()
+    }
+}
+
+macro_rules! slab_iter {
+    (
+        $(#[$attr:meta])*
+        $name:ident<$($lt:lifetime,)? K, V>,
+        $entries:ty,
+        $item:ty,
+        |$entry:ident| $value:expr
+    ) => {
+        $(#[$attr])*
+        pub struct $name<$($lt,)? K, V> {
+            entries: iter::Enumerate<$entries>,
+            len: usize,
+            _marker: PhantomData<fn(K) -> K>,
+        }
+
+        #[allow(single_use_lifetimes)]
+        impl<$($lt,)? K, V> fmt::Debug for $name<$($lt,)? K, V> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($name)).field("len", &self.len).finish()
+            }
+        }
+
+        #[allow(single_use_lifetimes)]
+        impl<$($lt,)? K: TiKey, V> Iterator for $name<$($lt,)? K, V> {
+            type Item = $item;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                for (index, $entry) in &mut self.entries {
+                    if let Some(value) = $value {
+                        self.len -= 1;
+                        return Some((K::from_usize(index), value));
+                    }
+                }
+                None
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (self.len, Some(self.len))
+            }
+        }
+
+        #[allow(single_use_lifetimes)]
+        impl<$($lt,)? K: TiKey, V> DoubleEndedIterator for $name<$($lt,)? K, V> {
+            #[inline]
+            fn next_back(&mut self) -> Option<Self::Item> {
+                while let Some((index, $entry)) = self.entries.next_back() {
+                    if let Some(value) = $value {
+                        self.len -= 1;
+                        return Some((K::from_usize(index), value));
+                    }
+                }
+                None
+            }
+        }
+
+        #[allow(single_use_lifetimes)]
+        impl<$($lt,)? K: TiKey, V> ExactSizeIterator for $name<$($lt,)? K, V> {}
+
+        #[allow(single_use_lifetimes)]
+        impl<$($lt,)? K: TiKey, V> FusedIterator for $name<$($lt,)? K, V> {}
+    };
+}
+
+slab_iter!(
+    /// An iterator over occupied slots of a [`TiSlab`].
+    ///
+    /// This struct is created by the [`TiSlab::iter_enumerated`] method.
+    ///
+    /// [`TiSlab`]: struct.TiSlab.html
+    /// [`TiSlab::iter_enumerated`]: struct.TiSlab.html#method.iter_enumerated
+    TiSlabIter<'a, K, V>,
+    slice::Iter<'a, Entry<V>>,
+    (K, &'a V),
+    |entry| match entry {
+        Entry::Occupied(value) => Some(value),
+        Entry::Vacant(_) => None,
+    }
+);
+
+slab_iter!(
+    /// A mutable iterator over occupied slots of a [`TiSlab`].
+    ///
+    /// This struct is created by the [`TiSlab::iter_mut_enumerated`] method.
+    ///
+    /// [`TiSlab`]: struct.TiSlab.html
+    /// [`TiSlab::iter_mut_enumerated`]: struct.TiSlab.html#method.iter_mut_enumerated
+    TiSlabIterMut<'a, K, V>,
+    slice::IterMut<'a, Entry<V>>,
+    (K, &'a mut V),
+    |entry| match entry {
+        Entry::Occupied(value) => Some(value),
+        Entry::Vacant(_) => None,
+    }
+);
+
+slab_iter!(
+    /// An iterator that moves out of occupied slots of a [`TiSlab`].
+    ///
+    /// This struct is created by the [`TiSlab::into_iter_enumerated`] method.
+    ///
+    /// [`TiSlab`]: struct.TiSlab.html
+    /// [`TiSlab::into_iter_enumerated`]: struct.TiSlab.html#method.into_iter_enumerated
+    TiSlabIntoIter<K, V>,
+    vec::IntoIter<Entry<V>>,
+    (K, V),
+    |entry| match entry {
+        Entry::Occupied(value) => Some(value),
+        Entry::Vacant(_) => None,
+    }
+);
+
+slab_iter!(
+    /// A draining iterator over occupied slots of a [`TiSlab`].
+    ///
+    /// This struct is created by the [`TiSlab::drain`] method.
+    ///
+    /// [`TiSlab`]: struct.TiSlab.html
+    /// [`TiSlab::drain`]: struct.TiSlab.html#method.drain
+    TiSlabDrain<'a, K, V>,
+    vec::Drain<'a, Entry<V>>,
+    (K, V),
+    |entry| match entry {
+        Entry::Occupied(value) => Some(value),
+        Entry::Vacant(_) => None,
+    }
+);
+
+#[cfg(test)]
+mod test {
+    use crate::test::Id;
+    use crate::TiSlab;
+    use alloc::{format, vec::Vec};
+
+    #[test]
+    fn key_reuse() {
+        let mut slab: TiSlab<Id, u32> = TiSlab::with_capacity(4);
+        let keys: Vec<Id> = (0..4).map(|value| slab.insert(value)).collect();
+        assert_eq!(slab.remove(keys[1]), 1);
+        assert_eq!(slab.try_remove(keys[1]), None);
+        assert_eq!(slab.remove(keys[3]), 3);
+        assert_eq!(slab.len(), 2);
+        assert_eq!(slab.vacant_key(), keys[3]);
+        assert_eq!(slab.insert(30), keys[3]);
+        assert_eq!(slab.insert(10), keys[1]);
+        assert_eq!(slab.insert(4), Id::from(4));
+        assert_eq!(slab.get(keys[1]), Some(&10));
+        assert_eq!(
+            format!("{:?}", slab),
+            "{Id(0): 0, Id(1): 10, Id(2): 2, Id(3): 30, Id(4): 4}"
+        );
+    }
+
+    #[test]
+    fn retain_and_iterate() {
+        let mut slab: TiSlab<Id, u32> = (0..6).fold(TiSlab::new(), |mut slab, value| {
+            let _ = slab.insert(value);
+            slab
+        });
+        slab.retain(|key, value| {
+            *value *= 10;
+            usize::from(key) % 2 == 0
+        });
+        assert_eq!(slab.len(), 3);
+        let mut iter = slab.iter_enumerated();
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.next_back(), Some((Id::from(4), &40)));
+        assert_eq!(iter.next(), Some((Id::from(0), &0)));
+        assert_eq!(iter.len(), 1);
+        for (_, value) in &mut slab {
+            *value += 1;
+        }
+        let drained: Vec<_> = slab.drain().collect();
+        assert_eq!(
+            drained,
+            [(Id::from(0), 1), (Id::from(2), 21), (Id::from(4), 41)]
+        );
+        assert!(slab.is_empty());
+        assert_eq!(slab.insert(7), Id::from(0));
+    }
+
+    #[test]
+    fn shrink_remap() {
+        let mut slab: TiSlab<Id, u32> = TiSlab::new();
+        for value in 0..5 {
+            let _ = slab.insert(value);
+        }
+        let _ = slab.remove(Id::from(0));
+        let _ = slab.remove(Id::from(3));
+        let remap = slab.shrink_to_fit();
+        assert_eq!(
+            remap.raw,
+            [
+                None,
+                Some(Id::from(0)),
+                Some(Id::from(1)),
+                None,
+                Some(Id::from(2))
+            ]
+        );
+        assert_eq!(
+            slab.into_iter().collect::<Vec<_>>(),
+            [(Id::from(0), 1), (Id::from(1), 2), (Id::from(2), 4)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "key index 1 is vacant in the slab")]
+    fn remove_vacant() {
+        let mut slab: TiSlab<Id, u32> = TiSlab::new();
+        let _ = slab.insert(0);
+        let _ = slab.remove(Id::from(1));
+    }
+}
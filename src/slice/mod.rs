@@ -28,7 +28,8 @@ use alloc::{borrow::ToOwned, boxed::Box};
 #[cfg(feature = "serde")]
 use serde::ser::{Serialize, Serializer};
 
-use crate::{TiEnumerated, TiRangeBounds, TiSliceKeys, TiSliceMutMap, TiSliceRefMap};
+use crate::key::{assert_keys_in_range, key_from_len};
+use crate::{TiEnumerated, TiKey, TiRangeBounds, TiSliceKeys, TiSliceMutMap, TiSliceRefMap};
 
 #[cfg(any(feature = "alloc", feature = "std"))]
 use crate::TiVec;
@@ -53,7 +54,8 @@ pub use slice_index::TiSliceIndex;
 /// [`RangeToInclusive`] range types with `K` indices for `get`-methods and index expressions.
 /// The [`RangeFull`] trait is not currently supported.
 ///
-/// `TiSlice<K, V>` require the index to implement
+/// `TiSlice<K, V>` require the index to implement [`TiKey`] trait.
+/// It is implemented for all types implementing
 /// [`From<usize>`][`From`] and [`Into<usize>`][`Into`] traits.
 /// Their implementation can be easily done
 /// with [`derive_more`] crate and `#[derive(From, Into)]`.
@@ -92,7 +94,7 @@ pub use slice_index::TiSliceIndex;
 ///   It acts like `self.iter().position(...)`,
 ///   but instead of `usize` it returns index of type `K`.
 /// - [`rposition`] - Searches for an element in an iterator from the right,
-/// returning its index of type `K`.
+///   returning its index of type `K`.
 ///   It acts like `self.iter().rposition(...)`,
 ///   but instead of `usize` it returns index of type `K`.
 ///
@@ -125,6 +127,7 @@ pub use slice_index::TiSliceIndex;
 /// [`iter_mut_enumerated`]: #method.iter_mut_enumerated
 /// [`position`]: #method.position
 /// [`rposition`]: #method.rposition
+/// [`TiKey`]: trait.TiKey.html
 /// [`slice`]: https://doc.rust-lang.org/std/primitive.slice.html
 /// [`From`]: https://doc.rust-lang.org/std/convert/trait.From.html
 /// [`Into`]: https://doc.rust-lang.org/std/convert/trait.Into.html
@@ -217,9 +220,9 @@ impl<K, V> TiSlice<K, V> {
     #[inline]
     pub fn next_key(&self) -> K
     where
-        K: From<usize>,
+        K: TiKey,
     {
-        self.raw.len().into()
+        key_from_len(self.raw.len())
     }
 
     /// Returns `true` if the slice has a length of 0.
@@ -250,9 +253,10 @@ impl<K, V> TiSlice<K, V> {
     /// ```
     pub fn keys(&self) -> TiSliceKeys<K>
     where
-        K: From<usize>,
+        K: TiKey,
     {
-        (0..self.len()).map(Into::into)
+        assert_keys_in_range::<K>(self.len());
+        (0..self.len()).map(K::from_usize)
     }
 
     /// Returns the first element of the slice, or `None` if it is empty.
@@ -292,12 +296,12 @@ impl<K, V> TiSlice<K, V> {
     #[inline]
     pub fn first_key(&self) -> Option<K>
     where
-        K: From<usize>,
+        K: TiKey,
     {
         if self.is_empty() {
             None
         } else {
-            Some(0.into())
+            Some(K::from_usize(0))
         }
     }
 
@@ -323,9 +327,9 @@ impl<K, V> TiSlice<K, V> {
     #[inline]
     pub fn first_key_value(&self) -> Option<(K, &V)>
     where
-        K: From<usize>,
+        K: TiKey,
     {
-        self.raw.first().map(|first| (0.into(), first))
+        self.raw.first().map(|first| (K::from_usize(0), first))
     }
 
     /// Returns the first slice element index of type `K` and a mutable reference
@@ -353,9 +357,9 @@ impl<K, V> TiSlice<K, V> {
     #[inline]
     pub fn first_key_value_mut(&mut self) -> Option<(K, &mut V)>
     where
-        K: From<usize>,
+        K: TiKey,
     {
-        self.raw.first_mut().map(|first| (0.into(), first))
+        self.raw.first_mut().map(|first| (K::from_usize(0), first))
     }
 
     /// Returns the first and all the rest of the elements of the slice, or `None` if it is empty.
@@ -443,12 +447,12 @@ impl<K, V> TiSlice<K, V> {
     #[inline]
     pub fn last_key(&self) -> Option<K>
     where
-        K: From<usize>,
+        K: TiKey,
     {
         if self.is_empty() {
             None
         } else {
-            Some((self.len() - 1).into())
+            Some(K::from_usize(self.len() - 1))
         }
     }
 
@@ -474,10 +478,10 @@ impl<K, V> TiSlice<K, V> {
     #[inline]
     pub fn last_key_value(&self) -> Option<(K, &V)>
     where
-        K: From<usize>,
+        K: TiKey,
     {
         let len = self.len();
-        self.raw.last().map(|last| ((len - 1).into(), last))
+        self.raw.last().map(|last| (K::from_usize(len - 1), last))
     }
 
     /// Returns the last slice element index of type `K` and a mutable reference
@@ -505,10 +509,12 @@ impl<K, V> TiSlice<K, V> {
     #[inline]
     pub fn last_key_value_mut(&mut self) -> Option<(K, &mut V)>
     where
-        K: From<usize>,
+        K: TiKey,
     {
         let len = self.len();
-        self.raw.last_mut().map(|last| ((len - 1).into(), last))
+        self.raw
+            .last_mut()
+            .map(|last| (K::from_usize(len - 1), last))
     }
 
     /// Returns a reference to an element or subslice
@@ -611,9 +617,9 @@ impl<K, V> TiSlice<K, V> {
     #[inline]
     pub fn swap(&mut self, a: K, b: K)
     where
-        usize: From<K>,
+        K: TiKey,
     {
-        self.raw.swap(a.into(), b.into())
+        self.raw.swap(a.to_usize(), b.to_usize())
     }
 
     /// Reverses the order of elements in the slice, in place.
@@ -662,12 +668,13 @@ impl<K, V> TiSlice<K, V> {
     #[inline]
     pub fn iter_enumerated(&self) -> TiEnumerated<Iter<'_, V>, K, &V>
     where
-        K: From<usize>,
+        K: TiKey,
     {
+        assert_keys_in_range::<K>(self.len());
         self.raw
             .iter()
             .enumerate()
-            .map(|(key, value)| (key.into(), value))
+            .map(|(key, value)| (K::from_usize(key), value))
     }
 
     /// Returns an iterator that allows modifying each value.
@@ -704,12 +711,13 @@ impl<K, V> TiSlice<K, V> {
     #[inline]
     pub fn iter_mut_enumerated(&mut self) -> TiEnumerated<IterMut<'_, V>, K, &mut V>
     where
-        K: From<usize>,
+        K: TiKey,
     {
+        assert_keys_in_range::<K>(self.len());
         self.raw
             .iter_mut()
             .enumerate()
-            .map(|(key, value)| (key.into(), value))
+            .map(|(key, value)| (K::from_usize(key), value))
     }
 
     /// Searches for an element in an iterator, returning its index of type `K`.
@@ -738,10 +746,11 @@ impl<K, V> TiSlice<K, V> {
     #[inline]
     pub fn position<P>(&self, predicate: P) -> Option<K>
     where
-        K: From<usize>,
+        K: TiKey,
         P: FnMut(&V) -> bool,
     {
-        self.raw.iter().position(predicate).map(Into::into)
+        assert_keys_in_range::<K>(self.len());
+        self.raw.iter().position(predicate).map(K::from_usize)
     }
 
     /// Searches for an element in an iterator from the right, returning its index of type `K`.
@@ -770,10 +779,11 @@ impl<K, V> TiSlice<K, V> {
     #[inline]
     pub fn rposition<P>(&self, predicate: P) -> Option<K>
     where
-        K: From<usize>,
+        K: TiKey,
         P: FnMut(&V) -> bool,
     {
-        self.raw.iter().rposition(predicate).map(Into::into)
+        assert_keys_in_range::<K>(self.len());
+        self.raw.iter().rposition(predicate).map(K::from_usize)
     }
 
     /// Returns an iterator over all contiguous windows of length
@@ -892,9 +902,9 @@ impl<K, V> TiSlice<K, V> {
     #[inline]
     pub fn split_at(&self, mid: K) -> (&Self, &Self)
     where
-        usize: From<K>,
+        K: TiKey,
     {
-        let (left, right) = self.raw.split_at(mid.into());
+        let (left, right) = self.raw.split_at(mid.to_usize());
         (left.as_ref(), right.as_ref())
     }
 
@@ -906,9 +916,9 @@ impl<K, V> TiSlice<K, V> {
     #[inline]
     pub fn split_at_mut(&mut self, mid: K) -> (&mut Self, &mut Self)
     where
-        usize: From<K>,
+        K: TiKey,
     {
-        let (left, right) = self.raw.split_at_mut(mid.into());
+        let (left, right) = self.raw.split_at_mut(mid.to_usize());
         (left.as_mut(), right.as_mut())
     }
 
@@ -1076,12 +1086,12 @@ impl<K, V> TiSlice<K, V> {
     pub fn binary_search(&self, x: &V) -> Result<K, K>
     where
         V: Ord,
-        K: From<usize>,
+        K: TiKey,
     {
         self.raw
             .binary_search(x)
-            .map(Into::into)
-            .map_err(Into::into)
+            .map(K::from_usize)
+            .map_err(key_from_len)
     }
 
     /// Binary searches this sorted slice with a comparator function.
@@ -1093,12 +1103,12 @@ impl<K, V> TiSlice<K, V> {
     pub fn binary_search_by<'a, F>(&'a self, f: F) -> Result<K, K>
     where
         F: FnMut(&'a V) -> Ordering,
-        K: From<usize>,
+        K: TiKey,
     {
         self.raw
             .binary_search_by(f)
-            .map(Into::into)
-            .map_err(Into::into)
+            .map(K::from_usize)
+            .map_err(key_from_len)
     }
 
     /// Binary searches this sorted slice with a key extraction function.
@@ -1111,12 +1121,12 @@ impl<K, V> TiSlice<K, V> {
     where
         F: FnMut(&'a V) -> B,
         B: Ord,
-        K: From<usize>,
+        K: TiKey,
     {
         self.raw
             .binary_search_by_key(b, f)
-            .map(Into::into)
-            .map_err(Into::into)
+            .map(K::from_usize)
+            .map_err(key_from_len)
     }
 
     /// Sorts the slice, but may not preserve the order of equal elements.
@@ -1171,9 +1181,9 @@ impl<K, V> TiSlice<K, V> {
     /// [`slice::rotate_left`]: https://doc.rust-lang.org/std/primitive.slice.html#method.rotate_left
     pub fn rotate_left(&mut self, mid: K)
     where
-        usize: From<K>,
+        K: TiKey,
     {
-        self.raw.rotate_left(mid.into())
+        self.raw.rotate_left(mid.to_usize())
     }
 
     /// Rotates the slice in-place such that the first `self.next_key() - k`
@@ -1186,9 +1196,9 @@ impl<K, V> TiSlice<K, V> {
     /// [`slice::rotate_right`]: https://doc.rust-lang.org/std/primitive.slice.html#method.rotate_right
     pub fn rotate_right(&mut self, k: K)
     where
-        usize: From<K>,
+        K: TiKey,
     {
-        self.raw.rotate_right(k.into())
+        self.raw.rotate_right(k.to_usize())
     }
 
     /// Copies the elements from `src` into `self`.
@@ -1225,9 +1235,9 @@ impl<K, V> TiSlice<K, V> {
     where
         R: TiRangeBounds<K>,
         V: Copy,
-        usize: From<K>,
+        K: TiKey,
     {
-        self.raw.copy_within(src.into_range(), dest.into())
+        self.raw.copy_within(src.into_range(), dest.to_usize())
     }
 
     /// Swaps all elements in `self` with those in `other`.
@@ -1451,7 +1461,7 @@ impl<K> TiSlice<K, u8> {
 
 impl<K, V> fmt::Debug for TiSlice<K, V>
 where
-    K: fmt::Debug + From<usize>,
+    K: fmt::Debug + TiKey,
     V: fmt::Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -1607,6 +1617,7 @@ impl<K, V: Serialize> Serialize for TiSlice<K, V> {
 }
 
 #[cfg(test)]
+#[allow(unused_qualifications)]
 mod test {
     use crate::{test::Id, TiSlice};
 
@@ -1,4 +1,4 @@
-use crate::{TiRangeBounds, TiSlice};
+use crate::{TiKey, TiRangeBounds, TiSlice};
 use core::ops;
 
 mod private {
@@ -70,37 +70,37 @@ pub trait TiSliceIndex<K, V>: private::Sealed<K> {
 
 impl<K, V> TiSliceIndex<K, V> for K
 where
-    usize: From<K>,
+    K: TiKey,
 {
     type Output = V;
 
     #[inline]
     fn get(self, slice: &TiSlice<K, V>) -> Option<&Self::Output> {
-        slice.raw.get(usize::from(self))
+        slice.raw.get(self.to_usize())
     }
 
     #[inline]
     fn get_mut(self, slice: &mut TiSlice<K, V>) -> Option<&mut Self::Output> {
-        slice.raw.get_mut(usize::from(self))
+        slice.raw.get_mut(self.to_usize())
     }
 
     #[inline]
     unsafe fn get_unchecked(self, slice: &TiSlice<K, V>) -> &Self::Output {
-        slice.raw.get_unchecked(usize::from(self))
+        slice.raw.get_unchecked(self.to_usize())
     }
 
     #[inline]
     unsafe fn get_unchecked_mut(self, slice: &mut TiSlice<K, V>) -> &mut Self::Output {
-        slice.raw.get_unchecked_mut(usize::from(self))
+        slice.raw.get_unchecked_mut(self.to_usize())
     }
 
     #[inline]
     fn index(self, slice: &TiSlice<K, V>) -> &Self::Output {
-        &slice.raw[usize::from(self)]
+        &slice.raw[self.to_usize()]
     }
 
     fn index_mut(self, slice: &mut TiSlice<K, V>) -> &mut Self::Output {
-        &mut slice.raw[usize::from(self)]
+        &mut slice.raw[self.to_usize()]
     }
 }
 
@@ -108,7 +108,7 @@ macro_rules! impl_ti_slice_range {
     ($ty:ty) => {
         impl<K, V> TiSliceIndex<K, V> for $ty
         where
-            usize: From<K>,
+            K: TiKey,
         {
             type Output = TiSlice<K, V>;
 
@@ -0,0 +1,1273 @@
+use core::{
+    borrow::{Borrow, BorrowMut},
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::{FromIterator, FusedIterator},
+    mem, ops, slice,
+};
+
+use alloc::{
+    boxed::Box,
+    vec::{self, Splice, Vec},
+};
+
+#[cfg(feature = "serde")]
+use serde::ser::{Serialize, Serializer};
+
+#[cfg(any(feature = "serde-alloc", feature = "serde-std"))]
+use serde::de::{Deserialize, Deserializer};
+
+use crate::range::range_to_bounds;
+use crate::{
+    TiArrayVec, TiArrayVecDrain, TiArrayVecIntoIter, TiEnumerated, TiKey, TiRangeBounds, TiSlice,
+    TiVec,
+};
+
+/// A growable vector type that stores up to `N` elements inline
+/// and only accepts keys of the type `K`.
+///
+/// `TiSmallVec<K, V, N>` mirrors the [`TiVec`] API.
+/// It keeps elements in an inline [`TiArrayVec<K, V, N>`][`TiArrayVec`]
+/// until they no longer fit and then moves them to a heap-allocated [`TiVec<K, V>`][`TiVec`].
+/// It dereferences to [`TiSlice<K, V>`][`TiSlice`],
+/// so all slice methods are available with `K` used for element indices.
+///
+/// `TiSmallVec<K, V, N>` can be converted to [`TiVec<K, V>`][`TiVec`] and back
+/// using [`From`] and [`Into`].
+///
+/// Added methods:
+/// - [`spilled`] - Returns `true` if the elements are stored on the heap.
+/// - [`push_and_get_key`] - Appends an element to the back of a collection
+///   and returns its index of type `K`.
+/// - [`pop_key_value`] - Removes the last element from a vector and returns it
+///   with its index of type `K`, or [`None`] if the vector is empty.
+/// - [`drain_enumerated`] - Creates a draining iterator that removes the specified
+///   range in the vector and yields the current count and the removed items.
+///   It acts like `self.drain(range).enumerate()`,
+///   but instead of `usize` it returns index of type `K`.
+/// - [`into_iter_enumerated`] - Converts the vector into iterator over all key-value pairs
+///   with `K` used for iteration indices.
+///   It acts like `self.into_iter().enumerate()`,
+///   but use `K` instead of `usize` for iteration indices.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::TiSmallVec;
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, From, Into, Eq, PartialEq)]
+/// struct NodeId(usize);
+///
+/// let mut successors: TiSmallVec<NodeId, NodeId, 2> = TiSmallVec::new();
+/// successors.push(NodeId(4));
+/// successors.push(NodeId(7));
+/// assert!(!successors.spilled());
+/// successors.insert(NodeId(1), NodeId(5));
+/// assert!(successors.spilled());
+/// assert_eq!(successors[NodeId(1)], NodeId(5));
+/// ```
+///
+/// [`spilled`]: #method.spilled
+/// [`push_and_get_key`]: #method.push_and_get_key
+/// [`pop_key_value`]: #method.pop_key_value
+/// [`drain_enumerated`]: #method.drain_enumerated
+/// [`into_iter_enumerated`]: #method.into_iter_enumerated
+/// [`TiArrayVec`]: struct.TiArrayVec.html
+/// [`TiSlice`]: struct.TiSlice.html
+/// [`TiVec`]: struct.TiVec.html
+/// [`From`]: https://doc.rust-lang.org/std/convert/trait.From.html
+/// [`Into`]: https://doc.rust-lang.org/std/convert/trait.Into.html
+pub struct TiSmallVec<K, V, const N: usize> {
+    repr: Repr<K, V, N>,
+}
+
+enum Repr<K, V, const N: usize> {
+    Inline(TiArrayVec<K, V, N>),
+    Heap(TiVec<K, V>),
+}
+
+impl<K, V, const N: usize> TiSmallVec<K, V, N> {
+    /// Constructs a new, empty `TiSmallVec<K, V, N>`.
+    ///
+    /// See [`Vec::new`] for more details.
+    ///
+    /// [`Vec::new`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.new
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            repr: Repr::Inline(TiArrayVec::new()),
+        }
+    }
+
+    /// Constructs a new, empty `TiSmallVec<K, V, N>` with the specified capacity.
+    ///
+    /// The elements are stored inline if `capacity` is less than or equal to `N`.
+    ///
+    /// See [`Vec::with_capacity`] for more details.
+    ///
+    /// [`Vec::with_capacity`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.with_capacity
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        if capacity <= N {
+            Self::new()
+        } else {
+            Self {
+                repr: Repr::Heap(TiVec::with_capacity(capacity)),
+            }
+        }
+    }
+
+    /// Returns `true` if the elements are stored on the heap.
+    #[inline]
+    pub fn spilled(&self) -> bool {
+        match self.repr {
+            Repr::Inline(_) => false,
+            Repr::Heap(_) => true,
+        }
+    }
+
+    /// Returns the number of elements the vector can hold without
+    /// reallocating.
+    ///
+    /// See [`Vec::capacity`] for more details.
+    ///
+    /// [`Vec::capacity`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.capacity
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        match self.repr {
+            Repr::Inline(_) => N,
+            Repr::Heap(ref vec) => vec.capacity(),
+        }
+    }
+
+    /// Moves the elements to the heap if they do not fit inline
+    /// after `additional` elements are added and returns the heap vector.
+    fn spill(&mut self, additional: usize) -> &mut TiVec<K, V> {
+        if let Repr::Inline(ref mut inline) = self.repr {
+            let len = inline.len();
+            let capacity = len
+                .checked_add(additional)
+                .expect("capacity overflow")
+                .max(N * 2);
+            let mut vec = Vec::with_capacity(capacity);
+            vec.extend(mem::take(inline));
+            self.repr = Repr::Heap(vec.into());
+        }
+        match self.repr {
+            Repr::Heap(ref mut vec) => vec,
+            Repr::Inline(_) => unreachable!(),
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more elements to be inserted
+    /// in the given `TiSmallVec<K, V, N>`.
+    ///
+    /// Moves the elements to the heap if they do not fit inline.
+    ///
+    /// See [`Vec::reserve`] for more details.
+    ///
+    /// [`Vec::reserve`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.reserve
+    pub fn reserve(&mut self, additional: usize) {
+        match self.repr {
+            Repr::Inline(ref inline) if additional <= inline.remaining_capacity() => {}
+            Repr::Inline(_) => {
+                let _ = self.spill(additional);
+            }
+            Repr::Heap(ref mut vec) => vec.reserve(additional),
+        }
+    }
+
+    /// Reserves the minimum capacity for exactly `additional` more elements to
+    /// be inserted in the given `TiSmallVec<K, V, N>`.
+    ///
+    /// Moves the elements to the heap if they do not fit inline.
+    ///
+    /// See [`Vec::reserve_exact`] for more details.
+    ///
+    /// [`Vec::reserve_exact`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.reserve_exact
+    pub fn reserve_exact(&mut self, additional: usize) {
+        match self.repr {
+            Repr::Inline(ref inline) if additional <= inline.remaining_capacity() => {}
+            Repr::Inline(_) => {
+                let vec = self.spill(0);
+                vec.reserve_exact(additional);
+            }
+            Repr::Heap(ref mut vec) => vec.reserve_exact(additional),
+        }
+    }
+
+    /// Shrinks the capacity of the vector as much as possible.
+    ///
+    /// Moves the elements back inline if they fit.
+    ///
+    /// See [`Vec::shrink_to_fit`] for more details.
+    ///
+    /// [`Vec::shrink_to_fit`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.shrink_to_fit
+    pub fn shrink_to_fit(&mut self) {
+        if let Repr::Heap(ref mut vec) = self.repr {
+            if vec.len() <= N {
+                let inline = mem::take(vec).into_iter().collect();
+                self.repr = Repr::Inline(inline);
+            } else {
+                vec.shrink_to_fit();
+            }
+        }
+    }
+
+    /// Converts the vector into [`Box<TiSlice<K, V>>`][`Box`].
+    ///
+    /// See [`Vec::into_boxed_slice`] for more details.
+    ///
+    /// [`Vec::into_boxed_slice`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.into_boxed_slice
+    /// [`Box`]: https://doc.rust-lang.org/std/boxed/struct.Box.html
+    pub fn into_boxed_slice(self) -> Box<TiSlice<K, V>> {
+        TiVec::from(self).into_boxed_slice()
+    }
+
+    /// Shortens the vector, keeping the first `len` elements and dropping
+    /// the rest.
+    ///
+    /// See [`Vec::truncate`] for more details.
+    ///
+    /// [`Vec::truncate`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.truncate
+    pub fn truncate(&mut self, len: usize) {
+        match self.repr {
+            Repr::Inline(ref mut inline) => inline.truncate(len),
+            Repr::Heap(ref mut vec) => vec.truncate(len),
+        }
+    }
+
+    /// Extracts a slice containing the entire vector.
+    ///
+    /// See [`Vec::as_slice`] for more details.
+    ///
+    /// [`Vec::as_slice`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.as_slice
+    #[inline]
+    pub fn as_slice(&self) -> &TiSlice<K, V> {
+        match self.repr {
+            Repr::Inline(ref inline) => inline.as_slice(),
+            Repr::Heap(ref vec) => vec.as_slice(),
+        }
+    }
+
+    /// Extracts a mutable slice of the entire vector.
+    ///
+    /// See [`Vec::as_mut_slice`] for more details.
+    ///
+    /// [`Vec::as_mut_slice`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.as_mut_slice
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut TiSlice<K, V> {
+        match self.repr {
+            Repr::Inline(ref mut inline) => inline.as_mut_slice(),
+            Repr::Heap(ref mut vec) => vec.as_mut_slice(),
+        }
+    }
+
+    /// Returns a raw pointer to the vector's buffer.
+    ///
+    /// See [`Vec::as_ptr`] for more details.
+    ///
+    /// [`Vec::as_ptr`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.as_ptr
+    #[inline]
+    pub fn as_ptr(&self) -> *const V {
+        match self.repr {
+            Repr::Inline(ref inline) => inline.as_ptr(),
+            Repr::Heap(ref vec) => vec.as_ptr(),
+        }
+    }
+
+    /// Returns an unsafe mutable pointer to the vector's buffer.
+    ///
+    /// See [`Vec::as_mut_ptr`] for more details.
+    ///
+    /// [`Vec::as_mut_ptr`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.as_mut_ptr
+    #[inline]
+    pub fn as_mut_ptr(&mut self) -> *mut V {
+        match self.repr {
+            Repr::Inline(ref mut inline) => inline.as_mut_ptr(),
+            Repr::Heap(ref mut vec) => vec.as_mut_ptr(),
+        }
+    }
+
+    /// Forces the length of the vector to `new_len`.
+    ///
+    /// See [`Vec::set_len`] for more details.
+    ///
+    /// # Safety
+    ///
+    /// `new_len` must be less than or equal to [`capacity`]
+    /// and the elements at `old_len..new_len` must be initialized.
+    ///
+    /// [`Vec::set_len`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.set_len
+    /// [`capacity`]: #method.capacity
+    #[inline]
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        match self.repr {
+            Repr::Inline(ref mut inline) => inline.set_len(new_len),
+            Repr::Heap(ref mut vec) => vec.set_len(new_len),
+        }
+    }
+
+    /// Removes an element from the vector and returns it.
+    ///
+    /// The removed element is replaced by the last element of the vector.
+    ///
+    /// See [`Vec::swap_remove`] for more details.
+    ///
+    /// [`Vec::swap_remove`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.swap_remove
+    #[inline]
+    pub fn swap_remove(&mut self, index: K) -> V
+    where
+        K: TiKey,
+    {
+        match self.repr {
+            Repr::Inline(ref mut inline) => inline.swap_remove(index),
+            Repr::Heap(ref mut vec) => vec.swap_remove(index),
+        }
+    }
+
+    /// Inserts an element at position `index` within the vector, shifting all
+    /// elements after it to the right.
+    ///
+    /// Moves the elements to the heap if they do not fit inline.
+    ///
+    /// See [`Vec::insert`] for more details.
+    ///
+    /// [`Vec::insert`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.insert
+    pub fn insert(&mut self, index: K, element: V)
+    where
+        K: TiKey,
+    {
+        match self.repr {
+            Repr::Inline(ref mut inline) if !inline.is_full() => inline.insert(index, element),
+            _ => self.spill(1).insert(index, element),
+        }
+    }
+
+    /// Removes and returns the element at position `index` within the vector,
+    /// shifting all elements after it to the left.
+    ///
+    /// See [`Vec::remove`] for more details.
+    ///
+    /// [`Vec::remove`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.remove
+    pub fn remove(&mut self, index: K) -> V
+    where
+        K: TiKey,
+    {
+        match self.repr {
+            Repr::Inline(ref mut inline) => inline.remove(index),
+            Repr::Heap(ref mut vec) => vec.remove(index),
+        }
+    }
+
+    /// Retains only the elements specified by the predicate.
+    ///
+    /// See [`Vec::retain`] for more details.
+    ///
+    /// [`Vec::retain`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.retain
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&V) -> bool,
+    {
+        match self.repr {
+            Repr::Inline(ref mut inline) => inline.retain(f),
+            Repr::Heap(ref mut vec) => vec.retain(f),
+        }
+    }
+
+    /// Removes all but the first of consecutive elements in the vector that resolve to the same
+    /// key.
+    ///
+    /// See [`Vec::dedup_by_key`] for more details.
+    ///
+    /// [`Vec::dedup_by_key`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.dedup_by_key
+    #[inline]
+    pub fn dedup_by_key<F, K2>(&mut self, mut key: F)
+    where
+        F: FnMut(&mut V) -> K2,
+        K2: PartialEq,
+    {
+        self.dedup_by(|a, b| key(a) == key(b))
+    }
+
+    /// Removes all but the first of consecutive elements in the vector satisfying a given equality
+    /// relation.
+    ///
+    /// See [`Vec::dedup_by`] for more details.
+    ///
+    /// [`Vec::dedup_by`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.dedup_by
+    pub fn dedup_by<F>(&mut self, same_bucket: F)
+    where
+        F: FnMut(&mut V, &mut V) -> bool,
+    {
+        match self.repr {
+            Repr::Inline(ref mut inline) => inline.dedup_by(same_bucket),
+            Repr::Heap(ref mut vec) => vec.dedup_by(same_bucket),
+        }
+    }
+
+    /// Appends an element to the back of a collection.
+    ///
+    /// Moves the elements to the heap if they do not fit inline.
+    ///
+    /// See [`Vec::push`] for more details.
+    ///
+    /// [`Vec::push`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.push
+    #[inline]
+    pub fn push(&mut self, value: V) {
+        match self.repr {
+            Repr::Inline(ref mut inline) => {
+                if let Err(err) = inline.try_push(value) {
+                    self.spill(1).push(err.element());
+                }
+            }
+            Repr::Heap(ref mut vec) => vec.push(value),
+        }
+    }
+
+    /// Appends an element to the back of a collection and returns its index of type `K`.
+    ///
+    /// It acts like `{ vec.push(...); vec.last_key().unwrap() }`,
+    /// but is optimized better.
+    ///
+    /// See [`Vec::push`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiSmallVec;
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut vec: TiSmallVec<Id, usize, 1> = TiSmallVec::new();
+    /// assert_eq!(vec.push_and_get_key(8), Id(0));
+    /// assert_eq!(vec.push_and_get_key(16), Id(1));
+    /// ```
+    ///
+    /// [`Vec::push`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.push
+    #[inline]
+    pub fn push_and_get_key(&mut self, value: V) -> K
+    where
+        K: TiKey,
+    {
+        let key = self.next_key();
+        self.push(value);
+        key
+    }
+
+    /// Removes the last element from a vector and returns it, or [`None`] if it
+    /// is empty.
+    ///
+    /// See [`Vec::pop`] for more details.
+    ///
+    /// [`Vec::pop`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.pop
+    #[inline]
+    pub fn pop(&mut self) -> Option<V> {
+        match self.repr {
+            Repr::Inline(ref mut inline) => inline.pop(),
+            Repr::Heap(ref mut vec) => vec.pop(),
+        }
+    }
+
+    /// Removes the last element from a vector and returns it with
+    /// its index of type `K`, or [`None`] if the vector is empty.
+    ///
+    /// See [`Vec::pop`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiSmallVec;
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut vec: TiSmallVec<Id, usize, 2> = vec![1, 2, 4].into_iter().collect();
+    /// assert_eq!(vec.pop_key_value(), Some((Id(2), 4)));
+    /// assert_eq!(vec.pop_key_value(), Some((Id(1), 2)));
+    /// ```
+    ///
+    /// [`Vec::pop`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.pop
+    #[inline]
+    pub fn pop_key_value(&mut self) -> Option<(K, V)>
+    where
+        K: TiKey,
+    {
+        match self.repr {
+            Repr::Inline(ref mut inline) => inline.pop_key_value(),
+            Repr::Heap(ref mut vec) => vec.pop_key_value(),
+        }
+    }
+
+    /// Moves all the elements of `other` into `Self`, leaving `other` empty.
+    ///
+    /// See [`Vec::append`] for more details.
+    ///
+    /// [`Vec::append`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.append
+    pub fn append(&mut self, other: &mut Self) {
+        self.extend(mem::take(other))
+    }
+
+    /// Creates a draining iterator that removes the specified range in the vector
+    /// and yields the removed items.
+    ///
+    /// See [`Vec::drain`] for more details.
+    ///
+    /// [`Vec::drain`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.drain
+    #[inline]
+    pub fn drain<R>(&mut self, range: R) -> TiSmallVecDrain<'_, V>
+    where
+        R: TiRangeBounds<K>,
+    {
+        TiSmallVecDrain {
+            repr: match self.repr {
+                Repr::Inline(ref mut inline) => DrainRepr::Inline(inline.drain(range)),
+                Repr::Heap(ref mut vec) => DrainRepr::Heap(vec.drain(range)),
+            },
+        }
+    }
+
+    /// Creates a draining iterator that removes the specified
+    /// range in the vector and yields the current count and the removed items.
+    ///
+    /// It acts like `self.drain(range).enumerate()`,
+    /// but instead of `usize` it returns index of type `K`.
+    ///
+    /// Note that the indices started from `K::from_usize(0)`,
+    /// regardless of the range starting point.
+    ///
+    /// See [`Vec::drain`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::{TiSlice, TiSmallVec};
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut vec: TiSmallVec<Id, usize, 4> = vec![1, 2, 4].into_iter().collect();
+    /// {
+    ///     let mut iterator = vec.drain_enumerated(Id(1)..);
+    ///     assert_eq!(iterator.next(), Some((Id(0), 2)));
+    ///     assert_eq!(iterator.next(), Some((Id(1), 4)));
+    ///     assert_eq!(iterator.next(), None);
+    /// }
+    /// assert_eq!(vec.as_slice(), TiSlice::from_ref(&[1]));
+    /// ```
+    ///
+    /// [`Vec::drain`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.drain
+    #[inline]
+    pub fn drain_enumerated<R>(&mut self, range: R) -> TiEnumerated<TiSmallVecDrain<'_, V>, K, V>
+    where
+        K: TiKey,
+        R: TiRangeBounds<K>,
+    {
+        self.drain(range)
+            .enumerate()
+            .map(|(key, value)| (K::from_usize(key), value))
+    }
+
+    /// Clears the vector, removing all values.
+    ///
+    /// See [`Vec::clear`] for more details.
+    ///
+    /// [`Vec::clear`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.clear
+    #[inline]
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+
+    /// Returns the number of elements in the vector, also referred to
+    /// as its 'length'.
+    ///
+    /// See [`Vec::len`] for more details.
+    ///
+    /// [`Vec::len`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.len
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    /// Returns `true` if the vector contains no elements.
+    ///
+    /// See [`Vec::is_empty`] for more details.
+    ///
+    /// [`Vec::is_empty`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.is_empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
+    }
+
+    /// Splits the collection into two at the given index.
+    ///
+    /// See [`Vec::split_off`] for more details.
+    ///
+    /// [`Vec::split_off`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.split_off
+    #[inline]
+    #[must_use = "use `.truncate()` if you don't need the other half"]
+    pub fn split_off(&mut self, at: K) -> Self
+    where
+        K: TiKey,
+    {
+        self.drain(at..).collect()
+    }
+
+    /// Resizes the `TiSmallVec` in-place so that `len` is equal to `new_len`.
+    ///
+    /// See [`Vec::resize_with`] for more details.
+    ///
+    /// [`Vec::resize_with`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.resize_with
+    pub fn resize_with<F>(&mut self, new_len: usize, mut f: F)
+    where
+        F: FnMut() -> V,
+    {
+        let len = self.len();
+        if new_len > len {
+            self.reserve(new_len - len);
+            self.extend((len..new_len).map(|_| f()));
+        } else {
+            self.truncate(new_len);
+        }
+    }
+
+    /// Resizes the `TiSmallVec` in-place so that `len` is equal to `new_len`.
+    ///
+    /// See [`Vec::resize`] for more details.
+    ///
+    /// [`Vec::resize`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.resize
+    pub fn resize(&mut self, new_len: usize, value: V)
+    where
+        V: Clone,
+    {
+        self.resize_with(new_len, || value.clone())
+    }
+
+    /// Clones and appends all elements in a slice to the `TiSmallVec`.
+    ///
+    /// See [`Vec::extend_from_slice`] for more details.
+    ///
+    /// [`Vec::extend_from_slice`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.extend_from_slice
+    pub fn extend_from_slice(&mut self, other: &TiSlice<K, V>)
+    where
+        V: Clone,
+    {
+        self.reserve(other.len());
+        match self.repr {
+            Repr::Inline(ref mut inline) => inline.extend_from_slice(other),
+            Repr::Heap(ref mut vec) => vec.extend_from_slice(other),
+        }
+    }
+
+    /// Removes consecutive repeated elements in the vector according to the
+    /// [`PartialEq`] trait implementation.
+    ///
+    /// See [`Vec::dedup`] for more details.
+    ///
+    /// [`PartialEq`]: https://doc.rust-lang.org/std/cmp/trait.PartialEq.html
+    /// [`Vec::dedup`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.dedup
+    #[inline]
+    pub fn dedup(&mut self)
+    where
+        V: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b)
+    }
+
+    /// Creates a splicing iterator that replaces the specified range in the vector
+    /// with the given `replace_with` iterator and yields the removed items.
+    /// `replace_with` does not need to be the same length as `range`.
+    ///
+    /// Keeps the elements inline if they still fit after the replacement
+    /// and moves them to the heap otherwise.
+    /// For inline elements the range is replaced immediately
+    /// and the returned iterator owns the removed items.
+    ///
+    /// See [`Vec::splice`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::{TiSlice, TiSmallVec};
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut vec: TiSmallVec<Id, usize, 4> = vec![1, 2, 3].into_iter().collect();
+    /// let removed: Vec<_> = vec.splice(Id(1)..Id(2), vec![7, 8]).collect();
+    /// assert_eq!(removed, [2]);
+    /// assert_eq!(vec.as_slice(), TiSlice::from_ref(&[1, 7, 8, 3]));
+    /// assert!(!vec.spilled());
+    /// ```
+    ///
+    /// [`Vec::splice`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.splice
+    pub fn splice<R, I>(
+        &mut self,
+        range: R,
+        replace_with: I,
+    ) -> TiSmallVecSplice<'_, I::IntoIter, N>
+    where
+        R: TiRangeBounds<K>,
+        I: IntoIterator<Item = V>,
+    {
+        let inline = match self.repr {
+            Repr::Inline(ref mut inline) => inline,
+            Repr::Heap(ref mut vec) => {
+                return TiSmallVecSplice {
+                    repr: SpliceRepr::Heap(vec.splice(range, replace_with)),
+                }
+            }
+        };
+        let range = range_to_bounds(range.into_range(), inline.len());
+        let start = range.start;
+        let removed: TiArrayVec<K, V, N> = inline.drain_bounds(range).collect();
+        let mut tail: TiArrayVec<K, V, N> = inline.drain_bounds(start..inline.len()).collect();
+        let mut replace_with = replace_with.into_iter();
+        let mut overflow = None;
+        for value in &mut replace_with {
+            if let Err(err) = inline.try_push(value) {
+                overflow = Some(err.element());
+                break;
+            }
+        }
+        if overflow.is_some() || inline.try_append(&mut tail).is_err() {
+            let additional = replace_with.size_hint().0.saturating_add(tail.len() + 1);
+            let vec = self.spill(additional);
+            vec.extend(overflow);
+            vec.extend(replace_with);
+            vec.extend(tail);
+        }
+        TiSmallVecSplice {
+            repr: SpliceRepr::Inline(removed.into_iter()),
+        }
+    }
+
+    /// Converts the vector into iterator over all key-value pairs
+    /// with `K` used for iteration indices.
+    ///
+    /// It acts like `self.into_iter().enumerate()`,
+    /// but use `K` instead of `usize` for iteration indices.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiSmallVec;
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let vec: TiSmallVec<Id, usize, 2> = vec![1, 2].into_iter().collect();
+    /// let mut iterator = vec.into_iter_enumerated();
+    /// assert_eq!(iterator.next(), Some((Id(0), 1)));
+    /// assert_eq!(iterator.next(), Some((Id(1), 2)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    #[inline(always)]
+    pub fn into_iter_enumerated(self) -> TiEnumerated<TiSmallVecIntoIter<V, N>, K, V>
+    where
+        K: TiKey,
+    {
+        self.into_iter()
+            .enumerate()
+            .map(|(key, value)| (K::from_usize(key), value))
+    }
+}
+
+impl<K, V, const N: usize> fmt::Debug for TiSmallVec<K, V, N>
+where
+    K: fmt::Debug + TiKey,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter_enumerated()).finish()
+    }
+}
+
+impl<K, V, const N: usize> AsRef<TiSlice<K, V>> for TiSmallVec<K, V, N> {
+    fn as_ref(&self) -> &TiSlice<K, V> {
+        self
+    }
+}
+
+impl<K, V, const N: usize> AsMut<TiSlice<K, V>> for TiSmallVec<K, V, N> {
+    fn as_mut(&mut self) -> &mut TiSlice<K, V> {
+        self
+    }
+}
+
+impl<K, V, const N: usize> AsRef<[V]> for TiSmallVec<K, V, N> {
+    fn as_ref(&self) -> &[V] {
+        &self.as_slice().raw
+    }
+}
+
+impl<K, V, const N: usize> AsMut<[V]> for TiSmallVec<K, V, N> {
+    fn as_mut(&mut self) -> &mut [V] {
+        &mut self.as_mut_slice().raw
+    }
+}
+
+impl<K, V, const N: usize> Borrow<TiSlice<K, V>> for TiSmallVec<K, V, N> {
+    fn borrow(&self) -> &TiSlice<K, V> {
+        self
+    }
+}
+
+impl<K, V, const N: usize> BorrowMut<TiSlice<K, V>> for TiSmallVec<K, V, N> {
+    fn borrow_mut(&mut self) -> &mut TiSlice<K, V> {
+        self
+    }
+}
+
+impl<K, V, const N: usize> ops::Deref for TiSmallVec<K, V, N> {
+    type Target = TiSlice<K, V>;
+
+    fn deref(&self) -> &TiSlice<K, V> {
+        self.as_slice()
+    }
+}
+
+impl<K, V, const N: usize> ops::DerefMut for TiSmallVec<K, V, N> {
+    fn deref_mut(&mut self) -> &mut TiSlice<K, V> {
+        self.as_mut_slice()
+    }
+}
+
+impl<K, V, const N: usize> Extend<V> for TiSmallVec<K, V, N> {
+    fn extend<I: IntoIterator<Item = V>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<'a, K, V: 'a + Copy, const N: usize> Extend<&'a V> for TiSmallVec<K, V, N> {
+    fn extend<I: IntoIterator<Item = &'a V>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+
+impl<K, V, const N: usize> FromIterator<V> for TiSmallVec<K, V, N> {
+    fn from_iter<I: IntoIterator<Item = V>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<K, A, B, const N: usize, const M: usize> PartialEq<TiSmallVec<K, B, M>> for TiSmallVec<K, A, N>
+where
+    A: PartialEq<B>,
+{
+    #[inline]
+    fn eq(&self, other: &TiSmallVec<K, B, M>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<'a, K, A, B, const N: usize> PartialEq<&'a TiSlice<K, B>> for TiSmallVec<K, A, N>
+where
+    A: PartialEq<B>,
+{
+    #[inline]
+    fn eq(&self, other: &&'a TiSlice<K, B>) -> bool {
+        self.as_slice() == *other
+    }
+}
+
+impl<K, V, const N: usize> PartialOrd<TiSmallVec<K, V, N>> for TiSmallVec<K, V, N>
+where
+    V: PartialOrd<V>,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &TiSmallVec<K, V, N>) -> Option<Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<K, V, const N: usize> Clone for TiSmallVec<K, V, N>
+where
+    V: Clone,
+{
+    fn clone(&self) -> TiSmallVec<K, V, N> {
+        Self {
+            repr: match self.repr {
+                Repr::Inline(ref inline) => Repr::Inline(inline.clone()),
+                Repr::Heap(ref vec) => Repr::Heap(vec.clone()),
+            },
+        }
+    }
+}
+
+impl<K, V, const N: usize> Default for TiSmallVec<K, V, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, const N: usize> Eq for TiSmallVec<K, V, N> where V: Eq {}
+
+impl<K, V, const N: usize> Hash for TiSmallVec<K, V, N>
+where
+    V: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().raw.hash(state)
+    }
+}
+
+impl<K, V, const N: usize> Ord for TiSmallVec<K, V, N>
+where
+    V: Ord,
+{
+    #[inline]
+    fn cmp(&self, other: &TiSmallVec<K, V, N>) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl<K, V, const N: usize> From<TiVec<K, V>> for TiSmallVec<K, V, N> {
+    /// Converts a `TiVec` into a `TiSmallVec`,
+    /// moving the elements inline if they fit.
+    fn from(vec: TiVec<K, V>) -> Self {
+        Self {
+            repr: if vec.len() <= N {
+                Repr::Inline(vec.into_iter().collect())
+            } else {
+                Repr::Heap(vec)
+            },
+        }
+    }
+}
+
+impl<K, V, const N: usize> From<TiSmallVec<K, V, N>> for TiVec<K, V> {
+    /// Converts a `TiSmallVec` into a `TiVec`,
+    /// reusing the heap allocation if the elements are already spilled.
+    fn from(vec: TiSmallVec<K, V, N>) -> Self {
+        match vec.repr {
+            Repr::Inline(inline) => inline.into(),
+            Repr::Heap(vec) => vec,
+        }
+    }
+}
+
+impl<K, V, const N: usize> IntoIterator for TiSmallVec<K, V, N> {
+    type Item = V;
+    type IntoIter = TiSmallVecIntoIter<V, N>;
+
+    #[inline]
+    fn into_iter(self) -> TiSmallVecIntoIter<V, N> {
+        TiSmallVecIntoIter {
+            repr: match self.repr {
+                Repr::Inline(inline) => IntoIterRepr::Inline(inline.into_iter()),
+                Repr::Heap(vec) => IntoIterRepr::Heap(vec.into_iter()),
+            },
+        }
+    }
+}
+
+impl<'a, K, V, const N: usize> IntoIterator for &'a TiSmallVec<K, V, N> {
+    type Item = &'a V;
+    type IntoIter = slice::Iter<'a, V>;
+
+    #[inline]
+    fn into_iter(self) -> slice::Iter<'a, V> {
+        self.as_slice().raw.iter()
+    }
+}
+
+impl<'a, K, V, const N: usize> IntoIterator for &'a mut TiSmallVec<K, V, N> {
+    type Item = &'a mut V;
+    type IntoIter = slice::IterMut<'a, V>;
+
+    #[inline]
+    fn into_iter(self) -> slice::IterMut<'a, V> {
+        self.as_mut_slice().raw.iter_mut()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K, V: Serialize, const N: usize> Serialize for TiSmallVec<K, V, N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_slice().serialize(serializer)
+    }
+}
+
+#[cfg(any(feature = "serde-alloc", feature = "serde-std"))]
+impl<'de, K, V: Deserialize<'de>, const N: usize> Deserialize<'de> for TiSmallVec<K, V, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        TiVec::deserialize(deserializer).map(Into::into)
+    }
+}
+
+/// A draining iterator for [`TiSmallVec`].
+///
+/// This struct is created by the [`TiSmallVec::drain`] method.
+///
+/// [`TiSmallVec`]: struct.TiSmallVec.html
+/// [`TiSmallVec::drain`]: struct.TiSmallVec.html#method.drain
+#[derive(Debug)]
+pub struct TiSmallVecDrain<'a, V> {
+    repr: DrainRepr<'a, V>,
+}
+
+#[derive(Debug)]
+enum DrainRepr<'a, V> {
+    Inline(TiArrayVecDrain<'a, V>),
+    Heap(vec::Drain<'a, V>),
+}
+
+impl<V> Iterator for TiSmallVecDrain<'_, V> {
+    type Item = V;
+
+    #[inline]
+    fn next(&mut self) -> Option<V> {
+        match self.repr {
+            DrainRepr::Inline(ref mut iter) => iter.next(),
+            DrainRepr::Heap(ref mut iter) => iter.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.repr {
+            DrainRepr::Inline(ref iter) => iter.size_hint(),
+            DrainRepr::Heap(ref iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<V> DoubleEndedIterator for TiSmallVecDrain<'_, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<V> {
+        match self.repr {
+            DrainRepr::Inline(ref mut iter) => iter.next_back(),
+            DrainRepr::Heap(ref mut iter) => iter.next_back(),
+        }
+    }
+}
+
+impl<V> ExactSizeIterator for TiSmallVecDrain<'_, V> {}
+
+impl<V> FusedIterator for TiSmallVecDrain<'_, V> {}
+
+/// A splicing iterator for [`TiSmallVec`].
+///
+/// This struct is created by the [`TiSmallVec::splice`] method.
+///
+/// [`TiSmallVec`]: struct.TiSmallVec.html
+/// [`TiSmallVec::splice`]: struct.TiSmallVec.html#method.splice
+pub struct TiSmallVecSplice<'a, I, const N: usize>
+where
+    I: Iterator,
+{
+    repr: SpliceRepr<'a, I, N>,
+}
+
+enum SpliceRepr<'a, I, const N: usize>
+where
+    I: Iterator,
+{
+    Inline(TiArrayVecIntoIter<I::Item, N>),
+    Heap(Splice<'a, I>),
+}
+
+impl<I, const N: usize> fmt::Debug for TiSmallVecSplice<'_, I, N>
+where
+    I: Iterator + fmt::Debug,
+    I::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tuple = f.debug_tuple("TiSmallVecSplice");
+        let _ = match self.repr {
+            SpliceRepr::Inline(ref iter) => tuple.field(iter),
+            SpliceRepr::Heap(ref iter) => tuple.field(iter),
+        };
+        tuple.finish()
+    }
+}
+
+impl<I, const N: usize> Iterator for TiSmallVecSplice<'_, I, N>
+where
+    I: Iterator,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        match self.repr {
+            SpliceRepr::Inline(ref mut iter) => iter.next(),
+            SpliceRepr::Heap(ref mut iter) => iter.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.repr {
+            SpliceRepr::Inline(ref iter) => iter.size_hint(),
+            SpliceRepr::Heap(ref iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<I, const N: usize> DoubleEndedIterator for TiSmallVecSplice<'_, I, N>
+where
+    I: Iterator,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<I::Item> {
+        match self.repr {
+            SpliceRepr::Inline(ref mut iter) => iter.next_back(),
+            SpliceRepr::Heap(ref mut iter) => iter.next_back(),
+        }
+    }
+}
+
+impl<I, const N: usize> ExactSizeIterator for TiSmallVecSplice<'_, I, N> where I: Iterator {}
+
+/// An iterator that moves out of a [`TiSmallVec`].
+///
+/// This struct is created by the `into_iter` method on [`TiSmallVec`].
+///
+/// [`TiSmallVec`]: struct.TiSmallVec.html
+#[derive(Debug)]
+pub struct TiSmallVecIntoIter<V, const N: usize> {
+    repr: IntoIterRepr<V, N>,
+}
+
+#[derive(Debug)]
+enum IntoIterRepr<V, const N: usize> {
+    Inline(TiArrayVecIntoIter<V, N>),
+    Heap(vec::IntoIter<V>),
+}
+
+impl<V, const N: usize> Iterator for TiSmallVecIntoIter<V, N> {
+    type Item = V;
+
+    #[inline]
+    fn next(&mut self) -> Option<V> {
+        match self.repr {
+            IntoIterRepr::Inline(ref mut iter) => iter.next(),
+            IntoIterRepr::Heap(ref mut iter) => iter.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.repr {
+            IntoIterRepr::Inline(ref iter) => iter.size_hint(),
+            IntoIterRepr::Heap(ref iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<V, const N: usize> DoubleEndedIterator for TiSmallVecIntoIter<V, N> {
+    #[inline]
+    fn next_back(&mut self) -> Option<V> {
+        match self.repr {
+            IntoIterRepr::Inline(ref mut iter) => iter.next_back(),
+            IntoIterRepr::Heap(ref mut iter) => iter.next_back(),
+        }
+    }
+}
+
+impl<V, const N: usize> ExactSizeIterator for TiSmallVecIntoIter<V, N> {}
+
+impl<V, const N: usize> FusedIterator for TiSmallVecIntoIter<V, N> {}
+
+#[cfg(test)]
+mod test {
+    use crate::test::Id;
+    use crate::{TiSlice, TiSmallVec, TiVec};
+    use alloc::{format, vec};
+    use core::iter::FromIterator;
+
+    #[test]
+    fn spill_and_shrink() {
+        let mut vec: TiSmallVec<Id, u32, 2> = TiSmallVec::new();
+        assert_eq!(vec.push_and_get_key(1), Id::from(0));
+        assert_eq!(vec.push_and_get_key(2), Id::from(1));
+        assert!(!vec.spilled());
+        assert_eq!(vec.capacity(), 2);
+        assert_eq!(vec.push_and_get_key(3), Id::from(2));
+        assert!(vec.spilled());
+        assert_eq!(vec, TiSlice::from_ref(&[1, 2, 3]));
+        assert_eq!(vec.pop_key_value(), Some((Id::from(2), 3)));
+        vec.shrink_to_fit();
+        assert!(!vec.spilled());
+        assert_eq!(vec, TiSlice::from_ref(&[1, 2]));
+        vec.reserve(1);
+        assert!(vec.spilled());
+    }
+
+    #[test]
+    fn insert_remove_splice() {
+        let mut vec: TiSmallVec<Id, u32, 4> = vec![1, 2, 4].into_iter().collect();
+        vec.insert(Id::from(2), 3);
+        assert!(!vec.spilled());
+        vec.insert(Id::from(0), 0);
+        assert!(vec.spilled());
+        assert_eq!(vec.remove(Id::from(1)), 1);
+        assert_eq!(vec.swap_remove(Id::from(0)), 0);
+        assert_eq!(vec, TiSlice::from_ref(&[4, 2, 3]));
+
+        let mut vec: TiSmallVec<Id, u32, 4> = vec![1, 2, 3].into_iter().collect();
+        let removed: TiVec<Id, u32> = vec.splice(Id::from(1)..Id::from(2), vec![7, 8]).collect();
+        assert_eq!(removed.raw, [2]);
+        assert_eq!(vec, TiSlice::from_ref(&[1, 7, 8, 3]));
+        assert!(!vec.spilled());
+        let removed: TiVec<Id, u32> = vec.splice(Id::from(1)..Id::from(3), vec![5, 6]).collect();
+        assert_eq!(removed.raw, [7, 8]);
+        assert!(!vec.spilled());
+        let removed: TiVec<Id, u32> = vec
+            .splice(Id::from(0)..Id::from(1), (0..3).filter(|_| true))
+            .collect();
+        assert_eq!(removed.raw, [1]);
+        assert!(vec.spilled());
+        assert_eq!(vec, TiSlice::from_ref(&[0, 1, 2, 5, 6, 3]));
+        let removed: TiVec<Id, u32> = vec.splice(.., vec![4]).collect();
+        assert_eq!(removed.raw, [0, 1, 2, 5, 6, 3]);
+        assert_eq!(vec, TiSlice::from_ref(&[4]));
+
+        let mut vec: TiSmallVec<Id, u32, 3> = vec![1, 2, 3].into_iter().collect();
+        drop(vec.splice(Id::from(1)..Id::from(1), vec![4]));
+        assert!(vec.spilled());
+        assert_eq!(vec, TiSlice::from_ref(&[1, 4, 2, 3]));
+    }
+
+    #[test]
+    fn drain_and_dedup() {
+        for_in!(for vec in [
+            TiSmallVec::<Id, u32, 8>::from_iter(vec![1, 1, 2, 3, 3, 3, 4]),
+            TiSmallVec::<Id, u32, 2>::from_iter(vec![1, 1, 2, 3, 3, 3, 4]),
+        ] {
+            let mut vec = vec;
+            vec.dedup();
+            assert_eq!(vec, TiSlice::from_ref(&[1, 2, 3, 4]));
+            let drained: TiVec<Id, (Id, u32)> =
+                vec.drain_enumerated(Id::from(1)..Id::from(3)).collect();
+            assert_eq!(drained.raw, [(Id::from(0), 2), (Id::from(1), 3)]);
+            assert_eq!(vec, TiSlice::from_ref(&[1, 4]));
+            let tail = vec.split_off(Id::from(1));
+            assert_eq!(tail, TiSlice::from_ref(&[4]));
+            vec.resize(3, 9);
+            assert_eq!(vec, TiSlice::from_ref(&[1, 9, 9]));
+        });
+    }
+
+    #[test]
+    fn conversions() {
+        let vec: TiVec<Id, u32> = vec![1, 2].into();
+        let small: TiSmallVec<Id, u32, 2> = vec.into();
+        assert!(!small.spilled());
+        assert_eq!(format!("{:?}", small), "{Id(0): 1, Id(1): 2}");
+        let vec: TiVec<Id, u32> = small.into();
+        assert_eq!(vec.raw, [1, 2]);
+        let small: TiSmallVec<Id, u32, 1> = vec.into();
+        assert!(small.spilled());
+        let mut iter = small.into_iter_enumerated();
+        assert_eq!(iter.next_back(), Some((Id::from(1), 2)));
+        assert_eq!(iter.next(), Some((Id::from(0), 1)));
+    }
+}
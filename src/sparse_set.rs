@@ -0,0 +1,336 @@
+use core::{
+    fmt,
+    iter::{self, FromIterator},
+    slice,
+};
+
+use alloc::vec::Vec;
+
+use crate::key::{assert_keys_in_range, offset_index};
+use crate::{TiKey, TiVec};
+
+/// A set of keys of the type `K` with constant time insertion, removal,
+/// lookup and clearing, and iteration in insertion order.
+///
+/// The set is a Briggs–Torczon sparse set:
+/// a sparse array maps every key of the capacity to its position in a dense array of keys.
+/// Iteration only visits the dense array, and removal moves the last key into the vacated position.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::TiSparseSet;
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct BlockId(usize);
+///
+/// let mut worklist: TiSparseSet<BlockId> = TiSparseSet::with_capacity(1000);
+/// assert!(worklist.insert(BlockId(7)));
+/// assert!(worklist.insert(BlockId(3)));
+/// assert!(!worklist.insert(BlockId(7)));
+/// assert_eq!(worklist.as_slice(), [BlockId(7), BlockId(3)]);
+/// assert_eq!(worklist.pop(), Some(BlockId(3)));
+/// worklist.clear();
+/// assert!(!worklist.contains(BlockId(7)));
+/// ```
+pub struct TiSparseSet<K> {
+    sparse: TiVec<K, u32>,
+    dense: Vec<K>,
+}
+
+#[cold]
+#[inline(never)]
+fn capacity_overflow(capacity: usize) -> ! {
+    panic!(
+        "sparse set capacity {} exceeds the maximum of {} positions",
+        capacity,
+        u32::MAX as u64 + 1
+    )
+}
+
+impl<K> TiSparseSet<K> {
+    /// Constructs a new, empty set with zero capacity.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            sparse: TiVec::new(),
+            dense: Vec::new(),
+        }
+    }
+
+    /// Constructs a new, empty set for keys below `capacity`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some keys below `capacity` can not be represented by the key type,
+    /// or if `capacity` exceeds the number of positions representable by `u32`.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self
+    where
+        K: TiKey,
+    {
+        let mut set = Self::new();
+        set.reserve_keys(capacity);
+        set
+    }
+
+    /// Returns the number of keys the set can hold without growing its sparse array.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.sparse.len()
+    }
+
+    /// Grows the sparse array so that the set can hold keys below `capacity`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some keys below `capacity` can not be represented by the key type,
+    /// or if `capacity` exceeds the number of positions representable by `u32`.
+    pub fn reserve_keys(&mut self, capacity: usize)
+    where
+        K: TiKey,
+    {
+        if capacity > self.sparse.len() {
+            assert_keys_in_range::<K>(capacity);
+            if capacity - 1 > u32::MAX as usize {
+                capacity_overflow(capacity);
+            }
+            self.sparse.raw.resize(capacity, 0);
+        }
+    }
+
+    /// Returns the number of keys in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    /// Returns `true` if the set contains no keys.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.dense.is_empty()
+    }
+
+    /// Returns `true` if the set contains the key.
+    #[inline]
+    pub fn contains(&self, key: K) -> bool
+    where
+        K: TiKey + Copy,
+    {
+        self.position(key.to_usize()).is_some()
+    }
+
+    /// Adds the key to the set and returns `true` if it was not present.
+    ///
+    /// Inserting a key at or above the capacity grows the sparse array.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is the maximum `usize` index,
+    /// or if the grown capacity exceeds the number of positions representable by `u32`.
+    #[inline]
+    pub fn insert(&mut self, key: K) -> bool
+    where
+        K: TiKey + Copy,
+    {
+        let index = key.to_usize();
+        if self.position(index).is_some() {
+            return false;
+        }
+        self.reserve_keys(offset_index::<K>(index, 1));
+        // `len < capacity` holds here, so the position fits into `u32`.
+        self.sparse.raw[index] = self.dense.len() as u32;
+        self.dense.push(key);
+        true
+    }
+
+    /// Removes the key from the set and returns `true` if it was present.
+    ///
+    /// The last key of the set takes the position of the removed key.
+    #[inline]
+    pub fn remove(&mut self, key: K) -> bool
+    where
+        K: TiKey + Copy,
+    {
+        match self.position(key.to_usize()) {
+            Some(position) => {
+                let _ = self.dense.swap_remove(position);
+                if let Some(&moved) = self.dense.get(position) {
+                    self.sparse[moved] = position as u32;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the last inserted key from the set and returns it,
+    /// or `None` if the set is empty.
+    #[inline]
+    pub fn pop(&mut self) -> Option<K> {
+        self.dense.pop()
+    }
+
+    /// Removes all keys from the set in constant time.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.dense.clear();
+    }
+
+    /// Returns the keys of the set in their dense order.
+    #[inline]
+    pub fn as_slice(&self) -> &[K] {
+        &self.dense
+    }
+
+    /// Returns an iterator over keys of the set in their dense order.
+    #[inline]
+    pub fn iter(&self) -> iter::Copied<slice::Iter<'_, K>>
+    where
+        K: Copy,
+    {
+        self.dense.iter().copied()
+    }
+
+    #[inline]
+    fn position(&self, index: usize) -> Option<usize>
+    where
+        K: TiKey + Copy,
+    {
+        let position = *self.sparse.raw.get(index)? as usize;
+        match self.dense.get(position) {
+            Some(&key) if key.to_usize() == index => Some(position),
+            _ => None,
+        }
+    }
+}
+
+impl<K> fmt::Debug for TiSparseSet<K>
+where
+    K: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(&self.dense).finish()
+    }
+}
+
+impl<K> Clone for TiSparseSet<K>
+where
+    K: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            sparse: self.sparse.clone(),
+            dense: self.dense.clone(),
+        }
+    }
+}
+
+impl<K> Default for TiSparseSet<K> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> PartialEq for TiSparseSet<K>
+where
+    K: TiKey + Copy,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.dense.iter().all(|&key| other.contains(key))
+    }
+}
+
+impl<K> Eq for TiSparseSet<K> where K: TiKey + Copy {}
+
+impl<K> Extend<K> for TiSparseSet<K>
+where
+    K: TiKey + Copy,
+{
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        for key in iter {
+            let _ = self.insert(key);
+        }
+    }
+}
+
+impl<K> FromIterator<K> for TiSparseSet<K>
+where
+    K: TiKey + Copy,
+{
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<'a, K> IntoIterator for &'a TiSparseSet<K>
+where
+    K: Copy,
+{
+    type Item = K;
+    type IntoIter = iter::Copied<slice::Iter<'a, K>>;
+
+    #[inline]
+    fn into_iter(self) -> iter::Copied<slice::Iter<'a, K>> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::{ids, Id};
+    use crate::TiSparseSet;
+    use alloc::{format, vec::Vec};
+    use core::iter::FromIterator;
+
+    #[test]
+    fn insert_remove_clear() {
+        let mut set: TiSparseSet<Id> = TiSparseSet::with_capacity(8);
+        assert_eq!(set.capacity(), 8);
+        for &index in &[5, 1, 6, 2] {
+            assert!(set.insert(Id::from(index)));
+        }
+        assert!(set.remove(Id::from(1)));
+        assert!(!set.remove(Id::from(1)));
+        assert!(!set.remove(Id::from(100)));
+        assert_eq!(set.as_slice(), [Id::from(5), Id::from(2), Id::from(6)]);
+        assert!(set.contains(Id::from(2)));
+        assert!(set.remove(Id::from(6)));
+        assert_eq!(format!("{:?}", set), "{Id(5), Id(2)}");
+
+        set.clear();
+        assert!(set.is_empty());
+        assert!(!set.contains(Id::from(5)));
+        assert!(set.insert(Id::from(2)));
+        assert!(!set.contains(Id::from(5)));
+        assert!(set.insert(Id::from(20)));
+        assert_eq!(set.capacity(), 21);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [Id::from(2), Id::from(20)]);
+    }
+
+    #[test]
+    fn worklist_and_equality() {
+        let mut set = TiSparseSet::from_iter(ids(&[3, 1, 3, 2]));
+        assert_eq!(set.len(), 3);
+        let other = TiSparseSet::from_iter(ids(&[1, 2, 3]));
+        assert_eq!(set, other);
+        let mut popped = Vec::new();
+        while let Some(key) = set.pop() {
+            popped.push(key);
+        }
+        assert_eq!(popped, ids(&[2, 1, 3]));
+        assert_ne!(set, other);
+    }
+
+    #[test]
+    #[should_panic(expected = "+ 1 is out of range for the key type")]
+    fn insert_usize_max() {
+        let mut set: TiSparseSet<Id> = TiSparseSet::new();
+        let _ = set.insert(Id::from(usize::MAX));
+    }
+}
@@ -5,7 +5,7 @@ use alloc::{boxed::Box, vec::Vec};
 
 use derive_more::{From, Into};
 
-use crate::TiSlice;
+use crate::{TiKey, TiSlice};
 
 #[cfg(any(feature = "alloc", feature = "std"))]
 use crate::TiVec;
@@ -13,6 +13,26 @@ use crate::TiVec;
 #[derive(From, Into, Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Id(usize);
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SmallId(pub u8);
+
+impl TiKey for SmallId {
+    const MAX: usize = 1;
+
+    fn from_usize(index: usize) -> Self {
+        Self(index as u8)
+    }
+
+    fn to_usize(self) -> usize {
+        self.0.into()
+    }
+}
+
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub fn ids(indices: &[usize]) -> Vec<Id> {
+    indices.iter().map(|&index| Id::from(index)).collect()
+}
+
 pub trait DummyConvert {
     type Target;
     fn into_t(self) -> Self::Target;
@@ -23,6 +43,7 @@ pub trait TypedConvert {
     fn into_t(self) -> Self::Target;
 }
 
+#[allow(clippy::extra_unused_lifetimes)]
 impl<'a, T> DummyConvert for T {
     type Target = T;
     fn into_t(self) -> Self::Target {
@@ -0,0 +1,550 @@
+use core::{fmt, marker::PhantomData, mem};
+
+use alloc::vec::Vec;
+
+use crate::key::{assert_keys_in_range, key_from_len};
+use crate::{TiKey, TiVec};
+
+/// A disjoint-set forest partitioning keys of the type `K` into classes
+/// with an optional value of the type `V` for every class.
+///
+/// Lookups compress paths and unions link by rank.
+/// Values of merged classes are combined by [`union_with`].
+///
+/// While a snapshot is open, every change is recorded in an undo log,
+/// so speculative unions can be undone with [`rollback_to`]
+/// or kept with [`commit`].
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::TiUnionFind;
+/// use derive_more::{From, Into};
+///
+/// #[derive(Clone, Copy, Debug, Eq, From, Into, PartialEq)]
+/// struct TypeVar(usize);
+///
+/// let mut vars: TiUnionFind<TypeVar, u32> = TiUnionFind::new();
+/// let a = vars.push(1);
+/// let b = vars.push(2);
+/// let c = vars.push(4);
+/// assert!(vars.union_with(a, b, |x, y| x + y));
+/// assert!(vars.same(a, b));
+/// assert_eq!(*vars.value(b), 3);
+///
+/// let snapshot = vars.snapshot();
+/// assert!(vars.union_with(b, c, |x, y| x + y));
+/// assert_eq!(*vars.value(a), 7);
+/// vars.rollback_to(snapshot);
+/// assert!(!vars.same(a, c));
+/// assert_eq!(*vars.value(a), 3);
+/// ```
+///
+/// [`union_with`]: #method.union_with
+/// [`rollback_to`]: #method.rollback_to
+/// [`commit`]: #method.commit
+pub struct TiUnionFind<K, V = ()> {
+    nodes: TiVec<K, Node<V>>,
+    undo_log: Vec<Undo<V>>,
+    open_snapshots: usize,
+}
+
+/// A snapshot of a [`TiUnionFind`] created by [`TiUnionFind::snapshot`].
+///
+/// Snapshots must be rolled back or committed in the reverse order of their creation.
+/// Dropping a snapshot does not close it, see [`TiUnionFind::snapshot`].
+///
+/// [`TiUnionFind`]: struct.TiUnionFind.html
+/// [`TiUnionFind::snapshot`]: struct.TiUnionFind.html#method.snapshot
+#[must_use = "the snapshot must be rolled back or committed"]
+pub struct TiUnionFindSnapshot<K> {
+    undo_len: usize,
+    /// The number of open snapshots including this one.
+    depth: usize,
+
+    /// Ties the container to the key type `K`.
+    _marker: PhantomData<fn(K) -> K>,
+}
+
+#[derive(Clone, Debug)]
+struct Node<V> {
+    parent: usize,
+    rank: u32,
+    /// The class value, stale in non-root nodes.
+    value: V,
+}
+
+#[derive(Clone, Debug)]
+enum Undo<V> {
+    Push,
+    SetParent(usize, usize),
+    SetRank(usize, u32),
+    SetValue(usize, V),
+    SwapValues(usize, usize),
+}
+
+#[cold]
+#[inline(never)]
+fn snapshot_not_open(depth: usize, open_snapshots: usize) -> ! {
+    panic!(
+        "union-find snapshot at depth {} is not the most recent open snapshot, {} snapshots are open",
+        depth, open_snapshots
+    )
+}
+
+impl<K, V> TiUnionFind<K, V> {
+    /// Constructs a new, empty union-find.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            nodes: TiVec::new(),
+            undo_log: Vec::new(),
+            open_snapshots: 0,
+        }
+    }
+
+    /// Constructs a new, empty union-find that can hold `capacity` keys
+    /// without reallocating.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            nodes: TiVec::with_capacity(capacity),
+            undo_log: Vec::new(),
+            open_snapshots: 0,
+        }
+    }
+
+    /// Returns the number of keys in the union-find.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the union-find contains no keys.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Adds a new key in a singleton class with the value and returns the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new key can not be represented by the key type.
+    #[inline]
+    pub fn push(&mut self, value: V) -> K
+    where
+        K: TiKey,
+    {
+        let index = self.nodes.len();
+        let key = key_from_len(index);
+        self.nodes.raw.push(Node {
+            parent: index,
+            rank: 0,
+            value,
+        });
+        self.log(|| Undo::Push);
+        key
+    }
+
+    /// Returns the representative key of the class of the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is out of bounds.
+    #[inline]
+    pub fn find(&mut self, key: K) -> K
+    where
+        K: TiKey,
+    {
+        K::from_usize(self.find_root(key.to_usize()))
+    }
+
+    /// Returns `true` if both keys are in the same class.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the keys is out of bounds.
+    #[inline]
+    pub fn same(&mut self, a: K, b: K) -> bool
+    where
+        K: TiKey,
+    {
+        self.find_root(a.to_usize()) == self.find_root(b.to_usize())
+    }
+
+    /// Merges the classes of both keys and returns `true` if they were different.
+    ///
+    /// The merged class keeps the value of the class of `a`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the keys is out of bounds.
+    pub fn union(&mut self, a: K, b: K) -> bool
+    where
+        K: TiKey,
+    {
+        match self.link(a.to_usize(), b.to_usize()) {
+            Some((root_a, root)) => {
+                if root != root_a {
+                    self.swap_values(root_a, root);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Merges the classes of both keys and returns `true` if they were different.
+    ///
+    /// The merged class gets the value returned by `merge`
+    /// for the values of the classes of `a` and `b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the keys is out of bounds.
+    pub fn union_with(&mut self, a: K, b: K, merge: impl FnOnce(&V, &V) -> V) -> bool
+    where
+        K: TiKey,
+    {
+        let root_a = self.find_root(a.to_usize());
+        let root_b = self.find_root(b.to_usize());
+        if root_a == root_b {
+            return false;
+        }
+        let value = merge(&self.nodes.raw[root_a].value, &self.nodes.raw[root_b].value);
+        let _ = self.link(root_a, root_b);
+        let root = self.nodes.raw[root_a].parent;
+        self.set_value_at(root, value);
+        true
+    }
+
+    /// Returns a reference to the value of the class of the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is out of bounds.
+    #[inline]
+    pub fn value(&mut self, key: K) -> &V
+    where
+        K: TiKey,
+    {
+        let root = self.find_root(key.to_usize());
+        &self.nodes.raw[root].value
+    }
+
+    /// Replaces the value of the class of the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is out of bounds.
+    #[inline]
+    pub fn set_value(&mut self, key: K, value: V)
+    where
+        K: TiKey,
+    {
+        let root = self.find_root(key.to_usize());
+        self.set_value_at(root, value);
+    }
+
+    /// Returns members of every class in ascending order of keys.
+    ///
+    /// Classes are keyed by the type `C` and ordered by their smallest keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of classes can not be represented by the class key type.
+    pub fn classes<C>(&mut self) -> TiVec<C, Vec<K>>
+    where
+        K: TiKey,
+        C: TiKey,
+    {
+        let mut class_of_root = alloc::vec![usize::MAX; self.nodes.len()];
+        let mut classes: TiVec<C, Vec<K>> = TiVec::new();
+        for index in 0..self.nodes.len() {
+            let root = self.find_root(index);
+            if class_of_root[root] == usize::MAX {
+                class_of_root[root] = classes.len();
+                classes.raw.push(Vec::new());
+                assert_keys_in_range::<C>(classes.len());
+            }
+            classes.raw[class_of_root[root]].push(K::from_usize(index));
+        }
+        classes
+    }
+
+    /// Starts recording changes so that they can be undone.
+    ///
+    /// The returned snapshot must be passed to [`rollback_to`] or [`commit`].
+    /// A snapshot that is dropped instead stays open forever:
+    /// every later change is recorded in the undo log, which then grows without bound,
+    /// and enclosing snapshots can no longer be closed.
+    ///
+    /// [`rollback_to`]: #method.rollback_to
+    /// [`commit`]: #method.commit
+    #[inline]
+    pub fn snapshot(&mut self) -> TiUnionFindSnapshot<K> {
+        self.open_snapshots += 1;
+        TiUnionFindSnapshot {
+            undo_len: self.undo_log.len(),
+            depth: self.open_snapshots,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Undoes all changes made since the snapshot was created.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the snapshot is not the most recent open snapshot of the union-find.
+    pub fn rollback_to(&mut self, snapshot: TiUnionFindSnapshot<K>) {
+        self.close_snapshot(&snapshot);
+        while self.undo_log.len() > snapshot.undo_len {
+            match self.undo_log.pop() {
+                Some(Undo::Push) => {
+                    let _ = self.nodes.raw.pop();
+                }
+                Some(Undo::SetParent(index, parent)) => self.nodes.raw[index].parent = parent,
+                Some(Undo::SetRank(index, rank)) => self.nodes.raw[index].rank = rank,
+                Some(Undo::SetValue(index, value)) => self.nodes.raw[index].value = value,
+                Some(Undo::SwapValues(a, b)) => self.swap_values_unlogged(a, b),
+                None => unreachable!(),
+            }
+        }
+    }
+
+    /// Keeps all changes made since the snapshot was created.
+    ///
+    /// The changes can still be undone by rolling back an enclosing snapshot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the snapshot is not the most recent open snapshot of the union-find.
+    #[inline]
+    pub fn commit(&mut self, snapshot: TiUnionFindSnapshot<K>) {
+        self.close_snapshot(&snapshot);
+        if self.open_snapshots == 0 {
+            self.undo_log.clear();
+        }
+    }
+
+    fn find_root(&mut self, index: usize) -> usize {
+        let mut root = index;
+        while self.nodes.raw[root].parent != root {
+            root = self.nodes.raw[root].parent;
+        }
+        let mut current = index;
+        while current != root {
+            let parent = self.nodes.raw[current].parent;
+            if parent != root {
+                self.set_parent(current, root);
+            }
+            current = parent;
+        }
+        root
+    }
+
+    #[inline]
+    fn set_parent(&mut self, index: usize, parent: usize) {
+        let old = mem::replace(&mut self.nodes.raw[index].parent, parent);
+        self.log(|| Undo::SetParent(index, old));
+    }
+
+    #[inline]
+    fn set_rank(&mut self, index: usize, rank: u32) {
+        let old = mem::replace(&mut self.nodes.raw[index].rank, rank);
+        self.log(|| Undo::SetRank(index, old));
+    }
+
+    /// Links the classes of both indices by rank and returns the old root of `a`
+    /// and the new common root, or `None` if the classes are the same.
+    fn link(&mut self, a: usize, b: usize) -> Option<(usize, usize)> {
+        let root_a = self.find_root(a);
+        let root_b = self.find_root(b);
+        if root_a == root_b {
+            return None;
+        }
+        let (rank_a, rank_b) = (self.nodes.raw[root_a].rank, self.nodes.raw[root_b].rank);
+        let (root, child) = if rank_a < rank_b {
+            (root_b, root_a)
+        } else {
+            (root_a, root_b)
+        };
+        if rank_a == rank_b {
+            self.set_rank(root, rank_a + 1);
+        }
+        self.set_parent(child, root);
+        Some((root_a, root))
+    }
+
+    fn set_value_at(&mut self, index: usize, value: V) {
+        let old = mem::replace(&mut self.nodes.raw[index].value, value);
+        if self.open_snapshots != 0 {
+            self.undo_log.push(Undo::SetValue(index, old));
+        }
+    }
+
+    #[inline]
+    fn swap_values(&mut self, a: usize, b: usize) {
+        self.swap_values_unlogged(a, b);
+        self.log(|| Undo::SwapValues(a, b));
+    }
+
+    #[inline]
+    fn swap_values_unlogged(&mut self, a: usize, b: usize) {
+        let (low, high) = (a.min(b), a.max(b));
+        let (head, tail) = self.nodes.raw.split_at_mut(high);
+        mem::swap(&mut head[low].value, &mut tail[0].value);
+    }
+
+    #[inline]
+    fn log(&mut self, undo: impl FnOnce() -> Undo<V>) {
+        if self.open_snapshots != 0 {
+            self.undo_log.push(undo());
+        }
+    }
+
+    fn close_snapshot(&mut self, snapshot: &TiUnionFindSnapshot<K>) {
+        if snapshot.depth != self.open_snapshots || snapshot.undo_len > self.undo_log.len() {
+            snapshot_not_open(snapshot.depth, self.open_snapshots);
+        }
+        self.open_snapshots -= 1;
+    }
+}
+
+impl<K> fmt::Debug for TiUnionFindSnapshot<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TiUnionFindSnapshot")
+            .field("undo_len", &self.undo_len)
+            .field("depth", &self.depth)
+            .finish()
+    }
+}
+
+impl<K, V> fmt::Debug for TiUnionFind<K, V>
+where
+    K: fmt::Debug + TiKey,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.nodes
+                    .iter_enumerated()
+                    .map(|(key, node)| (key, K::from_usize(node.parent))),
+            )
+            .finish()
+    }
+}
+
+impl<K, V> Clone for TiUnionFind<K, V>
+where
+    V: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            nodes: self.nodes.clone(),
+            undo_log: self.undo_log.clone(),
+            open_snapshots: self.open_snapshots,
+        }
+    }
+}
+
+impl<K, V> Default for TiUnionFind<K, V> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::Id;
+    use crate::TiUnionFind;
+    use alloc::{format, vec, vec::Vec};
+
+    #[test]
+    fn union_find_and_classes() {
+        let mut sets: TiUnionFind<Id> = TiUnionFind::with_capacity(6);
+        let keys: Vec<Id> = (0..6).map(|_| sets.push(())).collect();
+        assert!(sets.union(keys[4], keys[2]));
+        assert!(sets.union(keys[2], keys[0]));
+        assert!(!sets.union(keys[0], keys[4]));
+        assert!(sets.union(keys[5], keys[3]));
+        assert!(sets.same(keys[0], keys[4]));
+        assert!(!sets.same(keys[0], keys[1]));
+        assert_eq!(sets.find(keys[0]), sets.find(keys[2]));
+        let classes = sets.classes::<Id>();
+        assert_eq!(
+            classes.raw,
+            [
+                vec![Id::from(0), Id::from(2), Id::from(4)],
+                vec![Id::from(1)],
+                vec![Id::from(3), Id::from(5)],
+            ]
+        );
+        assert_eq!(format!("{:?}", TiUnionFind::<Id>::new()), "{}");
+    }
+
+    #[test]
+    fn values() {
+        let mut sets: TiUnionFind<Id, Vec<u32>> = TiUnionFind::new();
+        let keys: Vec<Id> = (0..4).map(|value| sets.push(vec![value])).collect();
+        assert!(sets.union_with(keys[1], keys[0], |a, b| [&a[..], &b[..]].concat()));
+        assert_eq!(sets.value(keys[0]), &[1, 0]);
+        assert!(sets.union(keys[2], keys[1]));
+        assert_eq!(sets.value(keys[0]), &[2]);
+        assert!(sets.union(keys[1], keys[3]));
+        assert_eq!(sets.value(keys[3]), &[2]);
+        sets.set_value(keys[0], vec![7]);
+        assert_eq!(sets.value(keys[2]), &[7]);
+    }
+
+    #[test]
+    fn snapshots() {
+        let mut sets: TiUnionFind<Id, u32> = TiUnionFind::new();
+        let keys: Vec<Id> = (0..8).map(|value| sets.push(value)).collect();
+        assert!(sets.union(keys[0], keys[1]));
+        let outer = sets.snapshot();
+        assert!(sets.union_with(keys[1], keys[2], |a, b| a + b));
+        let inner = sets.snapshot();
+        let extra = sets.push(100);
+        for &key in &keys[3..] {
+            assert!(sets.union_with(key, extra, |a, b| a.max(b) + 1));
+        }
+        assert!(sets.same(keys[7], keys[3]));
+        sets.rollback_to(inner);
+        assert_eq!(sets.len(), 8);
+        assert!(!sets.same(keys[3], keys[4]));
+        assert_eq!(*sets.value(keys[4]), 4);
+        assert_eq!(*sets.value(keys[0]), 2);
+
+        let inner = sets.snapshot();
+        sets.set_value(keys[5], 50);
+        assert!(sets.union(keys[6], keys[5]));
+        sets.commit(inner);
+        assert_eq!(*sets.value(keys[5]), 6);
+        sets.rollback_to(outer);
+        assert!(sets.same(keys[0], keys[1]));
+        assert!(!sets.same(keys[1], keys[2]));
+        assert!(!sets.same(keys[5], keys[6]));
+        assert_eq!(*sets.value(keys[5]), 5);
+        assert_eq!(*sets.value(keys[1]), 0);
+
+        let snapshot = sets.snapshot();
+        assert!(sets.union(keys[2], keys[3]));
+        sets.commit(snapshot);
+        assert!(sets.same(keys[2], keys[3]));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "union-find snapshot at depth 1 is not the most recent open snapshot, 2 snapshots are open"
+    )]
+    fn rollback_out_of_order() {
+        let mut sets: TiUnionFind<Id, u32> = TiUnionFind::new();
+        let outer = sets.snapshot();
+        let _inner = sets.snapshot();
+        sets.rollback_to(outer);
+    }
+}
@@ -19,7 +19,8 @@ use serde::ser::{Serialize, Serializer};
 #[cfg(any(feature = "serde-alloc", feature = "serde-std"))]
 use serde::de::{Deserialize, Deserializer};
 
-use crate::{TiEnumerated, TiRangeBounds, TiSlice};
+use crate::key::assert_keys_in_range;
+use crate::{TiEnumerated, TiKey, TiRangeBounds, TiSlice};
 
 /// A contiguous growable array type
 /// that only accepts keys of the type `K`.
@@ -29,7 +30,8 @@ use crate::{TiEnumerated, TiRangeBounds, TiSlice};
 /// and forwards to it as much as possible.
 ///
 /// `TiVec<K, V>` uses `K` instead of `usize` for element indices and
-/// require the index to implement
+/// require the index to implement [`TiKey`] trait.
+/// It is implemented for all types implementing
 /// [`From<usize>`][`From`] and [`Into<usize>`][`Into`] traits.
 /// Their implementation can be easily done
 /// with [`derive_more`] crate and `#[derive(From, Into)]`.
@@ -79,6 +81,7 @@ use crate::{TiEnumerated, TiRangeBounds, TiSlice};
 /// [`pop_key_value`]: #method.pop_key_value
 /// [`drain_enumerated`]: #method.drain_enumerated
 /// [`into_iter_enumerated`]: #method.into_iter_enumerated
+/// [`TiKey`]: trait.TiKey.html
 /// [`std::vec::Vec`]: https://doc.rust-lang.org/std/vec/struct.Vec.html
 /// [`From`]: https://doc.rust-lang.org/std/convert/trait.From.html
 /// [`Into`]: https://doc.rust-lang.org/std/convert/trait.Into.html
@@ -310,9 +313,9 @@ impl<K, V> TiVec<K, V> {
     #[inline]
     pub fn swap_remove(&mut self, index: K) -> V
     where
-        usize: From<K>,
+        K: TiKey,
     {
-        self.raw.swap_remove(index.into())
+        self.raw.swap_remove(index.to_usize())
     }
 
     /// Inserts an element at position `index` within the vector, shifting all
@@ -323,9 +326,9 @@ impl<K, V> TiVec<K, V> {
     /// [`Vec::insert`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.insert
     pub fn insert(&mut self, index: K, element: V)
     where
-        usize: From<K>,
+        K: TiKey,
     {
-        self.raw.insert(index.into(), element)
+        self.raw.insert(index.to_usize(), element)
     }
 
     /// Removes and returns the element at position `index` within the vector,
@@ -336,9 +339,9 @@ impl<K, V> TiVec<K, V> {
     /// [`Vec::remove`]: https://doc.rust-lang.org/std/vec/struct.Vec.html#method.remove
     pub fn remove(&mut self, index: K) -> V
     where
-        usize: From<K>,
+        K: TiKey,
     {
-        self.raw.remove(index.into())
+        self.raw.remove(index.to_usize())
     }
 
     /// Retains only the elements specified by the predicate.
@@ -415,7 +418,7 @@ impl<K, V> TiVec<K, V> {
     #[inline]
     pub fn push_and_get_key(&mut self, value: V) -> K
     where
-        K: From<usize>,
+        K: TiKey,
     {
         let key = self.next_key();
         self.raw.push(value);
@@ -458,9 +461,11 @@ impl<K, V> TiVec<K, V> {
     #[inline]
     pub fn pop_key_value(&mut self) -> Option<(K, V)>
     where
-        K: From<usize>,
+        K: TiKey,
     {
-        self.raw.pop().map(|value| (self.raw.len().into(), value))
+        self.raw
+            .pop()
+            .map(|value| (K::from_usize(self.raw.len()), value))
     }
 
     /// Moves all the elements of `other` into `Self`, leaving `other` empty.
@@ -518,13 +523,14 @@ impl<K, V> TiVec<K, V> {
     #[inline]
     pub fn drain_enumerated<R>(&mut self, range: R) -> TiEnumerated<Drain<'_, V>, K, V>
     where
-        K: From<usize>,
+        K: TiKey,
         R: TiRangeBounds<K>,
     {
+        assert_keys_in_range::<K>(self.len());
         self.raw
             .drain(range.into_range())
             .enumerate()
-            .map(|(key, value)| (key.into(), value))
+            .map(|(key, value)| (K::from_usize(key), value))
     }
 
     /// Clears the vector, removing all values.
@@ -566,9 +572,9 @@ impl<K, V> TiVec<K, V> {
     #[must_use = "use `.truncate()` if you don't need the other half"]
     pub fn split_off(&mut self, at: K) -> Self
     where
-        usize: From<K>,
+        K: TiKey,
     {
-        self.raw.split_off(at.into()).into()
+        self.raw.split_off(at.to_usize()).into()
     }
 
     /// Resizes the `TiVec` in-place so that `len` is equal to `new_len`.
@@ -661,18 +667,19 @@ impl<K, V> TiVec<K, V> {
     #[inline(always)]
     pub fn into_iter_enumerated(self) -> TiEnumerated<vec::IntoIter<V>, K, V>
     where
-        K: From<usize>,
+        K: TiKey,
     {
+        assert_keys_in_range::<K>(self.len());
         self.raw
             .into_iter()
             .enumerate()
-            .map(|(key, value)| (key.into(), value))
+            .map(|(key, value)| (K::from_usize(key), value))
     }
 }
 
 impl<K, V> fmt::Debug for TiVec<K, V>
 where
-    K: fmt::Debug + From<usize>,
+    K: fmt::Debug + TiKey,
     V: fmt::Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -928,6 +935,7 @@ impl<'de, K, V: Deserialize<'de>> Deserialize<'de> for TiVec<K, V> {
 }
 
 #[cfg(test)]
+#[allow(clippy::unnecessary_to_owned)]
 mod test {
     use crate::test::Id;
 
@@ -1019,7 +1027,8 @@ mod test {
                 vec.reserve(NUM_ADDED_ITEMS);
                 unsafe {
                     for j in 0..NUM_ADDED_ITEMS {
-                        *vec.as_mut_slice().into_t().get_unchecked_mut(initial_len + j) = j;
+                        // Spare capacity is past the slice end, so it is written through a pointer.
+                        vec.as_mut_ptr().add(initial_len + j).write(j);
                     }
                     vec.set_len(initial_len + NUM_ADDED_ITEMS);
                 }
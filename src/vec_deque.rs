@@ -0,0 +1,1188 @@
+use core::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::FromIterator,
+    marker::PhantomData,
+    ops,
+};
+
+use alloc::collections::vec_deque::{self, Drain, Iter, IterMut, VecDeque};
+
+#[cfg(feature = "serde")]
+use serde::ser::{Serialize, Serializer};
+
+#[cfg(any(feature = "serde-alloc", feature = "serde-std"))]
+use serde::de::{Deserialize, Deserializer};
+
+use crate::key::{assert_keys_in_range, key_from_len};
+use crate::{TiEnumerated, TiKey, TiRangeBounds, TiSlice, TiSliceKeys, TiVec};
+
+/// A double-ended queue implemented with a growable ring buffer
+/// that only accepts keys of the type `K`.
+///
+/// `TiVecDeque<K, V>` is a wrapper around Rust container type [`std::collections::VecDeque`].
+/// The struct mirrors the stable API of Rust [`std::collections::VecDeque`]
+/// and forwards to it as much as possible.
+///
+/// `TiVecDeque<K, V>` uses `K` instead of `usize` for element positions
+/// counted from the front of the queue and
+/// require the index to implement [`TiKey`] trait.
+///
+/// `TiVecDeque<K, V>` can be converted to
+/// [`std::collections::VecDeque<V>`][`std::collections::VecDeque`] and back
+/// using [`From`] and [`Into`].
+///
+/// Added methods:
+/// - [`from_ref`] - Converts a
+///   [`&std::collections::VecDeque<V>`][`std::collections::VecDeque`]
+///   into a `&TiVecDeque<K, V>`.
+/// - [`from_mut`] - Converts a
+///   [`&mut std::collections::VecDeque<V>`][`std::collections::VecDeque`]
+///   into a `&mut TiVecDeque<K, V>`.
+/// - [`next_key`] - Returns the key of the element pushed to the back.
+/// - [`keys`] - Returns an iterator over all keys.
+/// - [`iter_enumerated`] - Returns an iterator over all key-value pairs.
+/// - [`iter_mut_enumerated`] - Returns an iterator over all key-value pairs,
+///   with mutable references to the values.
+/// - [`push_back_and_get_key`] - Appends an element to the back of the queue
+///   and returns its index of type `K`.
+/// - [`pop_back_key_value`] - Removes the last element from the queue and returns it
+///   with its index of type `K`, or [`None`] if the queue is empty.
+/// - [`drain_enumerated`] - Creates a draining iterator that removes the specified
+///   range in the queue and yields the current count and the removed items.
+/// - [`into_iter_enumerated`] - Converts the queue into iterator over all key-value pairs
+///   with `K` used for iteration indices.
+///
+/// # Example
+///
+/// ```
+/// use typed_index_collections::{TiSlice, TiVecDeque};
+/// use derive_more::{From, Into};
+///
+/// #[derive(Debug, Eq, From, Into, PartialEq)]
+/// struct Pos(usize);
+///
+/// let mut frontier: TiVecDeque<Pos, char> = TiVecDeque::new();
+/// frontier.push_back('b');
+/// frontier.push_front('a');
+/// assert_eq!(frontier.push_back_and_get_key('c'), Pos(2));
+/// assert_eq!(frontier[Pos(1)], 'b');
+/// assert_eq!(frontier.pop_front(), Some('a'));
+/// assert_eq!(frontier.make_contiguous(), TiSlice::from_ref(&['b', 'c']));
+/// ```
+///
+/// [`from_ref`]: #method.from_ref
+/// [`from_mut`]: #method.from_mut
+/// [`next_key`]: #method.next_key
+/// [`keys`]: #method.keys
+/// [`iter_enumerated`]: #method.iter_enumerated
+/// [`iter_mut_enumerated`]: #method.iter_mut_enumerated
+/// [`push_back_and_get_key`]: #method.push_back_and_get_key
+/// [`pop_back_key_value`]: #method.pop_back_key_value
+/// [`drain_enumerated`]: #method.drain_enumerated
+/// [`into_iter_enumerated`]: #method.into_iter_enumerated
+/// [`TiKey`]: trait.TiKey.html
+/// [`std::collections::VecDeque`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html
+/// [`From`]: https://doc.rust-lang.org/std/convert/trait.From.html
+/// [`Into`]: https://doc.rust-lang.org/std/convert/trait.Into.html
+#[repr(transparent)]
+pub struct TiVecDeque<K, V> {
+    /// Raw queue property
+    pub raw: VecDeque<V>,
+
+    /// Ties the container to the key type `K`.
+    _marker: PhantomData<fn(K) -> K>,
+}
+
+impl<K, V> TiVecDeque<K, V> {
+    /// Creates an empty `TiVecDeque<K, V>`.
+    ///
+    /// See [`VecDeque::new`] for more details.
+    ///
+    /// [`VecDeque::new`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.new
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            raw: VecDeque::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates an empty `TiVecDeque<K, V>` with space for at least `capacity` elements.
+    ///
+    /// See [`VecDeque::with_capacity`] for more details.
+    ///
+    /// [`VecDeque::with_capacity`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.with_capacity
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            raw: VecDeque::with_capacity(capacity),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Converts a [`&std::collections::VecDeque<V>`] into a `&TiVecDeque<K, V>`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::collections::VecDeque;
+    /// # use typed_index_collections::TiVecDeque;
+    /// pub struct Id(usize);
+    /// let deque: &TiVecDeque<Id, usize> = TiVecDeque::from_ref(&VecDeque::from(vec![1, 2, 4]));
+    /// ```
+    ///
+    /// [`&std::collections::VecDeque<V>`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html
+    #[allow(trivial_casts)]
+    #[inline]
+    pub fn from_ref(raw: &VecDeque<V>) -> &Self {
+        unsafe { &*(raw as *const VecDeque<V> as *const Self) }
+    }
+
+    /// Converts a [`&mut std::collections::VecDeque<V>`] into a `&mut TiVecDeque<K, V>`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::collections::VecDeque;
+    /// # use typed_index_collections::TiVecDeque;
+    /// pub struct Id(usize);
+    /// let mut raw = VecDeque::from(vec![1, 2, 4]);
+    /// let deque: &mut TiVecDeque<Id, usize> = TiVecDeque::from_mut(&mut raw);
+    /// ```
+    ///
+    /// [`&mut std::collections::VecDeque<V>`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html
+    #[allow(trivial_casts)]
+    #[inline]
+    pub fn from_mut(raw: &mut VecDeque<V>) -> &mut Self {
+        unsafe { &mut *(raw as *mut VecDeque<V> as *mut Self) }
+    }
+
+    /// Provides a reference to the element at the given index.
+    ///
+    /// See [`VecDeque::get`] for more details.
+    ///
+    /// [`VecDeque::get`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.get
+    #[inline]
+    pub fn get(&self, index: K) -> Option<&V>
+    where
+        K: TiKey,
+    {
+        self.raw.get(index.to_usize())
+    }
+
+    /// Provides a mutable reference to the element at the given index.
+    ///
+    /// See [`VecDeque::get_mut`] for more details.
+    ///
+    /// [`VecDeque::get_mut`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.get_mut
+    #[inline]
+    pub fn get_mut(&mut self, index: K) -> Option<&mut V>
+    where
+        K: TiKey,
+    {
+        self.raw.get_mut(index.to_usize())
+    }
+
+    /// Swaps elements at indices `a` and `b`.
+    ///
+    /// See [`VecDeque::swap`] for more details.
+    ///
+    /// [`VecDeque::swap`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.swap
+    #[inline]
+    pub fn swap(&mut self, a: K, b: K)
+    where
+        K: TiKey,
+    {
+        self.raw.swap(a.to_usize(), b.to_usize())
+    }
+
+    /// Returns the number of elements the queue can hold without reallocating.
+    ///
+    /// See [`VecDeque::capacity`] for more details.
+    ///
+    /// [`VecDeque::capacity`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.capacity
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.raw.capacity()
+    }
+
+    /// Reserves the minimum capacity for at least `additional` more elements.
+    ///
+    /// See [`VecDeque::reserve_exact`] for more details.
+    ///
+    /// [`VecDeque::reserve_exact`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.reserve_exact
+    #[inline]
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.raw.reserve_exact(additional)
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    ///
+    /// See [`VecDeque::reserve`] for more details.
+    ///
+    /// [`VecDeque::reserve`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.reserve
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.raw.reserve(additional)
+    }
+
+    /// Shrinks the capacity of the queue as much as possible.
+    ///
+    /// See [`VecDeque::shrink_to_fit`] for more details.
+    ///
+    /// [`VecDeque::shrink_to_fit`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.shrink_to_fit
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.raw.shrink_to_fit()
+    }
+
+    /// Shortens the queue, keeping the first `len` elements and dropping the rest.
+    ///
+    /// See [`VecDeque::truncate`] for more details.
+    ///
+    /// [`VecDeque::truncate`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.truncate
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.raw.truncate(len)
+    }
+
+    /// Returns a front-to-back iterator.
+    ///
+    /// See [`VecDeque::iter`] for more details.
+    ///
+    /// [`VecDeque::iter`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.iter
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, V> {
+        self.raw.iter()
+    }
+
+    /// Returns a front-to-back iterator that returns mutable references.
+    ///
+    /// See [`VecDeque::iter_mut`] for more details.
+    ///
+    /// [`VecDeque::iter_mut`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.iter_mut
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, V> {
+        self.raw.iter_mut()
+    }
+
+    /// Returns a front-to-back iterator over all key-value pairs.
+    ///
+    /// It acts like `self.iter().enumerate()`,
+    /// but use `K` instead of `usize` for iteration indices.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiVecDeque;
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut deque: TiVecDeque<Id, usize> = vec![2, 4].into_iter().collect();
+    /// deque.push_front(1);
+    /// let mut iterator = deque.iter_enumerated();
+    /// assert_eq!(iterator.next(), Some((Id(0), &1)));
+    /// assert_eq!(iterator.next(), Some((Id(1), &2)));
+    /// assert_eq!(iterator.next(), Some((Id(2), &4)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    #[inline]
+    pub fn iter_enumerated(&self) -> TiEnumerated<Iter<'_, V>, K, &V>
+    where
+        K: TiKey,
+    {
+        assert_keys_in_range::<K>(self.len());
+        self.raw
+            .iter()
+            .enumerate()
+            .map(|(key, value)| (K::from_usize(key), value))
+    }
+
+    /// Returns a front-to-back iterator over all key-value pairs,
+    /// with mutable references to the values.
+    ///
+    /// It acts like `self.iter_mut().enumerate()`,
+    /// but use `K` instead of `usize` for iteration indices.
+    #[inline]
+    pub fn iter_mut_enumerated(&mut self) -> TiEnumerated<IterMut<'_, V>, K, &mut V>
+    where
+        K: TiKey,
+    {
+        assert_keys_in_range::<K>(self.len());
+        self.raw
+            .iter_mut()
+            .enumerate()
+            .map(|(key, value)| (K::from_usize(key), value))
+    }
+
+    /// Returns an iterator over all keys.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiVecDeque;
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let deque: TiVecDeque<Id, usize> = vec![1, 2].into_iter().collect();
+    /// let mut iterator = deque.keys();
+    /// assert_eq!(iterator.next(), Some(Id(0)));
+    /// assert_eq!(iterator.next(), Some(Id(1)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    #[inline]
+    pub fn keys(&self) -> TiSliceKeys<K>
+    where
+        K: TiKey,
+    {
+        assert_keys_in_range::<K>(self.len());
+        (0..self.len()).map(K::from_usize)
+    }
+
+    /// Returns the index for the element pushed to the back of the queue.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the queue length can not be represented by the key type.
+    #[inline]
+    pub fn next_key(&self) -> K
+    where
+        K: TiKey,
+    {
+        key_from_len(self.len())
+    }
+
+    /// Returns a pair of slices which contain, in order, the contents of the queue.
+    ///
+    /// Note that keys of the second slice start from `K::from_usize(0)`
+    /// and do not match positions in the queue.
+    /// Use [`make_contiguous`] to get a single slice with matching keys.
+    ///
+    /// See [`VecDeque::as_slices`] for more details.
+    ///
+    /// [`make_contiguous`]: #method.make_contiguous
+    /// [`VecDeque::as_slices`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.as_slices
+    #[inline]
+    pub fn as_slices(&self) -> (&TiSlice<K, V>, &TiSlice<K, V>) {
+        let (front, back) = self.raw.as_slices();
+        (TiSlice::from_ref(front), TiSlice::from_ref(back))
+    }
+
+    /// Returns a pair of mutable slices which contain, in order, the contents of the queue.
+    ///
+    /// See [`VecDeque::as_mut_slices`] for more details.
+    ///
+    /// [`VecDeque::as_mut_slices`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.as_mut_slices
+    #[inline]
+    pub fn as_mut_slices(&mut self) -> (&mut TiSlice<K, V>, &mut TiSlice<K, V>) {
+        let (front, back) = self.raw.as_mut_slices();
+        (TiSlice::from_mut(front), TiSlice::from_mut(back))
+    }
+
+    /// Returns the number of elements in the queue.
+    ///
+    /// See [`VecDeque::len`] for more details.
+    ///
+    /// [`VecDeque::len`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.len
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.raw.len()
+    }
+
+    /// Returns `true` if the queue is empty.
+    ///
+    /// See [`VecDeque::is_empty`] for more details.
+    ///
+    /// [`VecDeque::is_empty`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.is_empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    /// Creates an iterator that covers the specified range in the queue.
+    ///
+    /// See [`VecDeque::range`] for more details.
+    ///
+    /// [`VecDeque::range`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.range
+    #[inline]
+    pub fn range<R>(&self, range: R) -> Iter<'_, V>
+    where
+        R: TiRangeBounds<K>,
+    {
+        self.raw.range(range.into_range())
+    }
+
+    /// Creates an iterator that covers the specified mutable range in the queue.
+    ///
+    /// See [`VecDeque::range_mut`] for more details.
+    ///
+    /// [`VecDeque::range_mut`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.range_mut
+    #[inline]
+    pub fn range_mut<R>(&mut self, range: R) -> IterMut<'_, V>
+    where
+        R: TiRangeBounds<K>,
+    {
+        self.raw.range_mut(range.into_range())
+    }
+
+    /// Creates a draining iterator that removes the specified range in the queue
+    /// and yields the removed items.
+    ///
+    /// See [`VecDeque::drain`] for more details.
+    ///
+    /// [`VecDeque::drain`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.drain
+    #[inline]
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, V>
+    where
+        R: TiRangeBounds<K>,
+    {
+        self.raw.drain(range.into_range())
+    }
+
+    /// Creates a draining iterator that removes the specified
+    /// range in the queue and yields the current count and the removed items.
+    ///
+    /// It acts like `self.drain(range).enumerate()`,
+    /// but instead of `usize` it returns index of type `K`.
+    ///
+    /// Note that the indices started from `K::from_usize(0)`,
+    /// regardless of the range starting point.
+    ///
+    /// See [`VecDeque::drain`] for more details.
+    ///
+    /// [`VecDeque::drain`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.drain
+    #[inline]
+    pub fn drain_enumerated<R>(&mut self, range: R) -> TiEnumerated<Drain<'_, V>, K, V>
+    where
+        K: TiKey,
+        R: TiRangeBounds<K>,
+    {
+        self.raw
+            .drain(range.into_range())
+            .enumerate()
+            .map(|(key, value)| (K::from_usize(key), value))
+    }
+
+    /// Clears the queue, removing all values.
+    ///
+    /// See [`VecDeque::clear`] for more details.
+    ///
+    /// [`VecDeque::clear`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.clear
+    #[inline]
+    pub fn clear(&mut self) {
+        self.raw.clear()
+    }
+
+    /// Returns `true` if the queue contains an element equal to the given value.
+    ///
+    /// See [`VecDeque::contains`] for more details.
+    ///
+    /// [`VecDeque::contains`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.contains
+    #[inline]
+    pub fn contains(&self, x: &V) -> bool
+    where
+        V: PartialEq,
+    {
+        self.raw.contains(x)
+    }
+
+    /// Provides a reference to the front element, or [`None`] if the queue is empty.
+    ///
+    /// See [`VecDeque::front`] for more details.
+    ///
+    /// [`VecDeque::front`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.front
+    #[inline]
+    pub fn front(&self) -> Option<&V> {
+        self.raw.front()
+    }
+
+    /// Provides a mutable reference to the front element, or [`None`] if the queue is empty.
+    ///
+    /// See [`VecDeque::front_mut`] for more details.
+    ///
+    /// [`VecDeque::front_mut`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.front_mut
+    #[inline]
+    pub fn front_mut(&mut self) -> Option<&mut V> {
+        self.raw.front_mut()
+    }
+
+    /// Provides a reference to the back element, or [`None`] if the queue is empty.
+    ///
+    /// See [`VecDeque::back`] for more details.
+    ///
+    /// [`VecDeque::back`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.back
+    #[inline]
+    pub fn back(&self) -> Option<&V> {
+        self.raw.back()
+    }
+
+    /// Provides a mutable reference to the back element, or [`None`] if the queue is empty.
+    ///
+    /// See [`VecDeque::back_mut`] for more details.
+    ///
+    /// [`VecDeque::back_mut`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.back_mut
+    #[inline]
+    pub fn back_mut(&mut self) -> Option<&mut V> {
+        self.raw.back_mut()
+    }
+
+    /// Removes the first element and returns it, or [`None`] if the queue is empty.
+    ///
+    /// See [`VecDeque::pop_front`] for more details.
+    ///
+    /// [`VecDeque::pop_front`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.pop_front
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<V> {
+        self.raw.pop_front()
+    }
+
+    /// Removes the last element and returns it, or [`None`] if the queue is empty.
+    ///
+    /// See [`VecDeque::pop_back`] for more details.
+    ///
+    /// [`VecDeque::pop_back`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.pop_back
+    #[inline]
+    pub fn pop_back(&mut self) -> Option<V> {
+        self.raw.pop_back()
+    }
+
+    /// Removes the last element from the queue and returns it with
+    /// its index of type `K`, or [`None`] if the queue is empty.
+    ///
+    /// See [`VecDeque::pop_back`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiVecDeque;
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut deque: TiVecDeque<Id, usize> = vec![1, 2, 4].into_iter().collect();
+    /// assert_eq!(deque.pop_back_key_value(), Some((Id(2), 4)));
+    /// assert_eq!(deque.pop_back_key_value(), Some((Id(1), 2)));
+    /// ```
+    ///
+    /// [`VecDeque::pop_back`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.pop_back
+    #[inline]
+    pub fn pop_back_key_value(&mut self) -> Option<(K, V)>
+    where
+        K: TiKey,
+    {
+        self.raw
+            .pop_back()
+            .map(|value| (K::from_usize(self.raw.len()), value))
+    }
+
+    /// Prepends an element to the queue.
+    ///
+    /// Note that keys of all elements in the queue are shifted by one.
+    ///
+    /// See [`VecDeque::push_front`] for more details.
+    ///
+    /// [`VecDeque::push_front`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.push_front
+    #[inline]
+    pub fn push_front(&mut self, value: V) {
+        self.raw.push_front(value)
+    }
+
+    /// Appends an element to the back of the queue.
+    ///
+    /// See [`VecDeque::push_back`] for more details.
+    ///
+    /// [`VecDeque::push_back`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.push_back
+    #[inline]
+    pub fn push_back(&mut self, value: V) {
+        self.raw.push_back(value)
+    }
+
+    /// Appends an element to the back of the queue and returns its index of type `K`.
+    ///
+    /// It acts like `{ deque.push_back(...); deque.next_key() - 1 }`.
+    ///
+    /// See [`VecDeque::push_back`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiVecDeque;
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let mut deque: TiVecDeque<Id, usize> = TiVecDeque::new();
+    /// assert_eq!(deque.push_back_and_get_key(8), Id(0));
+    /// assert_eq!(deque.push_back_and_get_key(16), Id(1));
+    /// ```
+    ///
+    /// [`VecDeque::push_back`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.push_back
+    #[inline]
+    pub fn push_back_and_get_key(&mut self, value: V) -> K
+    where
+        K: TiKey,
+    {
+        let key = self.next_key();
+        self.raw.push_back(value);
+        key
+    }
+
+    /// Removes an element from anywhere in the queue and returns it,
+    /// replacing it with the last element.
+    ///
+    /// See [`VecDeque::swap_remove_back`] for more details.
+    ///
+    /// [`VecDeque::swap_remove_back`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.swap_remove_back
+    #[inline]
+    pub fn swap_remove_back(&mut self, index: K) -> Option<V>
+    where
+        K: TiKey,
+    {
+        self.raw.swap_remove_back(index.to_usize())
+    }
+
+    /// Removes an element from anywhere in the queue and returns it,
+    /// replacing it with the first element.
+    ///
+    /// See [`VecDeque::swap_remove_front`] for more details.
+    ///
+    /// [`VecDeque::swap_remove_front`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.swap_remove_front
+    #[inline]
+    pub fn swap_remove_front(&mut self, index: K) -> Option<V>
+    where
+        K: TiKey,
+    {
+        self.raw.swap_remove_front(index.to_usize())
+    }
+
+    /// Inserts an element at `index` within the queue,
+    /// shifting all elements with indices greater than or equal to `index` towards the back.
+    ///
+    /// See [`VecDeque::insert`] for more details.
+    ///
+    /// [`VecDeque::insert`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.insert
+    #[inline]
+    pub fn insert(&mut self, index: K, value: V)
+    where
+        K: TiKey,
+    {
+        self.raw.insert(index.to_usize(), value)
+    }
+
+    /// Removes and returns the element at `index` from the queue.
+    ///
+    /// See [`VecDeque::remove`] for more details.
+    ///
+    /// [`VecDeque::remove`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.remove
+    #[inline]
+    pub fn remove(&mut self, index: K) -> Option<V>
+    where
+        K: TiKey,
+    {
+        self.raw.remove(index.to_usize())
+    }
+
+    /// Splits the queue into two at the given index.
+    ///
+    /// See [`VecDeque::split_off`] for more details.
+    ///
+    /// [`VecDeque::split_off`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.split_off
+    #[inline]
+    #[must_use = "use `.truncate()` if you don't need the other half"]
+    pub fn split_off(&mut self, at: K) -> Self
+    where
+        K: TiKey,
+    {
+        self.raw.split_off(at.to_usize()).into()
+    }
+
+    /// Moves all the elements of `other` into `self`, leaving `other` empty.
+    ///
+    /// See [`VecDeque::append`] for more details.
+    ///
+    /// [`VecDeque::append`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.append
+    #[inline]
+    pub fn append(&mut self, other: &mut Self) {
+        self.raw.append(&mut other.raw)
+    }
+
+    /// Retains only the elements specified by the predicate.
+    ///
+    /// See [`VecDeque::retain`] for more details.
+    ///
+    /// [`VecDeque::retain`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.retain
+    #[inline]
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&V) -> bool,
+    {
+        self.raw.retain(f)
+    }
+
+    /// Retains only the elements specified by the predicate,
+    /// passing a mutable reference to it.
+    ///
+    /// See [`VecDeque::retain_mut`] for more details.
+    ///
+    /// [`VecDeque::retain_mut`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.retain_mut
+    #[inline]
+    pub fn retain_mut<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut V) -> bool,
+    {
+        self.raw.retain_mut(f)
+    }
+
+    /// Modifies the queue in-place so that `len()` is equal to `new_len`.
+    ///
+    /// See [`VecDeque::resize_with`] for more details.
+    ///
+    /// [`VecDeque::resize_with`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.resize_with
+    #[inline]
+    pub fn resize_with<F>(&mut self, new_len: usize, generator: F)
+    where
+        F: FnMut() -> V,
+    {
+        self.raw.resize_with(new_len, generator)
+    }
+
+    /// Modifies the queue in-place so that `len()` is equal to `new_len`.
+    ///
+    /// See [`VecDeque::resize`] for more details.
+    ///
+    /// [`VecDeque::resize`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.resize
+    #[inline]
+    pub fn resize(&mut self, new_len: usize, value: V)
+    where
+        V: Clone,
+    {
+        self.raw.resize(new_len, value)
+    }
+
+    /// Rearranges the internal storage of the queue so it is one contiguous slice,
+    /// which is then returned.
+    ///
+    /// See [`VecDeque::make_contiguous`] for more details.
+    ///
+    /// [`VecDeque::make_contiguous`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.make_contiguous
+    #[inline]
+    pub fn make_contiguous(&mut self) -> &mut TiSlice<K, V> {
+        TiSlice::from_mut(self.raw.make_contiguous())
+    }
+
+    /// Rotates the queue `n` places to the left.
+    ///
+    /// See [`VecDeque::rotate_left`] for more details.
+    ///
+    /// [`VecDeque::rotate_left`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.rotate_left
+    #[inline]
+    pub fn rotate_left(&mut self, n: usize) {
+        self.raw.rotate_left(n)
+    }
+
+    /// Rotates the queue `n` places to the right.
+    ///
+    /// See [`VecDeque::rotate_right`] for more details.
+    ///
+    /// [`VecDeque::rotate_right`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.rotate_right
+    #[inline]
+    pub fn rotate_right(&mut self, n: usize) {
+        self.raw.rotate_right(n)
+    }
+
+    /// Binary searches the sorted queue for a given element.
+    ///
+    /// See [`VecDeque::binary_search`] for more details.
+    ///
+    /// [`VecDeque::binary_search`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.binary_search
+    #[inline]
+    pub fn binary_search(&self, x: &V) -> Result<K, K>
+    where
+        K: TiKey,
+        V: Ord,
+    {
+        self.raw
+            .binary_search(x)
+            .map(K::from_usize)
+            .map_err(key_from_len)
+    }
+
+    /// Binary searches the sorted queue with a comparator function.
+    ///
+    /// See [`VecDeque::binary_search_by`] for more details.
+    ///
+    /// [`VecDeque::binary_search_by`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.binary_search_by
+    #[inline]
+    pub fn binary_search_by<F>(&self, f: F) -> Result<K, K>
+    where
+        F: FnMut(&V) -> Ordering,
+        K: TiKey,
+    {
+        self.raw
+            .binary_search_by(f)
+            .map(K::from_usize)
+            .map_err(key_from_len)
+    }
+
+    /// Binary searches the sorted queue with a key extraction function.
+    ///
+    /// See [`VecDeque::binary_search_by_key`] for more details.
+    ///
+    /// [`VecDeque::binary_search_by_key`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.binary_search_by_key
+    #[inline]
+    pub fn binary_search_by_key<B, F>(&self, b: &B, f: F) -> Result<K, K>
+    where
+        F: FnMut(&V) -> B,
+        B: Ord,
+        K: TiKey,
+    {
+        self.raw
+            .binary_search_by_key(b, f)
+            .map(K::from_usize)
+            .map_err(key_from_len)
+    }
+
+    /// Returns the index of the partition point according to the given predicate.
+    ///
+    /// See [`VecDeque::partition_point`] for more details.
+    ///
+    /// [`VecDeque::partition_point`]: https://doc.rust-lang.org/std/collections/struct.VecDeque.html#method.partition_point
+    #[inline]
+    pub fn partition_point<P>(&self, pred: P) -> K
+    where
+        K: TiKey,
+        P: FnMut(&V) -> bool,
+    {
+        key_from_len(self.raw.partition_point(pred))
+    }
+
+    /// Converts the queue into iterator over all key-value pairs
+    /// with `K` used for iteration indices.
+    ///
+    /// It acts like `self.into_iter().enumerate()`,
+    /// but use `K` instead of `usize` for iteration indices.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use derive_more::{From, Into};
+    /// # use typed_index_collections::TiVecDeque;
+    /// #[derive(Eq, Debug, From, Into, PartialEq)]
+    /// pub struct Id(usize);
+    /// let deque: TiVecDeque<Id, usize> = vec![1, 2].into_iter().collect();
+    /// let mut iterator = deque.into_iter_enumerated();
+    /// assert_eq!(iterator.next(), Some((Id(0), 1)));
+    /// assert_eq!(iterator.next(), Some((Id(1), 2)));
+    /// assert_eq!(iterator.next(), None);
+    /// ```
+    #[inline]
+    pub fn into_iter_enumerated(self) -> TiEnumerated<vec_deque::IntoIter<V>, K, V>
+    where
+        K: TiKey,
+    {
+        assert_keys_in_range::<K>(self.len());
+        self.raw
+            .into_iter()
+            .enumerate()
+            .map(|(key, value)| (K::from_usize(key), value))
+    }
+}
+
+impl<K, V> fmt::Debug for TiVecDeque<K, V>
+where
+    K: fmt::Debug + TiKey,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter_enumerated()).finish()
+    }
+}
+
+impl<K, V> AsRef<VecDeque<V>> for TiVecDeque<K, V> {
+    fn as_ref(&self) -> &VecDeque<V> {
+        &self.raw
+    }
+}
+
+impl<K, V> AsMut<VecDeque<V>> for TiVecDeque<K, V> {
+    fn as_mut(&mut self) -> &mut VecDeque<V> {
+        &mut self.raw
+    }
+}
+
+impl<K, V> AsRef<TiVecDeque<K, V>> for VecDeque<V> {
+    fn as_ref(&self) -> &TiVecDeque<K, V> {
+        TiVecDeque::from_ref(self)
+    }
+}
+
+impl<K, V> AsMut<TiVecDeque<K, V>> for VecDeque<V> {
+    fn as_mut(&mut self) -> &mut TiVecDeque<K, V> {
+        TiVecDeque::from_mut(self)
+    }
+}
+
+impl<K, V> ops::Index<K> for TiVecDeque<K, V>
+where
+    K: TiKey,
+{
+    type Output = V;
+
+    #[inline]
+    fn index(&self, index: K) -> &V {
+        &self.raw[index.to_usize()]
+    }
+}
+
+impl<K, V> ops::IndexMut<K> for TiVecDeque<K, V>
+where
+    K: TiKey,
+{
+    #[inline]
+    fn index_mut(&mut self, index: K) -> &mut V {
+        &mut self.raw[index.to_usize()]
+    }
+}
+
+impl<K, V> Extend<V> for TiVecDeque<K, V> {
+    fn extend<I: IntoIterator<Item = V>>(&mut self, iter: I) {
+        self.raw.extend(iter)
+    }
+}
+
+impl<'a, K, V: 'a + Copy> Extend<&'a V> for TiVecDeque<K, V> {
+    fn extend<I: IntoIterator<Item = &'a V>>(&mut self, iter: I) {
+        self.raw.extend(iter)
+    }
+}
+
+impl<K, V> FromIterator<V> for TiVecDeque<K, V> {
+    fn from_iter<I: IntoIterator<Item = V>>(iter: I) -> Self {
+        Self {
+            raw: FromIterator::from_iter(iter),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K, A, B> PartialEq<TiVecDeque<K, B>> for TiVecDeque<K, A>
+where
+    A: PartialEq<B>,
+{
+    #[inline]
+    fn eq(&self, other: &TiVecDeque<K, B>) -> bool {
+        self.raw.len() == other.raw.len() && self.raw.iter().zip(&other.raw).all(|(a, b)| a == b)
+    }
+}
+
+impl<K, V> PartialOrd<TiVecDeque<K, V>> for TiVecDeque<K, V>
+where
+    V: PartialOrd<V>,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &TiVecDeque<K, V>) -> Option<Ordering> {
+        self.raw.partial_cmp(&other.raw)
+    }
+}
+
+impl<K, V> Clone for TiVecDeque<K, V>
+where
+    V: Clone,
+{
+    fn clone(&self) -> TiVecDeque<K, V> {
+        self.raw.clone().into()
+    }
+}
+
+impl<K, V> Default for TiVecDeque<K, V> {
+    #[inline]
+    fn default() -> Self {
+        VecDeque::default().into()
+    }
+}
+
+impl<K, V> Eq for TiVecDeque<K, V> where V: Eq {}
+
+impl<K, V> Hash for TiVecDeque<K, V>
+where
+    V: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.raw.hash(state)
+    }
+}
+
+impl<K, V> Ord for TiVecDeque<K, V>
+where
+    V: Ord,
+{
+    #[inline]
+    fn cmp(&self, other: &TiVecDeque<K, V>) -> Ordering {
+        self.raw.cmp(&other.raw)
+    }
+}
+
+impl<K, V> From<VecDeque<V>> for TiVecDeque<K, V> {
+    fn from(deque: VecDeque<V>) -> Self {
+        Self {
+            raw: deque,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> From<TiVecDeque<K, V>> for VecDeque<V> {
+    fn from(deque: TiVecDeque<K, V>) -> Self {
+        deque.raw
+    }
+}
+
+impl<K, V> From<TiVec<K, V>> for TiVecDeque<K, V> {
+    fn from(vec: TiVec<K, V>) -> Self {
+        VecDeque::from(vec.raw).into()
+    }
+}
+
+impl<K, V> From<TiVecDeque<K, V>> for TiVec<K, V> {
+    fn from(deque: TiVecDeque<K, V>) -> Self {
+        alloc::vec::Vec::from(deque.raw).into()
+    }
+}
+
+impl<K, V> IntoIterator for TiVecDeque<K, V> {
+    type Item = V;
+    type IntoIter = vec_deque::IntoIter<V>;
+
+    #[inline]
+    fn into_iter(self) -> vec_deque::IntoIter<V> {
+        self.raw.into_iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a TiVecDeque<K, V> {
+    type Item = &'a V;
+    type IntoIter = Iter<'a, V>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, V> {
+        self.raw.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut TiVecDeque<K, V> {
+    type Item = &'a mut V;
+    type IntoIter = IterMut<'a, V>;
+
+    #[inline]
+    fn into_iter(self) -> IterMut<'a, V> {
+        self.raw.iter_mut()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K, V: Serialize> Serialize for TiVecDeque<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(&self.raw)
+    }
+}
+
+#[cfg(any(feature = "serde-alloc", feature = "serde-std"))]
+impl<'de, K, V: Deserialize<'de>> Deserialize<'de> for TiVecDeque<K, V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        VecDeque::deserialize(deserializer).map(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test::{Id, SmallId};
+    use crate::{TiSlice, TiVec, TiVecDeque};
+    use alloc::{collections::VecDeque, format, vec, vec::Vec};
+
+    #[test]
+    fn typed_positions() {
+        let mut deque: TiVecDeque<Id, u32> = TiVecDeque::with_capacity(4);
+        assert_eq!(deque.push_back_and_get_key(2), Id::from(0));
+        assert_eq!(deque.push_back_and_get_key(3), Id::from(1));
+        deque.push_front(1);
+        assert_eq!(deque.next_key(), Id::from(3));
+        assert_eq!(deque[Id::from(0)], 1);
+        deque[Id::from(2)] = 4;
+        assert_eq!(deque.get(Id::from(2)), Some(&4));
+        assert_eq!(deque.get(Id::from(3)), None);
+        assert_eq!(deque.binary_search(&2), Ok(Id::from(1)));
+        assert_eq!(deque.partition_point(|&value| value < 3), Id::from(2));
+        assert_eq!(
+            deque.keys().collect::<Vec<_>>(),
+            [Id::from(0), Id::from(1), Id::from(2)]
+        );
+        assert_eq!(format!("{:?}", deque), "{Id(0): 1, Id(1): 2, Id(2): 4}");
+        assert_eq!(deque.pop_back_key_value(), Some((Id::from(2), 4)));
+        assert_eq!(deque.remove(Id::from(0)), Some(1));
+    }
+
+    #[test]
+    fn slices_and_ranges() {
+        let mut deque: TiVecDeque<Id, u32> = TiVecDeque::with_capacity(4);
+        deque.extend([3, 4].iter());
+        deque.push_front(2);
+        deque.push_front(1);
+        let (front, back) = deque.as_slices();
+        let mut joined = front.raw.to_vec();
+        joined.extend_from_slice(&back.raw);
+        assert_eq!(joined, [1, 2, 3, 4]);
+        assert_eq!(
+            deque.range(Id::from(1)..Id::from(3)).collect::<Vec<_>>(),
+            [&2, &3]
+        );
+        for value in deque.range_mut(Id::from(2)..) {
+            *value *= 10;
+        }
+        let drained: Vec<_> = deque.drain_enumerated(Id::from(2)..).collect();
+        assert_eq!(drained, [(Id::from(0), 30), (Id::from(1), 40)]);
+        deque.rotate_left(1);
+        assert_eq!(deque.make_contiguous(), TiSlice::from_ref(&[2, 1]));
+    }
+
+    #[test]
+    fn conversions() {
+        let raw: VecDeque<u32> = vec![1, 2].into();
+        assert_eq!(TiVecDeque::<Id, u32>::from_ref(&raw)[Id::from(1)], 2);
+        let deque: TiVecDeque<Id, u32> = raw.into();
+        let vec: TiVec<Id, u32> = deque.clone().into();
+        assert_eq!(vec.raw, [1, 2]);
+        assert_eq!(TiVecDeque::from(vec), deque);
+        let mut iter = deque.into_iter_enumerated();
+        assert_eq!(iter.next_back(), Some((Id::from(1), 2)));
+        assert_eq!(iter.next(), Some((Id::from(0), 1)));
+    }
+
+    #[test]
+    fn search_at_key_max() {
+        let deque: TiVecDeque<SmallId, u32> = (0..1).collect();
+        assert_eq!(deque.binary_search(&1), Err(SmallId(1)));
+        assert_eq!(
+            deque.binary_search_by(|value| value.cmp(&1)),
+            Err(SmallId(1))
+        );
+        assert_eq!(
+            deque.binary_search_by_key(&1, |&value| value),
+            Err(SmallId(1))
+        );
+        assert_eq!(deque.partition_point(|_| true), SmallId(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn search_past_key_max() {
+        let deque: TiVecDeque<SmallId, u32> = (0..2).collect();
+        let _ = deque.binary_search(&2);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn partition_point_past_key_max() {
+        let deque: TiVecDeque<SmallId, u32> = (0..2).collect();
+        let _ = deque.partition_point(|_| true);
+    }
+}
@@ -22,3 +22,32 @@ pub extern "C" fn slice_test() {
     let ti_slice: &TiSlice<K, usize> = TiSlice::from_ref(slice);
     let _ = ti_slice;
 }
+
+typed_index_collections::ti_index! {
+    struct BlockId(u32) {
+        const MAX = 0xFFFF_FF00;
+        const START = 0;
+        debug = "bb{}";
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn ti_index_test() {
+    use typed_index_collections::{TiKey, TiSlice};
+    let slice = &[1, 2, 3];
+    let ti_slice: &TiSlice<BlockId, usize> = TiSlice::from_ref(slice);
+    let _ = ti_slice[BlockId::START];
+    let _ = ti_slice.next_key().to_usize();
+    let _ = BlockId::checked_from_usize(BlockId::LAST.to_usize());
+}
+
+#[no_mangle]
+pub extern "C" fn array_vec_test() {
+    use typed_index_collections::TiArrayVec;
+    let mut vec: TiArrayVec<BlockId, usize, 4> = TiArrayVec::new();
+    let key = vec.push_and_get_key(1);
+    let _ = vec.try_push(2);
+    vec.retain(|&value| value > 1);
+    let _ = vec.drain_enumerated(key..).count();
+    let _ = vec.pop_key_value();
+}